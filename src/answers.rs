@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::params::Changes;
+use crate::Day;
+
+/// The checked-in store of known answers, relative to the crate root.
+pub const ANSWERS_FILE: &str = "answers.json";
+
+/// Known answers, keyed by day, then input name, then part.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Answers {
+    days: BTreeMap<u8, BTreeMap<String, BTreeMap<u8, String>>>,
+}
+
+impl Answers {
+    /// Loads the store, treating a missing file as an empty store.
+    pub fn load(file: &str) -> Result<Self> {
+        if !Path::new(file).exists() {
+            return Ok(Answers::default());
+        }
+        let json = std::fs::read_to_string(file).with_context(|| format!("reading {}", file))?;
+        serde_json::from_str(&json).with_context(|| format!("parsing {}", file))
+    }
+
+    pub fn save(&self, file: &str) -> Result<()> {
+        std::fs::write(file, serde_json::to_string_pretty(self)? + "\n").with_context(|| format!("writing {}", file))
+    }
+
+    pub fn get(&self, day: u8, input: &str, part: u8) -> Option<&str> {
+        self.days.get(&day)?.get(input)?.get(&part).map(String::as_str)
+    }
+
+    pub fn set(&mut self, day: u8, input: &str, part: u8, answer: &str) {
+        self.days.entry(day).or_default().entry(input.to_string()).or_default().insert(part, answer.to_string());
+    }
+
+    /// Compares an answer with the known one, if there is one.
+    pub fn check(&self, day: u8, input: &str, part: u8, actual: String) -> Outcome {
+        match self.get(day, input, part) {
+            None => Outcome::Missing { actual },
+            Some(expected) if expected == actual => Outcome::Pass,
+            Some(expected) => Outcome::Fail { expected: expected.to_string(), actual },
+        }
+    }
+
+    /// The inputs that have at least one known answer for a day.
+    pub fn inputs(&self, day: u8) -> Vec<&str> {
+        self.days.get(&day).map_or(vec![], |inputs| inputs.keys().map(String::as_str).collect())
+    }
+}
+
+/// How a solver's answer compared with the store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Fail { expected: String, actual: String },
+    Missing { actual: String },
+    Error(String),
+}
+
+/// The result of checking one part of one day against one input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub day: u8,
+    pub input: String,
+    pub part: u8,
+    pub outcome: Outcome,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Day {:02} part {} ({}): ", self.day, self.part, self.input)?;
+        match &self.outcome {
+            Outcome::Pass => write!(f, "pass"),
+            Outcome::Fail { expected, actual } => write!(f, "FAIL, expected {} but got {}", single_line(expected), single_line(actual)),
+            Outcome::Missing { actual } => write!(f, "missing, got {}", single_line(actual)),
+            Outcome::Error(e) => write!(f, "ERROR, {}", e),
+        }
+    }
+}
+
+/// Shows multi-line answers (such as rendered screens) on one line.
+pub fn single_line(answer: &str) -> String {
+    if answer.contains('\n') {
+        format!("{:?}", answer)
+    } else {
+        answer.to_string()
+    }
+}
+
+/// Runs both parts of a day on an input file, with some of its parameters changed, and checks the
+/// answers against the store.
+pub fn verify_day(day: &Day, input: &str, changes: &Changes, answers: &Answers) -> Vec<Check> {
+    let check = |part: u8, outcome: Outcome| Check { day: day.number, input: input.to_string(), part, outcome };
+    let solution = match day.load_with(input, changes) {
+        Ok(solution) => solution,
+        Err(e) => return [1, 2].map(|part| check(part, Outcome::Error(format!("{:#}", e)))).to_vec(),
+    };
+
+    [1, 2].map(|part| {
+        let outcome = match solution.part(part) {
+            Err(e) => Outcome::Error(format!("{:#}", e)),
+            Ok(actual) => answers.check(day.number, input, part, actual.to_string()),
+        };
+        check(part, outcome)
+    }).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::params::{Config, PARAMS_FILE};
+
+    use super::*;
+
+    #[test]
+    fn store() -> Result<()> {
+        let mut answers = Answers::default();
+        answers.set(7, "day07ex.txt", 1, "95437");
+        answers.set(7, "day07ex.txt", 2, "1");
+        let answers: Answers = serde_json::from_str(&serde_json::to_string(&answers)?)?;
+        assert_eq!(Some("95437"), answers.get(7, "day07ex.txt", 1));
+        assert_eq!(None, answers.get(7, "day07.txt", 1));
+        assert_eq!(vec!["day07ex.txt"], answers.inputs(7));
+
+        let checks = verify_day(crate::day(7).unwrap(), "day07ex.txt", &Changes::new(), &answers);
+        assert_eq!(Outcome::Pass, checks[0].outcome);
+        assert_eq!(Outcome::Fail { expected: "1".to_string(), actual: "24933642".to_string() }, checks[1].outcome);
+        let checks = verify_day(crate::day(7).unwrap(), "nope.txt", &Changes::new(), &answers);
+        assert!(matches!(checks[0].outcome, Outcome::Error(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn checked_in_examples() -> Result<()> {
+        let answers = Answers::load(ANSWERS_FILE)?;
+        let config = Config::load(PARAMS_FILE)?;
+        for day in crate::days() {
+            for input in answers.inputs(day.number).into_iter().filter(|i| i.contains("ex")) {
+                for check in verify_day(day, input, &config.for_input(day.number, input), &answers) {
+                    assert!(matches!(check.outcome, Outcome::Pass | Outcome::Missing { .. }), "{}", check);
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::metrics::{self, format_counters, Counters};
+use crate::params::Changes;
+use crate::Day;
+
+/// Where `status` looks for the last benchmark report, relative to the crate root, as written by
+/// `bench --json bench.json`.
+pub const BENCH_FILE: &str = "bench.json";
+
+/// The spread of a set of timed runs, in nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timing {
+    pub min_ns: u64,
+    pub median_ns: u64,
+    pub max_ns: u64,
+}
+
+impl Timing {
+    pub fn from_samples(samples: &mut [Duration]) -> Self {
+        samples.sort();
+        let nanos = |d: &Duration| d.as_nanos() as u64;
+        Timing {
+            min_ns: samples.first().map_or(0, nanos),
+            median_ns: samples.get(samples.len() / 2).map_or(0, nanos),
+            max_ns: samples.last().map_or(0, nanos),
+        }
+    }
+}
+
+/// Formats nanoseconds using the largest unit that keeps the number readable.
+pub fn format_ns(ns: u64) -> String {
+    if ns < 1_000 {
+        format!("{}ns", ns)
+    } else if ns < 1_000_000 {
+        format!("{:.1}µs", ns as f64 / 1e3)
+    } else if ns < 1_000_000_000 {
+        format!("{:.1}ms", ns as f64 / 1e6)
+    } else {
+        format!("{:.2}s", ns as f64 / 1e9)
+    }
+}
+
+/// Timings for one day: parsing the input and each part, measured separately, along with the work
+/// each part counted (see [`metrics`]).
+#[derive(Debug, Clone, Serialize)]
+pub struct DayBench {
+    pub day: u8,
+    pub input: String,
+    pub parse: Timing,
+    pub part_1: Timing,
+    pub part_2: Timing,
+    #[serde(skip_serializing_if = "Counters::is_empty")]
+    pub part_1_counters: Counters,
+    #[serde(skip_serializing_if = "Counters::is_empty")]
+    pub part_2_counters: Counters,
+}
+
+impl fmt::Display for DayBench {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let none = Counters::new();
+        for (stage, timing, counters) in [("parse", &self.parse, &none), ("part 1", &self.part_1, &self.part_1_counters), ("part 2", &self.part_2, &self.part_2_counters)] {
+            let row = format!("{:<5}{:<8}{:>12}{:>12}{:>12}  {}", format!("{:02}", self.day), stage, format_ns(timing.min_ns), format_ns(timing.median_ns), format_ns(timing.max_ns), format_counters(counters));
+            writeln!(f, "{}", row.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// The header line matching [`DayBench`]'s table rows.
+pub const TABLE_HEADER: &str = "Day  Stage            Min      Median         Max  Work";
+
+/// A full benchmark run, as written to the JSON report.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub runs: usize,
+    pub days: Vec<DayBench>,
+}
+
+/// The median time each part took in a stored benchmark report, keyed by day, input and part.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Runtimes {
+    parts: HashMap<(u8, String, u8), u64>,
+}
+
+impl Runtimes {
+    /// Loads a report written by `bench --json`, treating a missing file as one with no timings.
+    pub fn load(file: &str) -> Result<Self> {
+        /// The parts of a [`DayBench`] that are read back.
+        #[derive(Deserialize)]
+        struct Stored {
+            day: u8,
+            input: String,
+            part_1: Timing,
+            part_2: Timing,
+        }
+
+        #[derive(Deserialize)]
+        struct StoredReport {
+            days: Vec<Stored>,
+        }
+
+        let mut runtimes = Runtimes::default();
+        if !Path::new(file).exists() {
+            return Ok(runtimes);
+        }
+        let json = std::fs::read_to_string(file).with_context(|| format!("reading {}", file))?;
+        let report: StoredReport = serde_json::from_str(&json).with_context(|| format!("parsing {}", file))?;
+        for day in report.days {
+            for (part, timing) in [(1, day.part_1), (2, day.part_2)] {
+                runtimes.set(day.day, &day.input, part, timing.median_ns);
+            }
+        }
+        Ok(runtimes)
+    }
+
+    pub fn get(&self, day: u8, input: &str, part: u8) -> Option<u64> {
+        self.parts.get(&(day, input.to_string(), part)).copied()
+    }
+
+    pub fn set(&mut self, day: u8, input: &str, part: u8, ns: u64) {
+        self.parts.insert((day, input.to_string(), part), ns);
+    }
+}
+
+/// Times parsing and both parts of a day over `runs` repeated runs, with some of its parameters
+/// changed. The input is read into memory first so that file IO isn't counted as parsing. The work
+/// counted is the first run's, as every run does the same.
+pub fn bench_day(day: &Day, file: &str, changes: &Changes, runs: usize) -> Result<DayBench> {
+    if runs == 0 {
+        Err(anyhow!("Need at least one run"))?;
+    }
+    let input = std::fs::read_to_string(file).with_context(|| format!("reading {}", file))?;
+
+    let mut parse = vec![];
+    let mut part_1 = vec![];
+    let mut part_2 = vec![];
+    let mut counters = [Counters::new(), Counters::new()];
+    for run in 0..runs {
+        let start = Instant::now();
+        let mut solution = (day.parse)(&mut input.as_bytes())?;
+        parse.push(start.elapsed());
+        solution.configure(changes)?;
+
+        for (part, samples) in [(1, &mut part_1), (2, &mut part_2)] {
+            let start = Instant::now();
+            let (answer, counted) = metrics::record(|| solution.part(part));
+            samples.push(start.elapsed());
+            answer.with_context(|| format!("Day {:02} part {}", day.number, part))?;
+            if run == 0 {
+                counters[part as usize - 1] = counted;
+            }
+        }
+    }
+
+    Ok(DayBench {
+        day: day.number,
+        input: file.to_string(),
+        parse: Timing::from_samples(&mut parse),
+        part_1: Timing::from_samples(&mut part_1),
+        part_2: Timing::from_samples(&mut part_2),
+        part_1_counters: std::mem::take(&mut counters[0]),
+        part_2_counters: std::mem::take(&mut counters[1]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timing() {
+        let mut samples = [5, 1, 3, 2, 4].map(Duration::from_nanos);
+        assert_eq!(Timing { min_ns: 1, median_ns: 3, max_ns: 5 }, Timing::from_samples(&mut samples));
+        assert_eq!("1.5ms", format_ns(1_500_000));
+    }
+
+    #[test]
+    fn bench_example() -> Result<()> {
+        let bench = bench_day(crate::day(10).unwrap(), "day10ex.txt", &Changes::new(), 3)?;
+        for timing in [bench.parse, bench.part_1, bench.part_2] {
+            assert!(timing.min_ns <= timing.median_ns && timing.median_ns <= timing.max_ns);
+        }
+        let json = serde_json::to_value(Report { runs: 3, days: vec![bench] })?;
+        assert_eq!(10, json["days"][0]["day"]);
+        assert!(json["days"][0]["part_2"]["median_ns"].is_u64());
+        assert!(json["days"][0].get("part_1_counters").is_none());
+
+        let bench = bench_day(crate::day(11).unwrap(), "day11ex.txt", &Changes::new(), 2)?;
+        assert_eq!(Counters::from([("inspections", 101 + 95 + 7 + 105), ("rounds", 20)]), bench.part_1_counters);
+        assert!(bench.to_string().contains("inspections 308, rounds 20"));
+
+        let file = std::env::temp_dir().join(format!("advent2022-bench-{}.json", std::process::id())).display().to_string();
+        std::fs::write(&file, serde_json::to_string(&Report { runs: 2, days: vec![bench.clone()] })?)?;
+        let runtimes = Runtimes::load(&file)?;
+        assert_eq!(Some(bench.part_2.median_ns), runtimes.get(11, "day11ex.txt", 2));
+        assert_eq!(None, runtimes.get(11, "day11.txt", 2));
+        std::fs::remove_file(&file)?;
+        assert_eq!(Runtimes::default(), Runtimes::load(&file)?);
+        Ok(())
+    }
+}
@@ -1,46 +1,239 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::io::BufRead;
+
+use anyhow::{anyhow, Result};
+
+use crate::parse::{ParseError, Scanner};
+use crate::{Answer, Solution};
+
+const DAY: u8 = 1;
+
+fn parse_calories(line: &str) -> Result<u64, ParseError> {
+    let mut scanner = Scanner::new(DAY, line);
+    let calories = scanner.number("calories")?;
+    scanner.end()?;
+    Ok(calories)
+}
+
+/// An elf, numbered from 1 in the order they appear, and the calories they carry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elf {
+    pub number: usize,
+    pub calories: u64,
+}
+
+/// Reads elves one at a time, a blank line ending each, reusing one line buffer so that inputs of
+/// any size are read in constant memory. The end of the input always ends an elf, so an empty input
+/// is a single elf carrying nothing.
+pub struct Elves<R> {
+    input: R,
+    line: String,
+    line_number: usize,
+    elves: usize,
+    done: bool,
+}
+
+impl<R: BufRead> Elves<R> {
+    pub fn new(input: R) -> Self {
+        Elves { input, line: String::new(), line_number: 0, elves: 0, done: false }
+    }
+
+    /// Reads the current elf's next line into the buffer, returning false at the end of the elf.
+    fn next_line(&mut self) -> Result<bool> {
+        self.line.clear();
+        if self.input.read_line(&mut self.line)? == 0 {
+            self.done = true;
+            return Ok(false);
+        }
+        self.line_number += 1;
+        Ok(!self.line.trim().is_empty())
+    }
+
+    fn next_elf(&mut self) -> Result<Elf> {
+        self.elves += 1;
+        let mut calories: u64 = 0;
+        while self.next_line()? {
+            let snack = parse_calories(self.line.trim()).map_err(|e| e.at_line(self.line_number))?;
+            calories = calories.checked_add(snack).ok_or_else(|| {
+                ParseError::new(DAY, "a total below 2^64 calories", format!("{} more on top of {}", snack, calories)).at_line(self.line_number)
+            })?;
+        }
+        Ok(Elf { number: self.elves, calories })
+    }
+}
+
+/// Stops at the first error.
+impl<R: BufRead> Iterator for Elves<R> {
+    type Item = Result<Elf>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let elf = self.next_elf();
+        self.done |= elf.is_err();
+        Some(elf)
+    }
+}
+
+/// The `n` elves carrying the most calories out of those pushed, kept in a heap of at most `n` so
+/// the least of them is the one replaced. Of elves carrying the same, the earlier is kept.
+pub struct TopElves {
+    n: usize,
+    seen: usize,
+    heap: BinaryHeap<Reverse<(u64, Reverse<usize>)>>,
+}
+
+impl TopElves {
+    pub fn new(n: usize) -> Self {
+        TopElves { n, seen: 0, heap: BinaryHeap::with_capacity(n + 1) }
+    }
+
+    pub fn push(&mut self, elf: Elf) {
+        self.seen += 1;
+        self.heap.push(Reverse((elf.calories, Reverse(elf.number))));
+        if self.heap.len() > self.n {
+            self.heap.pop();
+        }
+    }
+
+    /// How many elves have been pushed.
+    pub fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// The top elves, the most calories first.
+    pub fn into_sorted(self) -> Vec<Elf> {
+        self.heap.into_sorted_vec().into_iter().map(|Reverse((calories, Reverse(number)))| Elf { number, calories }).collect()
+    }
+}
+
+/// Reads every elf, keeping the `n` carrying the most. Returns them, the most first, along with how
+/// many elves there were.
+pub fn top_elves(input: impl BufRead, n: usize) -> Result<(Vec<Elf>, usize)> {
+    let mut top = TopElves::new(n);
+    for elf in Elves::new(input) {
+        top.push(elf?);
+    }
+    let seen = top.seen();
+    Ok((top.into_sorted(), seen))
+}
+
+/// The calories some elves carry between them.
+pub fn combined(elves: &[Elf]) -> Result<u64> {
+    elves.iter().try_fold(0u64, |total, elf| total.checked_add(elf.calories)).ok_or_else(|| anyhow!("The elves carry more than 2^64 calories between them"))
+}
+
+pub struct Day01 {
+    /// The three elves carrying the most, the most first.
+    top: Vec<Elf>,
+    elves: usize,
+}
+
+impl Solution for Day01 {
+    fn parse(input: &mut dyn BufRead) -> Result<Self> {
+        let (top, elves) = top_elves(input, 3)?;
+        Ok(Day01 { top, elves })
+    }
+
+    fn part_1(&self) -> Result<Answer> {
+        Ok(self.top.first().ok_or_else(|| anyhow!("No elves"))?.calories.into())
+    }
+
+    fn part_2(&self) -> Result<Answer> {
+        if self.elves < 3 {
+            Err(anyhow!("Need at least 3 elves, only have {}", self.elves))?;
+        }
+        Ok(combined(&self.top)?.into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-
-    fn elves_calories() -> Vec<i32> {
-        let file = File::open("day01.txt").unwrap();
-        let reader = BufReader::new(file);
-
-        let mut calories = 0;
-        let mut elves = vec![];
-        for line in reader.lines() {
-            let line = line.unwrap();
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                elves.push(calories);
-                calories = 0;
-            } else {
-                calories += trimmed.parse::<i32>().unwrap();
-            }
-        }
-        elves.push(calories);
-        elves
+    use std::io::{BufReader, Read};
+
+    use crate::generate;
+    use crate::open;
+    use crate::property::{self, Lines, Verdict};
+
+    use super::*;
+
+    #[test]
+    fn part_1() -> Result<()> {
+        let (top, _) = top_elves(open("day01.txt")?, 1)?;
+        println!("Elf {} has the most calories: {}", top[0].number, top[0].calories);
+        Ok(())
+    }
+
+    #[test]
+    fn part_2() -> Result<()> {
+        println!("Top 3 combined: {}", combined(&top_elves(open("day01.txt")?, 3)?.0)?);
+        Ok(())
     }
 
     #[test]
-    fn part_1() {
-        let mut max = (0, 0);
-        for (i, calories) in elves_calories().iter().enumerate() {
-            let elf = i + 1;
-            // println!("{}: {}", elf, calories);
-            if calories > &max.1 {
-                max = (elf, *calories);
+    fn ranks_elves() -> Result<()> {
+        let input = "1000\n2000\n\n4000\n\n2500\n500\n\n100\n";
+        let (top, elves) = top_elves(input.as_bytes(), 3)?;
+        assert_eq!(4, elves);
+        assert_eq!(vec![(2, 4000), (1, 3000), (3, 3000)], top.iter().map(|elf| (elf.number, elf.calories)).collect::<Vec<_>>());
+        assert_eq!(vec![Elf { number: 1, calories: 0 }], top_elves("".as_bytes(), 3)?.0);
+
+        let e = top_elves("18446744073709551615\n\n1\n18446744073709551615\n".as_bytes(), 3).unwrap_err();
+        assert_eq!("day 01, line 4: expected a total below 2^64 calories, found 18446744073709551615 more on top of 1", e.to_string());
+        let e = Day01::parse(&mut "1\n\n2\n".as_bytes())?.part_2().unwrap_err();
+        assert_eq!("Need at least 3 elves, only have 2", e.to_string());
+        let big = Day01::parse(&mut "18446744073709551615\n\n1\n\n1\n".as_bytes())?;
+        assert_eq!(Answer::Text("18446744073709551615".to_string()), big.part_1()?);
+        assert!(big.part_2().is_err());
+        Ok(())
+    }
+
+    /// Elves 1 to `count`, each carrying as many calories as their number, made as they're read.
+    struct ManyElves {
+        next: u64,
+        count: u64,
+        pending: Vec<u8>,
+    }
+
+    impl Read for ManyElves {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.pending.is_empty() && self.next <= self.count {
+                self.pending = format!("{}\n\n", self.next).into_bytes();
+                self.next += 1;
             }
+            let len = self.pending.len().min(buf.len());
+            buf[..len].copy_from_slice(&self.pending[..len]);
+            self.pending.drain(..len);
+            Ok(len)
         }
-        println!("Elf {} has the most calories: {}", max.0, max.1);
     }
 
     #[test]
-    fn part_2() {
-        let mut elves = elves_calories();
-        elves.sort();
-        elves.reverse();
-        println!("Top 3 combined: {}", elves[0] + elves[1] + elves[2]);
+    fn streams_a_million_elves() -> Result<()> {
+        let count = 1_000_000;
+        let (top, elves) = top_elves(BufReader::new(ManyElves { next: 1, count, pending: vec![] }), 3)?;
+        // The blank line after the last elf starts one more, carrying nothing.
+        assert_eq!(count as usize + 1, elves);
+        assert_eq!(vec![count, count - 1, count - 2], top.iter().map(|elf| elf.calories).collect::<Vec<_>>());
+        assert_eq!(count as usize, top[0].number);
+        Ok(())
+    }
+
+    #[test]
+    fn top_elves_are_the_largest() {
+        property::check(200, |rng, size| Lines(generate::day01(rng, size)), |input| {
+            let Ok(elves) = Elves::new(input.0.as_bytes()).collect::<Result<Vec<_>>>() else {
+                return Verdict::Discard;
+            };
+            let Ok((top, _)) = top_elves(input.0.as_bytes(), 3) else {
+                return Verdict::Fail("top_elves failed where reading every elf didn't".to_string());
+            };
+            let mut sorted = elves.clone();
+            sorted.sort_by_key(|elf| (Reverse(elf.calories), elf.number));
+            sorted.truncate(3);
+            Verdict::check(top == sorted, || format!("top three {:?}, but sorting gives {:?}", top, sorted))
+        });
     }
 }
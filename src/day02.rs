@@ -1,128 +1,193 @@
-#[cfg(test)]
-mod tests {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
+use std::io::BufRead;
 
-    use anyhow::{anyhow, Context, Result};
+use anyhow::{Context, Result};
 
-    use crate::day02::tests::GameResult::{DRAW, LOSS, WIN};
-    use crate::day02::tests::Move::{PAPER, ROCK, SCISSORS};
+use crate::day02::GameResult::{DRAW, LOSS, WIN};
+use crate::day02::Move::{PAPER, ROCK, SCISSORS};
+use crate::parse::{ParseError, Scanner};
+use crate::{Answer, Solution};
 
-    #[derive(Copy, Clone)]
-    enum Move {
-        ROCK = 1,
-        PAPER = 2,
-        SCISSORS = 3,
-    }
+const DAY: u8 = 2;
 
-    impl TryFrom<char> for Move {
-        type Error = anyhow::Error;
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Move {
+    ROCK = 1,
+    PAPER = 2,
+    SCISSORS = 3,
+}
 
-        fn try_from(value: char) -> std::result::Result<Self, Self::Error> {
-            Ok(match value {
-                'A' | 'X' => ROCK,
-                'B' | 'Y' => PAPER,
-                'C' | 'Z' => SCISSORS,
-                other => Err(anyhow!("Invalid move: '{}'", other))?,
-            })
-        }
-    }
+impl TryFrom<char> for Move {
+    type Error = ParseError;
 
-    enum GameResult {
-        LOSS = 0,
-        DRAW = 3,
-        WIN = 6,
+    fn try_from(value: char) -> std::result::Result<Self, Self::Error> {
+        Ok(match value {
+            'A' | 'X' => ROCK,
+            'B' | 'Y' => PAPER,
+            'C' | 'Z' => SCISSORS,
+            other => Err(ParseError::new(DAY, "a move (A, B, C, X, Y or Z)", format!("'{}'", other)))?,
+        })
     }
+}
 
-    impl TryFrom<char> for GameResult {
-        type Error = anyhow::Error;
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GameResult {
+    LOSS = 0,
+    DRAW = 3,
+    WIN = 6,
+}
 
-        fn try_from(value: char) -> std::result::Result<Self, Self::Error> {
-            Ok(match value {
-                'X' => LOSS,
-                'Y' => DRAW,
-                'Z' => WIN,
-                other => Err(anyhow!("Invalid game result: '{}'", other))?,
-            })
-        }
+impl TryFrom<char> for GameResult {
+    type Error = ParseError;
+
+    fn try_from(value: char) -> std::result::Result<Self, Self::Error> {
+        Ok(match value {
+            'X' => LOSS,
+            'Y' => DRAW,
+            'Z' => WIN,
+            other => Err(ParseError::new(DAY, "a game result (X, Y or Z)", format!("'{}'", other)))?,
+        })
     }
+}
 
-    impl GameResult {
-        fn determine(you: Move, them: Move) -> Self {
-            match you {
-                ROCK => match them {
-                    ROCK => DRAW,
-                    PAPER => LOSS,
-                    SCISSORS => WIN,
-                }
-                PAPER => match them {
-                    ROCK => WIN,
-                    PAPER => DRAW,
-                    SCISSORS => LOSS,
-                }
-                SCISSORS => match them {
-                    ROCK => LOSS,
-                    PAPER => WIN,
-                    SCISSORS => DRAW,
-                }
+impl GameResult {
+    pub fn determine(you: Move, them: Move) -> Self {
+        match you {
+            ROCK => match them {
+                ROCK => DRAW,
+                PAPER => LOSS,
+                SCISSORS => WIN,
+            }
+            PAPER => match them {
+                ROCK => WIN,
+                PAPER => DRAW,
+                SCISSORS => LOSS,
+            }
+            SCISSORS => match them {
+                ROCK => LOSS,
+                PAPER => WIN,
+                SCISSORS => DRAW,
             }
         }
+    }
 
-        fn required(&self, them: Move) -> Move {
-            match them {
-                ROCK => match self {
-                    LOSS => SCISSORS,
-                    DRAW => ROCK,
-                    WIN => PAPER,
-                }
-                PAPER => match self {
-                    LOSS => ROCK,
-                    DRAW => PAPER,
-                    WIN => SCISSORS,
-                }
-                SCISSORS => match self {
-                    LOSS => PAPER,
-                    DRAW => SCISSORS,
-                    WIN => ROCK,
-                }
+    pub fn required(&self, them: Move) -> Move {
+        match them {
+            ROCK => match self {
+                LOSS => SCISSORS,
+                DRAW => ROCK,
+                WIN => PAPER,
+            }
+            PAPER => match self {
+                LOSS => ROCK,
+                DRAW => PAPER,
+                WIN => SCISSORS,
+            }
+            SCISSORS => match self {
+                LOSS => PAPER,
+                DRAW => SCISSORS,
+                WIN => ROCK,
             }
         }
     }
+}
+
+/// Loads the strategy guide as (opponent, second column) pairs of raw characters, since the meaning
+/// of the second column differs between the two parts.
+pub fn load_strategy_guide(input: impl BufRead) -> Result<Vec<(char, char)>> {
+    fn parse_round(line: &str) -> std::result::Result<(char, char), ParseError> {
+        let mut scanner = Scanner::new(DAY, line);
+        let opponent = scanner.one_of("opponent move (A, B or C)", &['A', 'B', 'C'])?;
+        scanner.literal(" ")?;
+        let second = scanner.one_of("second column (X, Y or Z)", &['X', 'Y', 'Z'])?;
+        scanner.end()?;
+        Ok((opponent, second))
+    }
+
+    let mut guide = vec![];
+    for (i, line) in input.lines().enumerate() {
+        guide.push(parse_round(line?.trim_end()).map_err(|e| e.at_line(i + 1))?);
+    }
+    Ok(guide)
+}
+
+pub fn score_as_moves(guide: &[(char, char)]) -> Result<i32> {
+    let mut score = 0;
+    for (opponent, response) in guide {
+        let opponent: Move = (*opponent).try_into().with_context(|| "opponent")?;
+        let response: Move = (*response).try_into().with_context(|| "response")?;
+
+        score += response as i32;
+        score += GameResult::determine(response, opponent) as i32;
+    }
+    Ok(score)
+}
+
+pub fn score_as_outcomes(guide: &[(char, char)]) -> Result<i32> {
+    let mut score = 0;
+    for (opponent, outcome) in guide {
+        let opponent: Move = (*opponent).try_into()?;
+        let outcome: GameResult = (*outcome).try_into()?;
+
+        score += outcome.required(opponent) as i32;
+        score += outcome as i32;
+    }
+    Ok(score)
+}
+
+pub struct Day02 {
+    guide: Vec<(char, char)>,
+}
+
+impl Solution for Day02 {
+    fn parse(input: &mut dyn BufRead) -> Result<Self> {
+        Ok(Day02 { guide: load_strategy_guide(input)? })
+    }
+
+    fn part_1(&self) -> Result<Answer> {
+        Ok(score_as_moves(&self.guide)?.into())
+    }
+
+    fn part_2(&self) -> Result<Answer> {
+        Ok(score_as_outcomes(&self.guide)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generate;
+    use crate::open;
+    use crate::property::{self, Lines, Verdict};
+
+    use super::*;
 
     #[test]
     fn part_1() -> Result<()> {
-        let file = File::open("day02.txt").unwrap();
-        let reader = BufReader::new(file);
-
-        let mut score = 0;
-        for line in reader.lines() {
-            let line = line.unwrap();
-            let mut chars = line.chars();
-            let opponent: Move = chars.nth(0).unwrap().try_into().with_context(|| "opponent")?;
-            let response: Move = chars.nth(1).unwrap().try_into().with_context(|| "response")?;
-
-            score += response as i32;
-            score += GameResult::determine(response, opponent) as i32;
-        }
-        println!("Total score is: {}", score);
-
+        println!("Total score is: {}", score_as_moves(&load_strategy_guide(open("day02.txt")?)?)?);
         Ok(())
     }
 
     #[test]
     fn part_2() -> Result<()> {
-        let mut score = 0;
-        for line in BufReader::new(File::open("day02.txt").unwrap()).lines() {
-            let line = line.unwrap();
-            let mut chars = line.chars();
-            let opponent: Move = chars.nth(0).unwrap().try_into()?;
-            let outcome: GameResult = chars.nth(1).unwrap().try_into()?;
-
-            score += outcome.required(opponent) as i32;
-            score += outcome as i32;
-        }
-        println!("Total score is: {}", score);
-
+        println!("Total score is: {}", score_as_outcomes(&load_strategy_guide(open("day02.txt")?)?)?);
         Ok(())
     }
+
+    #[test]
+    fn rounds_score_one_to_nine() {
+        property::check(200, |rng, size| Lines(generate::day02(rng, size)), |input| {
+            let Ok(guide) = load_strategy_guide(input.0.as_bytes()) else {
+                return Verdict::Discard;
+            };
+            let rounds = guide.len() as i32;
+            for score in [score_as_moves(&guide), score_as_outcomes(&guide)] {
+                match score {
+                    Ok(score) if (rounds..=rounds * 9).contains(&score) => {}
+                    other => return Verdict::Fail(format!("{} rounds scored {:?}", rounds, other)),
+                }
+            }
+            Verdict::Pass
+        });
+    }
 }
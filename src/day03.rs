@@ -1,64 +1,127 @@
-#[cfg(test)]
-mod tests {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-
-    use anyhow::{anyhow, Result};
-
-    fn item_to_priority(c: u8) -> Result<u32> {
-        if c >= b'a' && c <= b'z' {
-            Ok((c - b'a' + 1) as u32)
-        } else if c >= b'A' && c <= b'Z' {
-            Ok((c - b'A' + 27) as u32)
-        } else {
-            Err(anyhow!("Invalid item: '{}' ({})", c as char, c))?
+use std::io::BufRead;
+
+use anyhow::{anyhow, Result};
+
+use crate::parse::ParseError;
+use crate::{Answer, Solution};
+
+const DAY: u8 = 3;
+
+pub fn item_to_priority(c: u8) -> Result<u32> {
+    if c.is_ascii_lowercase() {
+        Ok((c - b'a' + 1) as u32)
+    } else if c.is_ascii_uppercase() {
+        Ok((c - b'A' + 27) as u32)
+    } else {
+        Err(anyhow!("Invalid item: '{}' ({})", c as char, c))?
+    }
+}
+
+fn parse_rucksack(line: &str) -> std::result::Result<String, ParseError> {
+    if let Some((i, c)) = line.chars().enumerate().find(|(_, c)| !c.is_ascii_alphabetic()) {
+        Err(ParseError::new(DAY, "an item (a-z or A-Z)", format!("'{}'", c)).at_column(i + 1))?;
+    }
+    if !line.len().is_multiple_of(2) {
+        Err(ParseError::new(DAY, "an even number of items", format!("{} items", line.len())).at_column(1))?;
+    }
+    Ok(line.to_string())
+}
+
+pub fn load_rucksacks(input: impl BufRead) -> Result<Vec<String>> {
+    let mut rucksacks = vec![];
+    for (i, line) in input.lines().enumerate() {
+        rucksacks.push(parse_rucksack(line?.trim()).map_err(|e| e.at_line(i + 1))?);
+    }
+    Ok(rucksacks)
+}
+
+/// Sums the priorities of the item found in both compartments of each rucksack.
+pub fn misplaced_priorities(rucksacks: &[String]) -> Result<u32> {
+    let mut priorities = 0;
+    for rucksack in rucksacks {
+        let (first, second) = rucksack.split_at(rucksack.len() / 2);
+        for item in first.bytes() {
+            if second.bytes().any(|b| b == item) {
+                priorities += item_to_priority(item)?;
+                break;
+            }
         }
     }
+    Ok(priorities)
+}
 
-    #[test]
-    fn part_1() -> Result<()> {
-        let mut priorities = 0;
-        for line in BufReader::new(File::open("day03.txt").unwrap()).lines() {
-            let line = line.unwrap();
-            let trimmed = line.trim();
-            let (first, second) = trimmed.split_at(trimmed.len() / 2);
-            for item in first.bytes() {
-                if second.bytes().any(|b| b == item) {
-                    priorities += item_to_priority(item)?;
-                    break;
-                }
+/// Sums the priorities of the badge item carried by every elf in each group of three.
+pub fn badge_priorities(rucksacks: &[String]) -> Result<u32> {
+    if !rucksacks.len().is_multiple_of(3) {
+        Err(anyhow!("Rucksack count {} is not a multiple of 3", rucksacks.len()))?;
+    }
+    let mut priorities = 0;
+    for group in rucksacks.chunks(3) {
+        let (elf1, elf2, elf3) = (&group[0], &group[1], &group[2]);
+        for item in elf1.bytes() {
+            if elf2.as_bytes().contains(&item) && elf3.as_bytes().contains(&item) {
+                let prio = item_to_priority(item)?;
+                // println!("Group has '{}', prio {}", item as char, prio);
+                priorities += prio;
+                break;
             }
         }
-        println!("Sum of priorities: {}", priorities);
-        Ok(())
     }
+    Ok(priorities)
+}
 
+pub struct Day03 {
+    rucksacks: Vec<String>,
+}
+
+impl Solution for Day03 {
+    fn parse(input: &mut dyn BufRead) -> Result<Self> {
+        Ok(Day03 { rucksacks: load_rucksacks(input)? })
+    }
+
+    fn part_1(&self) -> Result<Answer> {
+        Ok(misplaced_priorities(&self.rucksacks)?.into())
+    }
+
+    fn part_2(&self) -> Result<Answer> {
+        Ok(badge_priorities(&self.rucksacks)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generate;
+    use crate::open;
+    use crate::property::{self, Lines, Verdict};
+
+    use super::*;
+
+    #[test]
+    fn part_1() -> Result<()> {
+        println!("Sum of priorities: {}", misplaced_priorities(&load_rucksacks(open("day03.txt")?)?)?);
+        Ok(())
+    }
 
     #[test]
     fn part_2() -> Result<()> {
-        let mut priorities = 0;
-        let mut _group = 0;
-        let mut lines = BufReader::new(File::open("day03.txt").unwrap()).lines();
-        loop {
-            _group += 1;
-            match lines.next() {
-                None => break,
-                Some(line) => {
-                    let elf1 = line?;
-                    let elf2 = lines.next().unwrap()?;
-                    let elf3 = lines.next().unwrap()?;
-                    for item in elf1.trim().bytes() {
-                        if elf2.as_bytes().contains(&item) && elf3.as_bytes().contains(&item) {
-                            let prio = item_to_priority(item)?;
-                            // println!("Group {} all have '{}', prio {}", group, item as char, prio);
-                            priorities += prio;
-                            break;
-                        }
-                    }
-                }
-            }
-        }
-        println!("Sum of priorities: {}", priorities);
+        println!("Sum of priorities: {}", badge_priorities(&load_rucksacks(open("day03.txt")?)?)?);
         Ok(())
-     }
+    }
+
+    #[test]
+    fn every_rucksack_has_a_priority() {
+        property::check(200, |rng, size| Lines(generate::day03(rng, size)), |input| {
+            let Ok(rucksacks) = load_rucksacks(input.0.as_bytes()) else {
+                return Verdict::Discard;
+            };
+            let (count, groups) = (rucksacks.len() as u32, rucksacks.len() as u32 / 3);
+            match (misplaced_priorities(&rucksacks), badge_priorities(&rucksacks)) {
+                (Ok(misplaced), Ok(badges)) => Verdict::check(
+                    (count..=count * 52).contains(&misplaced) && (groups..=groups * 52).contains(&badges),
+                    || format!("{} rucksacks gave misplaced {} and badges {}", count, misplaced, badges),
+                ),
+                _ => Verdict::Discard,
+            }
+        });
+    }
 }
@@ -1,51 +1,111 @@
+use std::io::BufRead;
+
+use anyhow::Result;
+
+use crate::parse::{ParseError, Scanner};
+use crate::{Answer, Solution};
+
+const DAY: u8 = 4;
+
+/// An inclusive range of section IDs assigned to one elf.
+pub type Sections = (u32, u32);
+
+fn find_bounds(scanner: &mut Scanner) -> std::result::Result<Sections, ParseError> {
+    let column = scanner.column();
+    let lower = scanner.number("first section")?;
+    scanner.literal("-")?;
+    let upper = scanner.number("last section")?;
+    if upper < lower {
+        Err(ParseError::new(DAY, "a range with its last section after its first", format!("{}-{}", lower, upper)).at_column(column))?;
+    }
+    Ok((lower, upper))
+}
+
+fn parse_pair(line: &str) -> std::result::Result<(Sections, Sections), ParseError> {
+    let mut scanner = Scanner::new(DAY, line);
+    let elf1 = find_bounds(&mut scanner)?;
+    scanner.literal(",")?;
+    let elf2 = find_bounds(&mut scanner)?;
+    scanner.end()?;
+    Ok((elf1, elf2))
+}
+
+pub fn load_assignments(input: impl BufRead) -> Result<Vec<(Sections, Sections)>> {
+    let mut assignments = vec![];
+    for (i, line) in input.lines().enumerate() {
+        assignments.push(parse_pair(line?.trim()).map_err(|e| e.at_line(i + 1))?);
+    }
+    Ok(assignments)
+}
+
+pub fn fully_overlaps((elf1_l, elf1_u): Sections, (elf2_l, elf2_u): Sections) -> bool {
+    (elf1_l >= elf2_l && elf1_u <= elf2_u) || (elf2_l >= elf1_l && elf2_u <= elf1_u)
+}
+
+pub fn partially_overlaps((elf1_l, elf1_u): Sections, (elf2_l, elf2_u): Sections) -> bool {
+    (elf1_l <= elf2_l && elf1_u >= elf2_l) || (elf2_l < elf1_l && elf2_u >= elf1_l)
+}
+
+pub fn count_overlapping(assignments: &[(Sections, Sections)], overlaps: fn(Sections, Sections) -> bool) -> usize {
+    assignments.iter().filter(|(elf1, elf2)| overlaps(*elf1, *elf2)).count()
+}
+
+pub struct Day04 {
+    assignments: Vec<(Sections, Sections)>,
+}
+
+impl Solution for Day04 {
+    fn parse(input: &mut dyn BufRead) -> Result<Self> {
+        Ok(Day04 { assignments: load_assignments(input)? })
+    }
+
+    fn part_1(&self) -> Result<Answer> {
+        Ok(count_overlapping(&self.assignments, fully_overlaps).into())
+    }
+
+    fn part_2(&self) -> Result<Answer> {
+        Ok(count_overlapping(&self.assignments, partially_overlaps).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-    use anyhow::Result;
+    use crate::generate;
+    use crate::open;
+    use crate::property::{self, Lines, Verdict};
 
-    fn find_bounds(desc: &str) -> Result<(u32, u32)> {
-        let (lower, upper) = desc.split_once("-").unwrap();
-        Ok((lower.parse::<u32>()?, upper.parse::<u32>()?))
-    }
+    use super::*;
 
     #[test]
     fn part_1() -> Result<()> {
-        let mut overlap = 0;
-        for line in BufReader::new(File::open("day04.txt").unwrap()).lines() {
-            let line = line.unwrap();
-            let (elf1, elf2) = line.trim().split_once(",").unwrap();
-            let (elf1_l, elf1_u) = find_bounds(elf1)?;
-            let (elf2_l, elf2_u) = find_bounds(elf2)?;
-            // print!("{} - {} ; {} - {}", elf1_l, elf1_u, elf2_l, elf2_u);
-            if (elf1_l >= elf2_l && elf1_u <= elf2_u) || (elf2_l >= elf1_l && elf2_u <= elf1_u) {
-                overlap += 1;
-                // println!(" Overlap!");
-            } else {
-                // print!("\n");
-            }
-        }
+        let overlap = count_overlapping(&load_assignments(open("day04.txt")?)?, fully_overlaps);
         println!("{} groups completely overlap", overlap);
         Ok(())
     }
 
     #[test]
     fn part_2() -> Result<()> {
-        let mut overlap = 0;
-        for line in BufReader::new(File::open("day04.txt").unwrap()).lines() {
-            let line = line.unwrap();
-            let (elf1, elf2) = line.trim().split_once(",").unwrap();
-            let (elf1_l, elf1_u) = find_bounds(elf1)?;
-            let (elf2_l, elf2_u) = find_bounds(elf2)?;
-            // print!("{} - {} ; {} - {}", elf1_l, elf1_u, elf2_l, elf2_u);
-            if (elf1_l <= elf2_l && elf1_u >= elf2_l) || (elf2_l < elf1_l && elf2_u >= elf1_l) {
-                overlap += 1;
-                // println!(" Overlap!");
-            } else {
-                // print!("\n");
-            }
-        }
+        let overlap = count_overlapping(&load_assignments(open("day04.txt")?)?, partially_overlaps);
         println!("{} groups partially overlap", overlap);
         Ok(())
     }
+
+    #[test]
+    fn bad_pair() {
+        let e = load_assignments("2-4,6-8\n2-4;6-8\n".as_bytes()).unwrap_err();
+        assert_eq!("day 04, line 2, column 4: expected ',', found ';6-8'", e.to_string());
+    }
+
+    #[test]
+    fn full_overlap_is_partial_overlap() {
+        property::check(200, |rng, size| Lines(generate::day04(rng, size)), |input| {
+            let Ok(assignments) = load_assignments(input.0.as_bytes()) else {
+                return Verdict::Discard;
+            };
+            match assignments.iter().find(|&&(a, b)| fully_overlaps(a, b) && !partially_overlaps(a, b)) {
+                Some(pair) => Verdict::Fail(format!("{:?} overlaps fully but not partially", pair)),
+                None => Verdict::Pass,
+            }
+        });
+    }
 }
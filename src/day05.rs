@@ -1,68 +1,298 @@
-#[cfg(test)]
-mod tests {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-    use anyhow::{Context, Result};
-
-    struct Instruction {
-        count: usize,
-        from: usize,
-        to: usize,
-    }
-
-    fn load_input(file: &str) -> Result<(Vec<Vec<char>>, Vec<Instruction>)> {
-        let mut stacks_done = false;
-        let mut stacks_lines = vec![];
-        let mut instructions = vec![];
-        let lines = BufReader::new(File::open(file).unwrap()).lines();
-        for line in lines {
-            let line = line?;
-            if stacks_done {
-                let split: Vec<&str> = line.trim().split(" ").collect();
-                instructions.push(Instruction {
-                    count: split[1].parse::<usize>().with_context(|| "count")?,
-                    from: split[3].parse::<usize>().with_context(|| "from")?,
-                    to: split[5].parse::<usize>().with_context(|| "to")?
-                });
-            } else if line.trim().is_empty() {
-                stacks_done = true;
-            } else {
-                stacks_lines.push(line);
+use std::io::BufRead;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::parse::{ParseError, Scanner};
+use crate::repl::{arg, optional_arg, Command, Explore};
+use crate::visualize::{Frame, Frames, Rgb, Visualize};
+use crate::{Answer, Solution};
+
+const DAY: u8 = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Instruction {
+    pub count: usize,
+    pub from: usize,
+    pub to: usize,
+}
+
+impl FromStr for Instruction {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut scanner = Scanner::new(DAY, s);
+        scanner.literal("move ")?;
+        let count = scanner.number("crate count")?;
+        scanner.literal(" from ")?;
+        let from = scanner.number("stack to move from")?;
+        scanner.literal(" to ")?;
+        let to = scanner.number("stack to move to")?;
+        scanner.end()?;
+        Ok(Instruction { count, from, to })
+    }
+}
+
+pub fn load_input(input: impl BufRead) -> Result<(Vec<Vec<char>>, Vec<Instruction>)> {
+    let mut lines = input.lines().enumerate();
+    let mut stacks_lines = vec![];
+    for (i, line) in lines.by_ref() {
+        let line = line?;
+        if line.trim().is_empty() {
+            break;
+        }
+        stacks_lines.push((i + 1, line));
+    }
+
+    let mut stacks = vec![];
+    let (numbers_line, numbers) = stacks_lines.pop().ok_or_else(|| ParseError::new(DAY, "a drawing of the stacks", "an empty line").at_line(1))?;
+    for (i, number) in numbers.split_whitespace().enumerate() {
+        if number != (i + 1).to_string() {
+            let column = numbers.find(number).unwrap_or_default() + 1;
+            Err(ParseError::new(DAY, format!("stack number {}", i + 1), format!("'{}'", number)).at_line(numbers_line).at_column(column))?;
+        }
+        stacks.push(vec![]);
+    }
+    while let Some((_, line)) = stacks_lines.pop() {
+        let bytes = line.as_bytes();
+        for (i, stack) in stacks.iter_mut().enumerate() {
+            let offset = i * 4 + 1;
+            if offset < bytes.len() && bytes[offset] != b' ' {
+                stack.push(bytes[offset] as char);
             }
         }
+    }
 
-        let mut stacks = vec![];
-        let stack_count = stacks_lines.pop().unwrap().trim().split("   ").last().unwrap().parse::<usize>().with_context(|| "stack count")?;
-        for _ in 0..stack_count {
-            stacks.push(vec![]);
+    let mut instructions = vec![];
+    for (i, line) in lines {
+        let instruction: Instruction = line?.trim().parse().map_err(|e: ParseError| e.at_line(i + 1))?;
+        for (stack, what) in [(instruction.from, "stack to move from"), (instruction.to, "stack to move to")] {
+            if stack == 0 || stack > stacks.len() {
+                Err(ParseError::new(DAY, format!("{} (1-{})", what, stacks.len()), stack.to_string()).at_line(i + 1))?;
+            }
         }
-        while let Some(line) = stacks_lines.pop() {
-            let bytes = line.as_bytes();
-            for i in 0..stack_count {
-                let offset = i * 4 + 1;
-                if offset < bytes.len() && bytes[offset] != b' ' {
-                    stacks[i].push(bytes[offset] as char);
-                }
+        instructions.push(instruction);
+    }
+
+    Ok((stacks, instructions))
+}
+
+fn stack_index(stacks: &[Vec<char>], number: usize) -> Result<usize> {
+    if number == 0 || number > stacks.len() {
+        Err(anyhow!("No such stack: {}", number))?;
+    }
+    Ok(number - 1)
+}
+
+fn tops(stacks: &[Vec<char>]) -> Result<String> {
+    let mut tops = String::new();
+    for (i, stack) in stacks.iter().enumerate() {
+        tops.push(*stack.last().ok_or_else(|| anyhow!("Stack {} is empty", i + 1))?);
+    }
+    Ok(tops)
+}
+
+/// Carries out one instruction, moving crates one at a time like the CrateMover 9000 or all at once,
+/// keeping their order, like the 9001.
+fn move_crates(stacks: &mut [Vec<char>], instruction: &Instruction, one_at_a_time: bool) -> Result<()> {
+    let (from, to) = (stack_index(stacks, instruction.from)?, stack_index(stacks, instruction.to)?);
+    if one_at_a_time {
+        for _ in 0..instruction.count {
+            let item = stacks[from].pop().ok_or_else(|| anyhow!("Stack {} is empty", instruction.from))?;
+            stacks[to].push(item);
+        }
+    } else {
+        let stack = &mut stacks[from];
+        if stack.len() < instruction.count {
+            Err(anyhow!("Stack {} has fewer than {} crates", instruction.from, instruction.count))?;
+        }
+        let mut items: Vec<char> = stack.drain((stack.len() - instruction.count)..).collect();
+        stacks[to].append(&mut items);
+    }
+    Ok(())
+}
+
+/// Moves crates one at a time and returns the crates left on top of each stack.
+pub fn crate_mover_9000(mut stacks: Vec<Vec<char>>, instructions: &[Instruction]) -> Result<String> {
+    for instruction in instructions {
+        move_crates(&mut stacks, instruction, true)?;
+    }
+    tops(&stacks)
+}
+
+/// Moves crates several at a time, keeping their order, and returns the crates left on top of each stack.
+pub fn crate_mover_9001(mut stacks: Vec<Vec<char>>, instructions: &[Instruction]) -> Result<String> {
+    for instruction in instructions {
+        move_crates(&mut stacks, instruction, false)?;
+    }
+    tops(&stacks)
+}
+
+/// Draws the stacks the way the puzzle does, with their numbers underneath and room for `height`
+/// crates above.
+pub fn draw_stacks(stacks: &[Vec<char>], height: usize) -> Grid<char> {
+    let mut grid = Grid::new((stacks.len() * 4).saturating_sub(1), height + 1, ' ');
+    for (i, stack) in stacks.iter().enumerate() {
+        for (level, &item) in stack.iter().enumerate() {
+            let y = height - 1 - level;
+            for (dx, c) in ['[', item, ']'].into_iter().enumerate() {
+                grid[Point::new(i * 4 + dx, y)] = c;
             }
         }
+        grid[Point::new(i * 4 + 1, height)] = char::from_digit((i as u32 + 1) % 10, 10).unwrap();
+    }
+    grid
+}
 
-        Ok((stacks, instructions))
+/// Crates in colours picked by their letter, in brown boxes above grey numbers.
+fn palette(cell: char) -> Rgb {
+    match cell {
+        '[' | ']' => [139, 90, 43],
+        'A'..='Z' => {
+            let i = cell as u8 - b'A';
+            [100 + i * 6, 220 - i * 5, 80 + i * 7]
+        }
+        '0'..='9' => [128, 128, 128],
+        _ => [0, 0, 0],
     }
+}
 
-    #[test]
-    fn part_1() -> Result<()> {
-        let (mut stacks, instructions) = load_input("day05.txt")?;
-        for instruction in instructions {
-            for _ in 0..instruction.count {
-                let item = stacks[instruction.from - 1].pop().unwrap();
-                stacks[instruction.to - 1].push(item);
+const COMMANDS: [Command; 4] = [
+    Command::new("stacks", "", "draws the stacks"),
+    Command::new("step", "[n]", "carries out the next n moves (default 1)"),
+    Command::new("crane", "<9000|9001>", "switches crane, the 9001 moving several crates at once"),
+    Command::new("reset", "", "puts the crates back where they started"),
+];
+
+/// Moves crates a step at a time.
+struct Explorer<'a> {
+    day: &'a Day05,
+    stacks: Vec<Vec<char>>,
+    moved: usize,
+    one_at_a_time: bool,
+}
+
+impl Explore for Explorer<'_> {
+    fn commands(&self) -> &'static [Command] {
+        &COMMANDS
+    }
+
+    fn run(&mut self, command: &str, args: &[&str]) -> Result<String> {
+        match command {
+            "stacks" => {
+                let height = self.stacks.iter().map(Vec::len).max().unwrap_or(0);
+                Ok(format!("{}after {} of {} moves\n", draw_stacks(&self.stacks, height), self.moved, self.day.instructions.len()))
+            }
+            "step" => {
+                let steps = optional_arg(args, 0, "number of moves", 1)?;
+                let mut out = String::new();
+                for instruction in self.day.instructions.iter().skip(self.moved).take(steps) {
+                    move_crates(&mut self.stacks, instruction, self.one_at_a_time)?;
+                    self.moved += 1;
+                    out += &format!("{}: move {} from {} to {}\n", self.moved, instruction.count, instruction.from, instruction.to);
+                }
+                if out.is_empty() {
+                    Err(anyhow!("No moves left"))?;
+                }
+                let tops: String = self.stacks.iter().map(|stack| stack.last().copied().unwrap_or(' ')).collect();
+                Ok(out + &format!("tops: {}\n", tops))
+            }
+            "crane" => {
+                self.one_at_a_time = match arg(args, 0, "crane")? {
+                    9000 => true,
+                    9001 => false,
+                    other => Err(anyhow!("No such crane: CrateMover {}", other))?,
+                };
+                Ok(String::new())
+            }
+            _ => {
+                self.stacks = self.day.stacks.clone();
+                self.moved = 0;
+                Ok(String::new())
             }
         }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Day05 {
+    stacks: Vec<Vec<char>>,
+    instructions: Vec<Instruction>,
+}
+
+impl Solution for Day05 {
+    fn parse(input: &mut dyn BufRead) -> Result<Self> {
+        let (stacks, instructions) = load_input(input)?;
+        Ok(Day05 { stacks, instructions })
+    }
+
+    fn part_1(&self) -> Result<Answer> {
+        Ok(crate_mover_9000(self.stacks.clone(), &self.instructions)?.into())
+    }
+
+    fn part_2(&self) -> Result<Answer> {
+        Ok(crate_mover_9001(self.stacks.clone(), &self.instructions)?.into())
+    }
+
+    fn model(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(self).ok()
+    }
+
+    fn visualizer(&self) -> Option<&dyn Visualize> {
+        Some(self)
+    }
+
+    fn explorer(&self) -> Option<Box<dyn Explore + '_>> {
+        Some(Box::new(Explorer { day: self, stacks: self.stacks.clone(), moved: 0, one_at_a_time: true }))
+    }
+}
 
-        let mut tops = String::new();
-        for stack in &stacks {
-            tops.push(*stack.last().unwrap());
+/// The stacks after each instruction, with part 1's crane moving crates one at a time and part 2's
+/// several at once.
+impl Visualize for Day05 {
+    fn visualize(&self, part: u8, frames: &mut Frames) -> Result<()> {
+        let mut stacks = self.stacks.clone();
+        let height = stacks.iter().map(Vec::len).sum();
+        frames.frame(|| Frame::new("start", draw_stacks(&stacks, height)).with_palette(palette))?;
+        for instruction in &self.instructions {
+            move_crates(&mut stacks, instruction, part == 1)?;
+            let caption = format!("move {} from {} to {}", instruction.count, instruction.from, instruction.to);
+            frames.frame(|| Frame::new(caption, draw_stacks(&stacks, height)).with_palette(palette))?;
         }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generate;
+    use crate::open;
+    use crate::property::{self, Lines, Verdict};
+    use crate::repl;
+    use crate::visualize;
+
+    use super::*;
+
+    #[test]
+    fn explore() -> Result<()> {
+        assert_eq!("    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \nafter 0 of 4 moves\n", repl::session(5, "stacks")?);
+        let out = repl::session(5, "step\nstep 5\nstep\nreset\ncrane 9001\nstep 4\ncrane 9002")?;
+        let expected = "1: move 1 from 2 to 1\ntops: DCP\n\
+            2: move 3 from 1 to 3\n3: move 2 from 2 to 1\n4: move 1 from 1 to 2\ntops: CMZ\n\
+            Error: No moves left\n\
+            1: move 1 from 2 to 1\n2: move 3 from 1 to 3\n3: move 2 from 2 to 1\n4: move 1 from 1 to 2\ntops: MCD\n\
+            Error: No such crane: CrateMover 9002\n";
+        assert_eq!(expected, out);
+        Ok(())
+    }
+
+    #[test]
+    fn part_1() -> Result<()> {
+        let (stacks, instructions) = load_input(open("day05.txt")?)?;
+        let tops = crate_mover_9000(stacks, &instructions)?;
         // assert_eq!(tops, "CMZ".to_string());
         assert_eq!(tops, "SHMSDGZVC".to_string());
         Ok(())
@@ -70,19 +300,37 @@ mod tests {
 
     #[test]
     fn part_2() -> Result<()> {
-        let (mut stacks, instructions) = load_input("day05.txt")?;
-        for instruction in instructions {
-            let stack = stacks.get_mut(instruction.from - 1).unwrap();
-            let mut items: Vec<char> = stack.drain((stack.len() - instruction.count)..).collect();
-            stacks[instruction.to - 1].append(&mut items);
-        }
-        let mut tops = String::new();
-        for stack in &stacks {
-            tops.push(*stack.last().unwrap());
-        }
+        let (stacks, instructions) = load_input(open("day05.txt")?)?;
+        let tops = crate_mover_9001(stacks, &instructions)?;
         println!("Final top of stacks: {}", tops);
         // assert_eq!(tops, "MCD".to_string());
         assert_eq!(tops, "VRZGHDFBQ".to_string());
         Ok(())
     }
+
+    #[test]
+    fn visualize() -> Result<()> {
+        let day = Day05::parse(&mut open("day05ex.txt")?)?;
+        let frames = visualize::text_frames(&day, 2)?;
+        assert_eq!(5, frames.len());
+        assert!(frames[0].ends_with("    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n"));
+        assert!(frames[4].starts_with("move 1 from 1 to 2\n"));
+        assert!(frames[4].ends_with("        [D]\n        [N]\n        [Z]\n[M] [C] [P]\n 1   2   3 \n"));
+        Ok(())
+    }
+
+    #[test]
+    fn moving_one_at_a_time_is_the_9000() {
+        // Splitting every move into single-crate moves makes the 9001 behave just like the 9000.
+        property::check(200, |rng, size| Lines(generate::day05(rng, size)), |input| {
+            let Ok((stacks, instructions)) = load_input(input.0.as_bytes()) else {
+                return Verdict::Discard;
+            };
+            let singles: Vec<Instruction> = instructions.iter().flat_map(|i| vec![Instruction { count: 1, ..i.clone() }; i.count]).collect();
+            match (crate_mover_9000(stacks.clone(), &instructions), crate_mover_9001(stacks, &singles)) {
+                (Ok(a), Ok(b)) => Verdict::check(a == b, || format!("9000 gave {}, 9001 one at a time gave {}", a, b)),
+                _ => Verdict::Discard,
+            }
+        });
+    }
 }
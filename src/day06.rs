@@ -1,51 +1,112 @@
-#[cfg(test)]
-mod tests {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-    use anyhow::{anyhow, Result};
+use std::io::BufRead;
 
-    const PACKET_SEQ_LEN: usize = 4;
-    const MESSAGE_SEQ_LEN: usize = 14;
+use anyhow::{anyhow, Result};
 
-    fn find_start_of_sequence(buffer: &[u8], length: usize) -> Result<usize> {
-        if buffer.len() < length {
-            Err(anyhow!("Message is not long enough to find start of sequence: {} (req {})", buffer.len(), length))?;
-        }
-        for i in 0..=(buffer.len() - length) {
-            let mut good = true;
-            for j in 0..(length-1) {
-                for k in (j + 1)..length {
-                    if buffer[i + j] == buffer[i + k] {
-                        good = false;
-                        break;
-                    }
-                    if !good {
-                        break;
-                    }
+use crate::differential::Implementation;
+use crate::{Answer, Solution};
+
+pub const PACKET_SEQ_LEN: usize = 4;
+pub const MESSAGE_SEQ_LEN: usize = 14;
+
+pub fn find_start_of_sequence(buffer: &[u8], length: usize) -> Result<usize> {
+    if buffer.len() < length {
+        Err(anyhow!("Message is not long enough to find start of sequence: {} (req {})", buffer.len(), length))?;
+    }
+    for i in 0..=(buffer.len() - length) {
+        let mut good = true;
+        for j in 0..(length - 1) {
+            for k in (j + 1)..length {
+                if buffer[i + j] == buffer[i + k] {
+                    good = false;
+                    break;
+                }
+                if !good {
+                    break;
                 }
             }
-            if good {
-                return Ok(i + length);
+        }
+        if good {
+            return Ok(i + length);
+        }
+    }
+    Err(anyhow!("Start of sequence ({}) not found in: {}", length, String::from_utf8_lossy(buffer)))
+}
+
+/// The same as [`find_start_of_sequence`], but sliding a window of letter counts along the buffer
+/// instead of comparing every pair in each window, so it is linear in the buffer's length.
+pub fn find_start_of_sequence_counting(buffer: &[u8], length: usize) -> Result<usize> {
+    if buffer.len() < length {
+        Err(anyhow!("Message is not long enough to find start of sequence: {} (req {})", buffer.len(), length))?;
+    }
+    let mut counts = [0; 256];
+    let mut repeated = 0;
+    for (i, &byte) in buffer.iter().enumerate() {
+        counts[byte as usize] += 1;
+        if counts[byte as usize] == 2 {
+            repeated += 1;
+        }
+        if i >= length {
+            let old = buffer[i - length] as usize;
+            counts[old] -= 1;
+            if counts[old] == 1 {
+                repeated -= 1;
             }
         }
-        Err(anyhow!("Start of sequence ({}) not found in: {}", length, String::from_utf8_lossy(buffer)))
+        if i + 1 >= length && repeated == 0 {
+            return Ok(i + 1);
+        }
     }
+    Err(anyhow!("Start of sequence ({}) not found in: {}", length, String::from_utf8_lossy(buffer)))
+}
+
+pub fn load_datastream(mut reader: impl BufRead) -> Result<String> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+pub struct Day06 {
+    datastream: String,
+}
+
+impl Solution for Day06 {
+    fn parse(input: &mut dyn BufRead) -> Result<Self> {
+        Ok(Day06 { datastream: load_datastream(input)? })
+    }
+
+    fn part_1(&self) -> Result<Answer> {
+        Ok(find_start_of_sequence(self.datastream.as_bytes(), PACKET_SEQ_LEN)?.into())
+    }
+
+    fn part_2(&self) -> Result<Answer> {
+        Ok(find_start_of_sequence(self.datastream.as_bytes(), MESSAGE_SEQ_LEN)?.into())
+    }
+
+    fn implementations(&self, part: u8) -> Vec<Implementation<'_>> {
+        let length = if part == 1 { PACKET_SEQ_LEN } else { MESSAGE_SEQ_LEN };
+        vec![Implementation::new("counting", move || Ok(find_start_of_sequence_counting(self.datastream.as_bytes(), length)?.into()))]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::differential;
+    use crate::generate;
+    use crate::open;
+    use crate::params::Changes;
+    use crate::property::{self, Lines, Verdict};
+
+    use super::*;
 
     #[test]
     fn part_1() -> Result<()> {
-        for (i, line) in BufReader::new(File::open("day06.txt")?).lines().enumerate() {
-            let line = line?;
-            println!("{}: {}", i + 1, find_start_of_sequence(line.as_bytes(), PACKET_SEQ_LEN)?);
-        }
+        println!("Day 06 part 1: {}", find_start_of_sequence(load_datastream(open("day06.txt")?)?.as_bytes(), PACKET_SEQ_LEN)?);
         Ok(())
     }
 
     #[test]
     fn part_2() -> Result<()> {
-        for (i, line) in BufReader::new(File::open("day06.txt")?).lines().enumerate() {
-            let line = line?;
-            println!("{}: {}", i + 1, find_start_of_sequence(line.as_bytes(), MESSAGE_SEQ_LEN)?);
-        }
+        println!("Day 06 part 2: {}", find_start_of_sequence(load_datastream(open("day06.txt")?)?.as_bytes(), MESSAGE_SEQ_LEN)?);
         Ok(())
     }
 
@@ -69,4 +130,31 @@ mod tests {
         assert_eq!(26, find_start_of_sequence("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw".as_bytes(), MESSAGE_SEQ_LEN)?);
         Ok(())
     }
+
+    #[test]
+    fn implementations_agree() -> Result<()> {
+        let day = crate::day(6).unwrap();
+        let input = std::fs::read_to_string("day06.txt")?;
+        for part in [1, 2] {
+            assert_eq!(2, differential::check(day, part, "day06.txt", &input, &Changes::new()).map_err(|d| anyhow!("{}", d))?);
+            for input in ["", "abc", "aaaaaaaaaaaaaaaaaaaa", "abcdefghijklmn"] {
+                differential::check(day, part, input, input, &Changes::new()).map_err(|d| anyhow!("{}", d))?;
+            }
+            differential::check_generated(day, part, 200, |seed, size| generate::generate(6, seed, size), &Changes::new()).map_err(|d| anyhow!("{}", d))?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn packet_starts_before_message() {
+        property::check(200, |rng, size| Lines(generate::day06(rng, size)), |input| {
+            let Ok(datastream) = load_datastream(input.0.as_bytes()) else {
+                return Verdict::Discard;
+            };
+            match (find_start_of_sequence(datastream.as_bytes(), PACKET_SEQ_LEN), find_start_of_sequence(datastream.as_bytes(), MESSAGE_SEQ_LEN)) {
+                (Ok(packet), Ok(message)) => Verdict::check(packet <= message, || format!("packet at {}, message at {}", packet, message)),
+                (packet, message) => Verdict::Fail(format!("packet {:?}, message {:?}", packet.ok(), message.ok())),
+            }
+        });
+    }
 }
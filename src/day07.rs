@@ -1,169 +1,331 @@
-#[cfg(test)]
-mod tests {
-    use std::collections::{BTreeMap, VecDeque};
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-    use anyhow::{anyhow, Result};
+use std::collections::{BTreeMap, VecDeque};
+use std::io::BufRead;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
 
-    struct FsDir {
-        size: u64,
-        items: BTreeMap<String, FsItem>,
+use crate::params::{self, Changes};
+use crate::parse::{ParseError, Scanner};
+use crate::repl::{Command, Explore};
+use crate::{Answer, Solution};
+
+const DAY: u8 = 7;
+
+pub enum TerminalLine {
+    Cd(String),
+    Ls,
+    Dir(String),
+    File(u64, String),
+}
+
+impl FromStr for TerminalLine {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut scanner = Scanner::new(DAY, s);
+        if scanner.accept("$ cd ") {
+            Ok(TerminalLine::Cd(scanner.word("directory name")?.to_string()))
+        } else if scanner.accept("$ ls") {
+            scanner.end()?;
+            Ok(TerminalLine::Ls)
+        } else if scanner.accept("dir ") {
+            Ok(TerminalLine::Dir(scanner.word("directory name")?.to_string()))
+        } else {
+            let size = scanner.number("'$ cd', '$ ls', 'dir' or a file size")?;
+            scanner.literal(" ")?;
+            Ok(TerminalLine::File(size, scanner.word("file name")?.to_string()))
+        }
     }
+}
 
-    impl FsDir {
-        fn new() -> Self {
-            FsDir {
-                size: 0,
-                items: BTreeMap::new(),
-            }
+#[derive(Serialize)]
+pub struct FsDir {
+    pub size: u64,
+    pub items: BTreeMap<String, FsItem>,
+}
+
+impl FsDir {
+    fn new() -> Self {
+        FsDir {
+            size: 0,
+            items: BTreeMap::new(),
         }
+    }
 
-        fn load(&mut self, lines: &mut VecDeque<String>) -> Result<()> {
-            while let Some(line) = lines.pop_front() {
-                if line.starts_with("$ cd") {
-                    let name = line[4..].trim();
-                    if name == "/" {
-                        Err(anyhow!("Unhandled 'cd /' command!"))?;
+    fn load(&mut self, lines: &mut VecDeque<(usize, TerminalLine)>, is_root: bool) -> std::result::Result<(), ParseError> {
+        while let Some((number, line)) = lines.pop_front() {
+            match line {
+                TerminalLine::Cd(name) => {
+                    if name == "/" || (is_root && name == "..") {
+                        Err(ParseError::new(DAY, "a subdirectory (only the first line can 'cd' to the root)", format!("'{}'", name)).at_line(number).at_column(6))?;
                     } else if name == ".." {
                         break;
                     }
-                    match self.items.get_mut(name).unwrap() {
-                        FsItem::File(_) => Err(anyhow!("Trying to 'cd' to file '{}'", name))?,
-                        FsItem::Dir(dir) => dir.load(lines)?,
+                    match self.items.get_mut(&name) {
+                        Some(FsItem::Dir(dir)) => dir.load(lines, false)?,
+                        _ => Err(ParseError::new(DAY, "a directory listed by 'ls'", format!("'{}'", name)).at_line(number).at_column(6))?,
                     }
-                } else if line.starts_with("$ ls") {
-                    self.items.clear();
-                } else if line.starts_with("dir ") {
-                    self.items.insert(line.trim()[4..].to_string(), FsItem::Dir(FsDir::new()));
-                } else {
-                    let (size, name) = line.trim().split_once(" ").unwrap();
-                    self.items.insert(name.to_string(), FsItem::File(size.parse::<u64>()?));
+                }
+                TerminalLine::Ls => self.items.clear(),
+                TerminalLine::Dir(name) => {
+                    self.items.insert(name, FsItem::Dir(FsDir::new()));
+                }
+                TerminalLine::File(size, name) => {
+                    self.items.insert(name, FsItem::File(size));
                 }
             }
-            Ok(())
         }
+        Ok(())
+    }
 
-        fn calc_size(&mut self) {
-            for item in self.items.values_mut() {
-                self.size += match item {
-                    FsItem::File(size) => *size,
-                    FsItem::Dir(dir) => {
-                        dir.calc_size();
-                        dir.size
-                    }
-                };
-            }
+    fn calc_size(&mut self) {
+        for item in self.items.values_mut() {
+            self.size += match item {
+                FsItem::File(size) => *size,
+                FsItem::Dir(dir) => {
+                    dir.calc_size();
+                    dir.size
+                }
+            };
         }
     }
+}
 
-    enum FsItem {
-        File(u64),
-        Dir(FsDir),
-    }
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FsItem {
+    File(u64),
+    Dir(FsDir),
+}
 
-    impl FsItem {
-        fn size(&self) -> u64 {
-            match self {
-                FsItem::File(size) => *size,
-                FsItem::Dir(dir) => dir.size,
-            }
+impl FsItem {
+    pub fn size(&self) -> u64 {
+        match self {
+            FsItem::File(size) => *size,
+            FsItem::Dir(dir) => dir.size,
         }
     }
 
-    fn load_fs(file: &str) -> Result<FsItem> {
-        let mut root = FsDir::new();
-        let mut lines: VecDeque<String> = BufReader::new(File::open(file)?).lines().map(|l| l.unwrap()).collect();
-        let first = lines.pop_front().unwrap();
-        if first.trim() != "$ cd /" {
-            Err(anyhow!("Unexpected first line of input: {}", first.trim()))?;
-        }
-        root.load(&mut lines)?;
-        if !lines.is_empty() {
-            Err(anyhow!("{} input lines remaining when FS processing complete!", lines.len()))?;
+    /// The item at a path such as `/a/e`, relative to this one.
+    pub fn find(&self, path: &str) -> Option<&FsItem> {
+        path.split('/').filter(|name| !name.is_empty()).try_fold(self, |item, name| match item {
+            FsItem::Dir(dir) => dir.items.get(name),
+            FsItem::File(_) => None,
+        })
+    }
+}
+
+pub fn load_fs(input: impl BufRead) -> Result<FsItem> {
+    let mut root = FsDir::new();
+    let mut input = input.lines();
+    let first = input.next().transpose()?.unwrap_or_default();
+    if first.trim() != "$ cd /" {
+        Err(ParseError::new(DAY, "'$ cd /'", format!("'{}'", first.trim())).at_line(1).at_column(1))?;
+    }
+    let mut lines = VecDeque::new();
+    for (i, line) in input.enumerate() {
+        lines.push_back((i + 2, line?.trim().parse().map_err(|e: ParseError| e.at_line(i + 2))?));
+    }
+    root.load(&mut lines, true)?;
+    root.calc_size();
+    Ok(FsItem::Dir(root))
+}
+
+/// Collects the size of every directory in the tree, keyed by its full path.
+pub fn dir_sizes(name: &str, item: &FsItem, dirs: &mut BTreeMap<String, u64>) {
+    if let FsItem::Dir(dir) = item {
+        dirs.insert(name.to_string(), dir.size);
+        for (item_name, item) in &dir.items {
+            dir_sizes(&format!("{}/{}", name, item_name).replace("//", "/"), item, dirs);
         }
-        root.calc_size();
-        Ok(FsItem::Dir(root))
     }
+}
 
-    fn dir_sizes(name: &str, item: &FsItem, dirs: &mut BTreeMap<String, u64>) {
-        if let FsItem::Dir(dir) = item {
-            dirs.insert(name.to_string(), dir.size);
-            for (item_name, item) in &dir.items {
-                dir_sizes(&format!("{}/{}", name, item_name).replace("//", "/"), item, dirs);
-            }
+pub fn sum_size_dirs_at_most(fs: &FsItem, limit: u64) -> u64 {
+    let mut dirs = BTreeMap::new();
+    dir_sizes("/", fs, &mut dirs);
+    dirs.values().filter(|s| **s <= limit).sum()
+}
+
+pub fn find_smallest_dir_to_free(fs: &FsItem, needed: u64, total: u64) -> Result<(String, u64)> {
+    let to_free = needed.saturating_sub(total.saturating_sub(fs.size()));
+    // println!("total: {}, used: {}, available: {}, to_free: {}", total, fs.size(), total - fs.size(), to_free);
+    let mut dirs = BTreeMap::new();
+    dir_sizes("/", fs, &mut dirs);
+    let mut dirs: Vec<(String, u64)> = dirs.into_iter().collect();
+    dirs.sort_unstable_by_key(|(_, size)| *size);
+    // println!("{:?}", dirs);
+    dirs.into_iter().find(|(_, size)| *size > to_free).ok_or_else(|| anyhow!("No directory frees {} bytes", to_free))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Params {
+    /// Part 1 adds up the directories of at most this size.
+    pub small_dir_limit: u64,
+    pub disk_size: u64,
+    /// How much free space the update needs in part 2.
+    pub space_needed: u64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params { small_dir_limit: 100_000, disk_size: 70000000, space_needed: 30000000 }
+    }
+}
+
+const COMMANDS: [Command; 2] = [
+    Command::new("ls", "[path]", "lists a directory (default /) with the size of everything in it"),
+    Command::new("du", "[path]", "the total size of a file or directory (default /)"),
+];
+
+/// Looks around the file system.
+struct Explorer<'a> {
+    fs: &'a FsItem,
+}
+
+impl Explore for Explorer<'_> {
+    fn commands(&self) -> &'static [Command] {
+        &COMMANDS
+    }
+
+    fn run(&mut self, command: &str, args: &[&str]) -> Result<String> {
+        let path = args.first().copied().unwrap_or("/");
+        let item = self.fs.find(path).ok_or_else(|| anyhow!("No such file or directory: {}", path))?;
+        match (command, item) {
+            ("ls", FsItem::Dir(dir)) => Ok(dir
+                .items
+                .iter()
+                .map(|(name, item)| match item {
+                    FsItem::File(size) => format!("{} {}\n", size, name),
+                    FsItem::Dir(dir) => format!("dir {} ({})\n", name, dir.size),
+                })
+                .collect()),
+            _ => Ok(format!("{} {}\n", item.size(), path)),
         }
     }
+}
+
+pub struct Day07 {
+    fs: FsItem,
+    params: Params,
+}
+
+impl Solution for Day07 {
+    fn parse(input: &mut dyn BufRead) -> Result<Self> {
+        Ok(Day07 { fs: load_fs(input)?, params: Params::default() })
+    }
+
+    fn part_1(&self) -> Result<Answer> {
+        Ok(sum_size_dirs_at_most(&self.fs, self.params.small_dir_limit).into())
+    }
 
-    fn sum_size_dirs_at_most(file: &str, limit: u64) -> Result<u64> {
-        let fs = load_fs(file)?;
-        let mut dirs = BTreeMap::new();
-        dir_sizes("/", &fs, &mut dirs);
-        Ok(dirs.values().filter(|s| **s <= limit).sum())
+    fn part_2(&self) -> Result<Answer> {
+        Ok(find_smallest_dir_to_free(&self.fs, self.params.space_needed, self.params.disk_size)?.1.into())
     }
 
-    fn find_smallest_dir_to_free(file: &str, needed: u64, total: u64) -> Result<(String, u64)> {
-        let fs = load_fs(file)?;
-        let to_free = needed - (total - fs.size());
-        // println!("total: {}, used: {}, available: {}, to_free: {}", total, fs.size(), total - fs.size(), to_free);
-        let mut dirs = BTreeMap::new();
-        dir_sizes("/", &fs, &mut dirs);
-        let mut dirs: Vec<(String, u64)> = dirs.into_iter().collect();
-        dirs.sort_unstable_by_key(|(_, size)| *size);
-        // println!("{:?}", dirs);
-        Ok(dirs.iter().filter(|(_, size)| *size > to_free).map(|(name, size)| (name.to_string(), *size)).next().unwrap())
+    fn model(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(&self.fs).ok()
     }
 
+    fn configure(&mut self, changes: &Changes) -> Result<()> {
+        self.params = params::apply(&self.params, changes)?;
+        Ok(())
+    }
+
+    fn params(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(&self.params).ok()
+    }
+
+    fn explorer(&self) -> Option<Box<dyn Explore + '_>> {
+        Some(Box::new(Explorer { fs: &self.fs }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generate;
+    use crate::open;
+    use crate::property::{self, Lines, Verdict};
+    use crate::repl;
+    use crate::snapshot;
+
+    use super::*;
+
     #[test]
     fn part_1() -> Result<()> {
-        println!("Day 07 part 1: {}", sum_size_dirs_at_most("day07.txt", 100_000)?);
+        println!("Day 07 part 1: {}", sum_size_dirs_at_most(&load_fs(open("day07.txt")?)?, 100_000));
         Ok(())
     }
 
     #[test]
     fn part_2() -> Result<()> {
-        let (name, size) = find_smallest_dir_to_free("day07.txt", 30000000, 70000000)?;
+        let (name, size) = find_smallest_dir_to_free(&load_fs(open("day07.txt")?)?, 30000000, 70000000)?;
         println!("Day 07 part 2: {} ({})", size, name);
         Ok(())
     }
 
     #[test]
     fn part_1_test() -> Result<()> {
-        assert_eq!(95437, sum_size_dirs_at_most("day07ex.txt", 100_000)?);
+        assert_eq!(95437, sum_size_dirs_at_most(&load_fs(open("day07ex.txt")?)?, 100_000));
         Ok(())
     }
 
     #[test]
     fn used_space() -> Result<()> {
-        let fs = load_fs("day07ex.txt")?;
+        let fs = load_fs(open("day07ex.txt")?)?;
         assert_eq!(48381165, fs.size());
         Ok(())
     }
 
     #[test]
     fn part_2_test() -> Result<()> {
-        assert_eq!(("/d".to_string(), 24933642), find_smallest_dir_to_free("day07ex.txt", 30000000, 70000000)?);
+        assert_eq!(("/d".to_string(), 24933642), find_smallest_dir_to_free(&load_fs(open("day07ex.txt")?)?, 30000000, 70000000)?);
         Ok(())
     }
 
-    #[test]
-    fn print_tree() -> Result<()> {
-        fn print_item(name: &str, item: &FsItem, indent: usize) {
-            for _ in 0..indent {
-                print!(" ");
-            }
-            match item {
-                FsItem::File(size) => println!("- {} (file, size={})", name, size),
-                FsItem::Dir(dir) => {
-                    println!("- {} (dir, size={})", name, dir.size);
-                    for (name, item) in &dir.items {
-                        print_item(name, item, indent + 2);
-                    }
+    /// Draws a directory tree like the puzzle text does, a line per item indented under its parent.
+    fn render_tree(name: &str, item: &FsItem, indent: usize, out: &mut String) {
+        match item {
+            FsItem::File(size) => *out += &format!("{:indent$}- {} (file, size={})\n", "", name, size),
+            FsItem::Dir(dir) => {
+                *out += &format!("{:indent$}- {} (dir, size={})\n", "", name, dir.size);
+                for (name, item) in &dir.items {
+                    render_tree(name, item, indent + 2, out);
                 }
             }
         }
+    }
 
-        let fs = load_fs("day07.txt")?;
-        print_item("/", &fs, 0);
+    #[test]
+    fn explore() -> Result<()> {
+        assert_eq!("dir a (94853)\n14848514 b.txt\n8504156 c.dat\ndir d (24933642)\n", repl::session(7, "ls")?);
+        assert_eq!("584 /a/e\n29116 /a/f\n", repl::session(7, "du /a/e\ndu /a/f")?);
+        assert_eq!("584 i\n584 /a/e/\nError: No such file or directory: /x\n", repl::session(7, "ls a/e\ndu /a/e/\nls /x")?);
+        Ok(())
+    }
+
+    #[test]
+    fn print_tree() -> Result<()> {
+        for (input, name) in [("day07ex.txt", "day07ex_tree"), ("day07.txt", "day07_tree")] {
+            let mut tree = String::new();
+            render_tree("/", &load_fs(open(input)?)?, 0, &mut tree);
+            snapshot::assert_snapshot(name, &tree);
+        }
         Ok(())
     }
+
+    #[test]
+    fn root_holds_every_file() {
+        property::check(200, |rng, size| Lines(generate::day07(rng, size)), |input| {
+            let Ok(fs) = load_fs(input.0.as_bytes()) else {
+                return Verdict::Discard;
+            };
+            let files: u64 = input.0.lines().filter_map(|line| match line.parse() {
+                Ok(TerminalLine::File(size, _)) => Some(size),
+                _ => None,
+            }).sum();
+            Verdict::check(fs.size() == files, || format!("root is {} but the files add up to {}", fs.size(), files))
+        });
+    }
 }
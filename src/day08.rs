@@ -1,161 +1,111 @@
-#[cfg(test)]
-mod tests {
-    use std::collections::HashSet;
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-    use anyhow::Result;
-
-    fn load_grid(file: &str) -> Result<Vec<Vec<i8>>> {
-        let mut grid = vec![];
-        for line in BufReader::new(File::open(file)?).lines() {
-            grid.push(line?.trim().chars().map(|c| c.to_digit(10).unwrap() as i8).collect());
-        }
-        Ok(grid)
-    }
+use std::io::BufRead;
 
-    fn visible_trees(grid: Vec<Vec<i8>>) -> usize {
-        let mut visible: HashSet<(usize, usize)> = HashSet::new();
-        let mut heights: Vec<i8> = vec![];
-
-        let max_x = grid.len();
-        let max_y = grid[0].len();
-
-        for _ in 0..max_x {
-            heights.push(-1);
-        }
-        for x in 0..max_x {
-            for y in 0..max_y {
-                if grid[x][y] > heights[x] {
-                    visible.insert((x, y));
-                    heights[x] = grid[x][y];
-                }
-            }
-        }
-
-        for x in 0..max_x {
-            heights[x] = -1;
-        }
-        for x in 0..max_x {
-            for y in (0..max_y).rev() {
-                if grid[x][y] > heights[x] {
-                    visible.insert((x, y));
-                    heights[x] = grid[x][y];
-                }
-            }
-        }
+use anyhow::Result;
 
-        heights.clear();
-        for _ in 0..max_y {
-            heights.push(-1);
-        }
-        for y in 0..max_y {
-            for x in 0..max_x {
-                if grid[x][y] > heights[y] {
-                    visible.insert((x, y));
-                    heights[y] = grid[x][y];
-                }
-            }
-        }
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::parse::ParseError;
+use crate::{Answer, Solution};
 
-        for y in 0..max_y {
-            heights[y] = -1;
-        }
-        for y in 0..max_y {
-            for x in (0..max_x).rev() {
-                if grid[x][y] > heights[y] {
-                    visible.insert((x, y));
-                    heights[y] = grid[x][y];
-                }
-            }
-        }
+const DAY: u8 = 8;
 
-        visible.len()
-    }
+pub fn load_grid(input: impl BufRead) -> Result<Grid<i8>> {
+    Grid::parse(DAY, input, "trees", |_, c| {
+        let height = c.to_digit(10).ok_or_else(|| ParseError::new(DAY, "a tree height (0-9)", format!("'{}'", c)))?;
+        Ok(height as i8)
+    })
+}
 
-    fn view_score(grid: &Vec<Vec<i8>>, tree_x: usize, tree_y: usize) -> u64 {
-        let max_x = grid.len();
-        let max_y = grid[0].len();
-        let tree_h = grid[tree_x][tree_y];
-        let mut score = 1;
+/// Counts the trees that are taller than every tree between them and at least one edge.
+pub fn visible_trees(grid: &Grid<i8>) -> usize {
+    grid.positions()
+        .filter(|&pos| grid.rays(pos).into_iter().any(|mut ray| ray.all(|other| grid[other] < grid[pos])))
+        .count()
+}
 
+pub fn view_score(grid: &Grid<i8>, tree: Point<usize>) -> u64 {
+    let tree_h = grid[tree];
+    let mut score = 1;
+    for ray in grid.rays(tree) {
         let mut count = 0;
-        for x in (0..tree_x).rev() {
+        for other in ray {
             count += 1;
-            if grid[x][tree_y] >= tree_h {
+            if grid[other] >= tree_h {
                 break;
             }
         }
         score *= count;
+    }
+    score
+}
 
-        count = 0;
-        for x in (tree_x + 1)..max_x {
-            count += 1;
-            if grid[x][tree_y] >= tree_h {
-                break;
-            }
-        }
-        score *= count;
+pub fn best_view_score(grid: &Grid<i8>) -> u64 {
+    grid.positions().map(|pos| view_score(grid, pos)).max().unwrap_or(0)
+}
 
-        count = 0;
-        for y in (0..tree_y).rev() {
-            count += 1;
-            if grid[tree_x][y] >= tree_h {
-                break;
-            }
-        }
-        score *= count;
+pub struct Day08 {
+    grid: Grid<i8>,
+}
 
-        count = 0;
-        for y in (tree_y + 1)..max_y {
-            count += 1;
-            if grid[tree_x][y] >= tree_h {
-                break;
-            }
-        }
-        score *= count;
+impl Solution for Day08 {
+    fn parse(input: &mut dyn BufRead) -> Result<Self> {
+        Ok(Day08 { grid: load_grid(input)? })
+    }
 
-        score
+    fn part_1(&self) -> Result<Answer> {
+        Ok(visible_trees(&self.grid).into())
     }
 
-    fn best_view_score(grid: &Vec<Vec<i8>>) -> u64 {
-        let max_x = grid.len();
-        let max_y = grid[0].len();
-
-        let mut best = 0;
-        for x in 1..(max_x - 1) {
-            for y in 1..(max_y - 1) {
-                let score = view_score(grid, x, y);
-                if score > best {
-                    best = score;
-                }
-            }
-        }
-        best
+    fn part_2(&self) -> Result<Answer> {
+        Ok(best_view_score(&self.grid).into())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generate;
+    use crate::open;
+    use crate::property::{self, Lines, Verdict};
+
+    use super::*;
 
     #[test]
     fn part_1() -> Result<()> {
-        println!("Day 08 part 1: {}", visible_trees(load_grid("day08.txt")?));
+        println!("Day 08 part 1: {}", visible_trees(&load_grid(open("day08.txt")?)?));
         Ok(())
     }
 
     #[test]
     fn part_2() -> Result<()> {
-        println!("Day 08 part 2: {}", best_view_score(&load_grid("day08.txt")?));
+        println!("Day 08 part 2: {}", best_view_score(&load_grid(open("day08.txt")?)?));
         Ok(())
     }
 
     #[test]
     fn part_1_test() -> Result<()> {
-        assert_eq!(21, visible_trees(load_grid("day08ex.txt")?));
+        assert_eq!(21, visible_trees(&load_grid(open("day08ex.txt")?)?));
         Ok(())
     }
 
     #[test]
     fn part_2_test() -> Result<()> {
-        let grid = load_grid("day08ex.txt")?;
-        assert_eq!(4, view_score(&grid, 1, 2));
-        assert_eq!(8, view_score(&grid, 3, 2));
+        let grid = load_grid(open("day08ex.txt")?)?;
+        assert_eq!(4, view_score(&grid, Point::new(2, 1)));
+        assert_eq!(8, view_score(&grid, Point::new(2, 3)));
+        assert_eq!(8, best_view_score(&grid));
         Ok(())
     }
+
+    #[test]
+    fn edges_are_visible() {
+        property::check(100, |rng, size| Lines(generate::day08(rng, size)), |input| {
+            let Ok(grid) = load_grid(input.0.as_bytes()) else {
+                return Verdict::Discard;
+            };
+            let (width, height) = (grid.width(), grid.height());
+            let edge = if width < 3 || height < 3 { width * height } else { 2 * (width + height) - 4 };
+            let visible = visible_trees(&grid);
+            Verdict::check(edge <= visible && visible <= width * height, || format!("{} visible in a {}x{} grid", visible, width, height))
+        });
+    }
 }
@@ -1,112 +1,233 @@
-#[cfg(test)]
-mod tests {
-    use std::collections::HashSet;
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-    use anyhow::Result;
-
-    enum Direction {
-        UP(i64),
-        DOWN(i64),
-        LEFT(i64),
-        RIGHT(i64),
-    }
-
-    impl From<&str> for Direction {
-        fn from(input: &str) -> Self {
-            let (dir, count) = input.split_once(" ").unwrap();
-            let count = count.parse::<i64>().unwrap();
-            match dir {
-                "U" => Direction::UP(count),
-                "D" => Direction::DOWN(count),
-                "L" => Direction::LEFT(count),
-                "R" => Direction::RIGHT(count),
-                other => panic!("Unexpected direction: {}", other),
-            }
+use std::collections::HashSet;
+use std::io::BufRead;
+use std::str::FromStr;
+
+use anyhow::Result;
+use serde_json::json;
+
+use crate::geometry::{Bounds, Direction, Point};
+use crate::grid::Grid;
+use crate::parse::{ParseError, Scanner};
+use crate::trace::{self, Level};
+use crate::visualize::{Frame, Frames, Rgb, Visualize};
+use crate::{Answer, Solution};
+
+const DAY: u8 = 9;
+
+/// One line of the path: the head moving some number of steps in a direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Motion {
+    pub direction: Direction,
+    pub steps: u32,
+}
+
+impl FromStr for Motion {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut scanner = Scanner::new(DAY, s);
+        let direction = match scanner.one_of("direction (U, D, L or R)", &['U', 'D', 'L', 'R'])? {
+            'U' => Direction::Up,
+            'D' => Direction::Down,
+            'L' => Direction::Left,
+            _ => Direction::Right,
+        };
+        scanner.literal(" ")?;
+        let steps = scanner.number("step count")?;
+        scanner.end()?;
+        Ok(Motion { direction, steps })
+    }
+}
+
+/// Moves the head one step, and then each knot after the one in front of it.
+fn step_rope(knots: &mut [Point<i64>], direction: Direction) {
+    knots[0] += direction.unit();
+    for i in 1..knots.len() {
+        if !knots[i - 1].is_touching(knots[i]) {
+            let step = (knots[i - 1] - knots[i]).signum();
+            knots[i] += step;
         }
     }
+}
 
-    fn count_tail_positions(path: &Vec<Direction>, knots: usize) -> usize {
-        let mut knots = vec![(0, 0); knots];
-        let mut tail_positions = HashSet::from([(0i64, 0i64)]);
+pub fn count_tail_positions(path: &[Motion], knots: usize) -> usize {
+    let mut knots = vec![Point::<i64>::ORIGIN; knots];
+    let mut tail_positions = HashSet::from([Point::ORIGIN]);
 
-        for direction in path {
-            let mut head = match direction {
-                Direction::UP(count) => (0, *count),
-                Direction::DOWN(count) => (0, -*count),
-                Direction::LEFT(count) => (-*count, 0),
-                Direction::RIGHT(count) => (*count, 0),
-            };
-            while head != (0, 0) {
-                if head.0 > 0 {
-                    head.0 -= 1;
-                    knots[0].0 += 1;
-                } else if head.0 < 0 {
-                    head.0 += 1;
-                    knots[0].0 -= 1;
-                } else if head.1 > 0 {
-                    head.1 -= 1;
-                    knots[0].1 += 1;
-                } else if head.1 < 0 {
-                    head.1 += 1;
-                    knots[0].1 -= 1;
-                }
-                for i in 1..knots.len() {
-                    if !is_touching(knots[i - 1], knots[i]) {
-                        let (x, y) = move_follower(knots[i - 1], knots[i]);
-                        knots[i].0 += x;
-                        knots[i].1 += y;
-                    }
-                }
-                tail_positions.insert(*knots.last().unwrap());
-            }
+    for motion in path {
+        for _ in 0..motion.steps {
+            step_rope(&mut knots, motion.direction);
+            tail_positions.insert(*knots.last().unwrap());
+            trace::event(Level::Step, "move", || json!({ "direction": format!("{:?}", motion.direction), "head": knots[0], "tail": knots.last() }));
         }
-        tail_positions.len()
+        trace::event(Level::Summary, "motion", || json!({ "direction": format!("{:?}", motion.direction), "steps": motion.steps, "visited": tail_positions.len() }));
+    }
+    tail_positions.len()
+}
+
+/// Draws the rope the way the puzzle does, within `bounds`: the head as 'H', the other knots by
+/// number (or 'T' for a two-knot rope's tail), the start as 's' and where the tail has been as '#'.
+pub fn draw_rope(knots: &[Point<i64>], visited: &HashSet<Point<i64>>, bounds: Bounds<i64>) -> Grid<char> {
+    let mut grid = Grid::new(bounds.width() as usize, bounds.height() as usize, '.');
+    let cell = |p: Point<i64>| Point::new((p.x - bounds.min.x) as usize, (p.y - bounds.min.y) as usize);
+    for &p in visited {
+        grid[cell(p)] = '#';
+    }
+    grid[cell(Point::ORIGIN)] = 's';
+    for (i, &knot) in knots.iter().enumerate().rev() {
+        grid[cell(knot)] = match i {
+            0 => 'H',
+            1 if knots.len() == 2 => 'T',
+            i => char::from_digit(i as u32 % 10, 10).unwrap(),
+        };
+    }
+    grid
+}
+
+fn palette(cell: char) -> Rgb {
+    match cell {
+        'H' => [255, 64, 64],
+        'T' | '0'..='9' => [255, 200, 64],
+        's' => [64, 160, 255],
+        '#' => [80, 80, 80],
+        _ => [0, 0, 0],
+    }
+}
+
+pub fn load_path(input: impl BufRead) -> Result<Vec<Motion>> {
+    let mut path = vec![];
+    for (i, line) in input.lines().enumerate() {
+        path.push(line?.trim().parse().map_err(|e: ParseError| e.at_line(i + 1))?);
+    }
+    Ok(path)
+}
+
+pub struct Day09 {
+    path: Vec<Motion>,
+}
+
+impl Solution for Day09 {
+    fn parse(input: &mut dyn BufRead) -> Result<Self> {
+        Ok(Day09 { path: load_path(input)? })
+    }
+
+    fn part_1(&self) -> Result<Answer> {
+        Ok(count_tail_positions(&self.path, 2).into())
     }
 
-    fn is_touching(head: (i64, i64), tail: (i64, i64)) -> bool {
-        (head.0 - tail.0).abs() <= 1 && (head.1 - tail.1).abs() <= 1
+    fn part_2(&self) -> Result<Answer> {
+        Ok(count_tail_positions(&self.path, 10).into())
     }
 
-    fn move_follower(head: (i64, i64), tail: (i64, i64)) -> (i64, i64) {
-        ((head.0 - tail.0).clamp(-1, 1), (head.1 - tail.1).clamp(-1, 1))
+    fn visualizer(&self) -> Option<&dyn Visualize> {
+        Some(self)
     }
+}
+
+/// The rope after each step of the head, with two knots in part 1 and ten in part 2.
+impl Visualize for Day09 {
+    fn visualize(&self, part: u8, frames: &mut Frames) -> Result<()> {
+        let count = if part == 1 { 2 } else { 10 };
+        // Every frame is the size of the whole area the rope moves through, found by a first run.
+        let mut knots = vec![Point::<i64>::ORIGIN; count];
+        let mut bounds = Bounds::new(Point::ORIGIN);
+        for motion in &self.path {
+            for _ in 0..motion.steps {
+                step_rope(&mut knots, motion.direction);
+                bounds.include(knots[0]);
+            }
+        }
 
-    fn load_path(file: &str) -> Result<Vec<Direction>> {
-        let mut path = vec![];
-        for line in BufReader::new(File::open(file)?).lines() {
-            path.push(line?.trim().into());
+        let mut knots = vec![Point::<i64>::ORIGIN; count];
+        let mut visited = HashSet::from([Point::ORIGIN]);
+        frames.frame(|| Frame::new("start", draw_rope(&knots, &visited, bounds)).with_palette(palette))?;
+        for motion in &self.path {
+            for step in 1..=motion.steps {
+                step_rope(&mut knots, motion.direction);
+                visited.insert(*knots.last().unwrap());
+                let caption = format!("{:?} {} (step {})", motion.direction, motion.steps, step);
+                frames.frame(|| Frame::new(caption, draw_rope(&knots, &visited, bounds)).with_palette(palette))?;
+            }
         }
-        Ok(path)
+        Ok(())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::generate;
+    use crate::open;
+    use crate::property::{self, Lines, Verdict};
+    use crate::visualize;
+
+    use super::*;
 
     #[test]
     fn part_1() -> Result<()> {
-        println!("Day 09 part 1: {}", count_tail_positions(&load_path("day09.txt")?, 2));
+        println!("Day 09 part 1: {}", count_tail_positions(&load_path(open("day09.txt")?)?, 2));
         Ok(())
     }
 
     #[test]
     fn part_2() -> Result<()> {
-        println!("Day 09 part 2: {}", count_tail_positions(&load_path("day09.txt")?, 10));
+        println!("Day 09 part 2: {}", count_tail_positions(&load_path(open("day09.txt")?)?, 10));
         Ok(())
     }
 
     #[test]
     fn part_1_test() -> Result<()> {
-        assert_eq!(13, count_tail_positions(&load_path("day09ex1.txt")?, 2));
+        assert_eq!(13, count_tail_positions(&load_path(open("day09ex1.txt")?)?, 2));
+        Ok(())
+    }
+
+    #[test]
+    fn visualize() -> Result<()> {
+        let day = Day09::parse(&mut open("day09ex1.txt")?)?;
+        let frames = visualize::text_frames(&day, 1)?;
+        assert_eq!(25, frames.len());
+        assert_eq!("start\n......\n......\n......\n......\nH.....\n", frames[0]);
+        assert_eq!("Right 2 (step 2)\n..##..\n...##.\n.TH##.\n....#.\ns###..\n", frames[24]);
         Ok(())
     }
 
+    #[test]
+    fn bad_direction() {
+        let e = load_path("R 4\nX 4\n".as_bytes()).unwrap_err();
+        assert_eq!("day 09, line 2, column 1: expected direction (U, D, L or R), found 'X'", e.to_string());
+        let e = load_path("R four\n".as_bytes()).unwrap_err();
+        assert_eq!("day 09, line 1, column 3: expected step count, found 'four'", e.to_string());
+    }
+
     #[test]
     fn part_2_test_1() -> Result<()> {
-        assert_eq!(1, count_tail_positions(&load_path("day09ex1.txt")?, 10));
+        assert_eq!(1, count_tail_positions(&load_path(open("day09ex1.txt")?)?, 10));
         Ok(())
     }
 
     #[test]
     fn part_2_test_2() -> Result<()> {
-        assert_eq!(36, count_tail_positions(&load_path("day09ex2.txt")?, 10));
+        assert_eq!(36, count_tail_positions(&load_path(open("day09ex2.txt")?)?, 10));
         Ok(())
     }
+
+    #[test]
+    fn one_knot_tail_follows_the_head() {
+        property::check(200, |rng, size| Lines(generate::day09(rng, size)), |input| {
+            let Ok(path) = load_path(input.0.as_bytes()) else {
+                return Verdict::Discard;
+            };
+            let mut head = Point::<i64>::ORIGIN;
+            let mut visited = HashSet::from([head]);
+            for motion in &path {
+                for _ in 0..motion.steps {
+                    head += motion.direction.unit();
+                    visited.insert(head);
+                }
+            }
+            let tail = count_tail_positions(&path, 1);
+            Verdict::check(tail == visited.len(), || format!("tail visited {} cells, head visited {}", tail, visited.len()))
+        });
+    }
 }
@@ -1,101 +1,257 @@
-#[cfg(test)]
-mod tests {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-    use anyhow::Result;
+use std::io::BufRead;
+use std::str::FromStr;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::params::{self, Changes};
+use crate::parse::{ParseError, Scanner};
+use crate::trace::{self, Level};
+use crate::visualize::{Frame, Frames, Rgb, Visualize};
+use crate::{Answer, Solution};
+
+const DAY: u8 = 10;
+
+pub const INTERESTING_CYCLES: [usize; 6] = [20, 60, 100, 140, 180, 220];
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug)]
+pub enum Instruction {
+    NOOP,
+    ADDX(i64),
+}
+
+impl FromStr for Instruction {
+    type Err = ParseError;
 
-    enum Instruction {
-        NOOP,
-        ADDX(i64),
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut scanner = Scanner::new(DAY, s);
+        let instruction = if scanner.accept("noop") {
+            Instruction::NOOP
+        } else if scanner.accept("addx") {
+            scanner.literal(" ")?;
+            Instruction::ADDX(scanner.number("value to add")?)
+        } else {
+            Err(scanner.error("'noop' or 'addx'"))?
+        };
+        scanner.end()?;
+        Ok(instruction)
     }
+}
 
-    impl From<&str> for Instruction {
-        fn from(input: &str) -> Self {
-            match &input[..4] {
-                "noop" => Instruction::NOOP,
-                "addx" => Instruction::ADDX(input[5..].parse::<i64>().unwrap()),
-                other => panic!("Invalid instruction: {}", other),
-            }
-        }
+pub fn load_instructions(input: impl BufRead) -> Result<Vec<Instruction>> {
+    let mut instructions = vec![];
+    for (i, line) in input.lines().enumerate() {
+        instructions.push(line?.trim().parse().map_err(|e: ParseError| e.at_line(i + 1))?);
     }
+    Ok(instructions)
+}
 
-    fn load_instructions(file: &str) -> Result<Vec<Instruction>> {
-        let mut instructions = vec![];
-        for line in BufReader::new(File::open(file)?).lines() {
-            instructions.push(line?.trim().into());
+pub fn process_instructions(instructions: &[Instruction]) -> Vec<i64> {
+    let mut x = 1i64;
+    let mut result = vec![x];
+    for instruction in instructions {
+        let (cycles, change) = match instruction {
+            Instruction::NOOP => (1, 0),
+            Instruction::ADDX(v) => (2, *v),
+        };
+        result.push(x);
+        for _ in 1..cycles {
+            result.push(x);
         }
-        Ok(instructions)
+        x += change;
     }
+    result
+}
 
-    fn process_instructions(instructions: &Vec<Instruction>) -> Vec<i64> {
-        let mut x = 1i64;
-        let mut result = vec![x];
-        for instruction in instructions {
-            let (cycles, change) = match instruction {
-                Instruction::NOOP => (1, 0),
-                Instruction::ADDX(v) => (2, *v),
-            };
-            result.push(x);
-            for _ in 1..cycles {
-                result.push(x);
+/// The X register holds its last value once the program has finished.
+fn x_during(xs: &[i64], cycle: usize) -> i64 {
+    *xs.get(cycle).unwrap_or(&xs[xs.len() - 1])
+}
+
+pub fn find_signal_strengths(instructions: &[Instruction], interesting: &[usize]) -> Vec<i64> {
+    let xs = process_instructions(instructions);
+    interesting
+        .iter()
+        .map(|&cycle| {
+            let x = x_during(&xs, cycle);
+            trace::event(Level::Step, "signal", || json!({ "cycle": cycle, "x": x, "strength": x * cycle as i64 }));
+            x * cycle as i64
+        })
+        .collect()
+}
+
+pub fn print_screen(instructions: &[Instruction], width: usize, height: usize) -> String {
+    let xs = process_instructions(instructions);
+    let steps = trace::enabled(Level::Step);
+    let mut screen = String::new();
+    for i in 0..height {
+        for j in 0..width {
+            let pos = i * width + j + 1;
+            let sprite = x_during(&xs, pos);
+            let pixel = j as i64;
+            let lit = ((sprite - 1)..=(sprite + 1)).contains(&pixel);
+            if steps {
+                trace::event(Level::Step, "cycle", || json!({ "cycle": pos, "sprite": format!("{}-{}", sprite - 1, sprite + 1), "pixel": pixel, "lit": lit }));
             }
-            x += change;
+            screen.push(if lit { '#' } else { '.' });
         }
-        result
-    }
-
-    fn find_signal_strengths(instructions: &Vec<Instruction>, interesting: Vec<usize>) -> Vec<i64> {
-        let xs = process_instructions(instructions);
-        interesting.iter().map(|c| xs[*c] * (*c as i64)).collect()
-    }
-
-    fn print_screen(instructions: &Vec<Instruction>) -> String {
-        let xs = process_instructions(instructions);
-        let mut screen = String::new();
-        for i in 0..6 {
-            for j in 0..40 {
-                let pos = i * 40 + j + 1;
-                let sprite = xs[pos];
-                let pixel = j as i64;
-                // println!("{} - s: {}-{}, p: {}", pos, sprite - 1, sprite + 1, pixel);
-                if pixel >= (sprite - 1) && pixel <= (sprite + 1) {
-                    screen.push('#');
-                } else {
-                    screen.push('.');
+        screen.push('\n');
+    }
+    screen
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Params {
+    /// The cycles whose signal strengths part 1 adds up.
+    pub interesting_cycles: Vec<usize>,
+    pub screen_width: usize,
+    pub screen_height: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params { interesting_cycles: INTERESTING_CYCLES.to_vec(), screen_width: 40, screen_height: 6 }
+    }
+}
+
+/// Lit pixels in green on the screen, with the sprite in white and the beam in red on the row
+/// beneath it.
+fn palette(cell: char) -> Rgb {
+    match cell {
+        '#' => [64, 255, 64],
+        '.' => [0, 48, 0],
+        '=' => [255, 255, 255],
+        '^' => [255, 64, 64],
+        _ => [0, 0, 0],
+    }
+}
+
+pub struct Day10 {
+    instructions: Vec<Instruction>,
+    params: Params,
+}
+
+impl Solution for Day10 {
+    fn parse(input: &mut dyn BufRead) -> Result<Self> {
+        Ok(Day10 { instructions: load_instructions(input)?, params: Params::default() })
+    }
+
+    fn part_1(&self) -> Result<Answer> {
+        Ok(find_signal_strengths(&self.instructions, &self.params.interesting_cycles).iter().sum::<i64>().into())
+    }
+
+    fn part_2(&self) -> Result<Answer> {
+        Ok(print_screen(&self.instructions, self.params.screen_width, self.params.screen_height).into())
+    }
+
+    fn visualizer(&self) -> Option<&dyn Visualize> {
+        Some(self)
+    }
+
+    fn configure(&mut self, changes: &Changes) -> Result<()> {
+        self.params = params::apply(&self.params, changes)?;
+        Ok(())
+    }
+
+    fn params(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(&self.params).ok()
+    }
+}
+
+/// The CRT drawing the screen a pixel per cycle, the same for both parts. Below the screen, the
+/// sprite is drawn as '=' and the beam's column as '^'.
+impl Visualize for Day10 {
+    fn visualize(&self, _part: u8, frames: &mut Frames) -> Result<()> {
+        let (width, height) = (self.params.screen_width, self.params.screen_height);
+        let xs = process_instructions(&self.instructions);
+        let mut screen = Grid::new(width, height + 1, ' ');
+        for cycle in 1..=width * height {
+            let beam = Point::new((cycle - 1) % width, (cycle - 1) / width);
+            let sprite = x_during(&xs, cycle);
+            screen[beam] = if (sprite - 1..=sprite + 1).contains(&(beam.x as i64)) { '#' } else { '.' };
+            frames.frame(|| {
+                let mut screen = screen.clone();
+                for x in (sprite - 1..=sprite + 1).filter(|x| (0..width as i64).contains(x)) {
+                    screen[Point::new(x as usize, height)] = '=';
                 }
-            }
-            screen.push('\n');
+                screen[Point::new(beam.x, height)] = '^';
+                Frame::new(format!("cycle {}: X = {}", cycle, sprite), screen).with_palette(palette)
+            })?;
         }
-        screen
+        Ok(())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generate;
+    use crate::open;
+    use crate::property::{self, Lines, Verdict};
+    use crate::snapshot;
+    use crate::visualize;
+
+    use super::*;
 
     #[test]
     fn part_1() -> Result<()> {
-        let answer = find_signal_strengths(&load_instructions("day10.txt")?, vec![20, 60, 100, 140, 180, 220]).iter().sum::<i64>();
+        let answer = find_signal_strengths(&load_instructions(open("day10.txt")?)?, &INTERESTING_CYCLES).iter().sum::<i64>();
         println!("Day 10 part 1: {}", answer);
         Ok(())
     }
 
     #[test]
     fn part_2() -> Result<()> {
-        println!("Day 10 part 2: \n{}", print_screen(&load_instructions("day10.txt")?));
+        snapshot::assert_snapshot("day10_screen", &print_screen(&load_instructions(open("day10.txt")?)?, 40, 6));
         Ok(())
     }
 
     #[test]
     fn part_1_test() -> Result<()> {
-        let strengths = find_signal_strengths(&load_instructions("day10ex.txt")?, vec![20, 60, 100, 140, 180, 220]);
+        let strengths = find_signal_strengths(&load_instructions(open("day10ex.txt")?)?, &INTERESTING_CYCLES);
         assert_eq!(vec![420, 1140, 1800, 2940, 2880, 3960], strengths);
         assert_eq!(13140i64, strengths.iter().sum::<i64>());
         Ok(())
     }
 
+    #[test]
+    fn bad_instruction() {
+        let e = load_instructions("noop\naddx 3\nadd 5\n".as_bytes()).unwrap_err();
+        assert_eq!("day 10, line 3, column 1: expected 'noop' or 'addx', found 'add'", e.to_string());
+        let e = load_instructions("addx\n".as_bytes()).unwrap_err().downcast::<ParseError>().unwrap();
+        assert_eq!((1, 5), (e.line, e.column));
+    }
+
     #[test]
     fn part_2_test() -> Result<()> {
-        let answer = "##..##..##..##..##..##..##..##..##..##..\n###...###...###...###...###...###...###.\n####....####....####....####....####....\n#####.....#####.....#####.....#####.....\n######......######......######......####\n#######.......#######.......#######.....\n";
-        let screen = print_screen(&load_instructions("day10ex.txt")?);
-        // print!("{}", screen);
-        assert_eq!(answer, screen);
+        snapshot::assert_snapshot("day10ex_screen", &print_screen(&load_instructions(open("day10ex.txt")?)?, 40, 6));
         Ok(())
     }
+
+    #[test]
+    fn visualize() -> Result<()> {
+        let day = Day10::parse(&mut open("day10ex.txt")?)?;
+        let frames = visualize::text_frames(&day, 2)?;
+        assert_eq!(240, frames.len());
+        assert!(frames[2].starts_with("cycle 3: X = 16\n##.  "));
+        assert!(frames[2].ends_with("\n  ^            ===                      \n"));
+        let screen = print_screen(&day.instructions, 40, 6);
+        assert!(frames[239].contains(&screen));
+        Ok(())
+    }
+
+    #[test]
+    fn screen_is_40_by_6() {
+        property::check(200, |rng, size| Lines(generate::day10(rng, size)), |input| {
+            let Ok(instructions) = load_instructions(input.0.as_bytes()) else {
+                return Verdict::Discard;
+            };
+            let screen = print_screen(&instructions, 40, 6);
+            let rows: Vec<&str> = screen.lines().collect();
+            Verdict::check(rows.len() == 6 && rows.iter().all(|r| r.len() == 40 && r.chars().all(|c| c == '#' || c == '.')), || screen.clone())
+        });
+    }
 }
@@ -1,177 +1,450 @@
-#[cfg(test)]
-mod tests {
-    use std::collections::VecDeque;
-    use std::fs::File;
-    use std::io::{BufReader, Read};
-    use anyhow::Result;
-
-    enum Operation {
-        Add(Option<u64>),
-        Subtract(Option<u64>),
-        Multiply(Option<u64>),
-        Divide(Option<u64>),
-    }
-
-    impl From<&str> for Operation {
-        fn from(input: &str) -> Self {
-            const PREFIX: &str = "  Operation: new = old ";
-            if !input.starts_with(PREFIX) {
-                panic!("Unexpected operation: {}", input);
-            }
-            let number = if &input[(PREFIX.len() + 2)..] == "old" { None } else { Some(input[(PREFIX.len() + 2)..].parse::<u64>().unwrap()) };
-            match input.chars().nth(PREFIX.len()).unwrap() {
-                '+' => Operation::Add(number),
-                '-' => Operation::Subtract(number),
-                '*' => Operation::Multiply(number),
-                '/' => Operation::Divide(number),
-                other => panic!("Unexpected operation sign '{}' ({})", other, input),
-            }
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::BufRead;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::metrics;
+use crate::params::{self, Changes};
+use crate::parse::{ParseError, Scanner};
+use crate::repl::{optional_arg, Command, Explore};
+use crate::trace::{self, Level};
+use crate::{Answer, Solution};
+
+const DAY: u8 = 11;
+
+#[derive(Debug, Clone, Serialize)]
+pub enum Operation {
+    Add(Option<u64>),
+    Subtract(Option<u64>),
+    Multiply(Option<u64>),
+    Divide(Option<u64>),
+}
+
+impl FromStr for Operation {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut scanner = Scanner::new(DAY, s);
+        scanner.literal("  Operation: new = old ")?;
+        let sign = scanner.one_of("operation sign (+, -, * or /)", &['+', '-', '*', '/'])?;
+        scanner.literal(" ")?;
+        let column = scanner.column();
+        let number = if scanner.accept("old") { None } else { Some(scanner.number("a number or 'old'")?) };
+        scanner.end()?;
+        if sign == '/' && number == Some(0) {
+            Err(ParseError::new(DAY, "a non-zero divisor", "'0'").at_column(column))?;
         }
+        Ok(match sign {
+            '+' => Operation::Add(number),
+            '-' => Operation::Subtract(number),
+            '*' => Operation::Multiply(number),
+            _ => Operation::Divide(number),
+        })
     }
+}
 
-    impl Operation {
-        fn apply(&self, old: u64) -> u64 {
-            match self {
-                Operation::Add(number) => match number {
-                    None => old + old,
-                    Some(number) => old + number,
-                }
-                Operation::Subtract(number) => match number {
-                    None => old - old,
-                    Some(number) => old - number,
-                }
-                Operation::Multiply(number) => match number {
-                    None => old * old,
-                    Some(number) => old * number,
-                }
-                Operation::Divide(number) => match number {
-                    None => old / old,
-                    Some(number) => old / number,
-                }
-            }
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (sign, number) = match self {
+            Operation::Add(number) => ('+', number),
+            Operation::Subtract(number) => ('-', number),
+            Operation::Multiply(number) => ('*', number),
+            Operation::Divide(number) => ('/', number),
+        };
+        match number {
+            None => write!(f, "new = old {} old", sign),
+            Some(number) => write!(f, "new = old {} {}", sign, number),
         }
     }
+}
 
-    struct Throw {
-        divisible_by: u64,
-        true_monkey: usize,
-        false_monkey: usize,
+impl Operation {
+    /// The new worry level, or an error if it would go below zero or past `u64::MAX`.
+    pub fn apply(&self, old: u64) -> Result<u64> {
+        let new = match self {
+            Operation::Add(number) => match number {
+                None => old.checked_add(old),
+                Some(number) => old.checked_add(*number),
+            }
+            Operation::Subtract(number) => match number {
+                None => Some(0),
+                Some(number) => old.checked_sub(*number),
+            }
+            Operation::Multiply(number) => match number {
+                None => old.checked_mul(old),
+                Some(number) => old.checked_mul(*number),
+            }
+            Operation::Divide(number) => match number {
+                None => Some(1),
+                Some(number) => old.checked_div(*number),
+            }
+        };
+        new.ok_or_else(|| anyhow!("Worry level {} is out of range after {}", old, self))
     }
+}
 
-    impl From<&[&str]> for Throw {
-        fn from(input: &[&str]) -> Self {
-            const TEST_PREFIX: &str = "  Test: divisible by ";
-            const TRUE_PREFIX: &str = "    If true: throw to monkey ";
-            const FALSE_PREFIX: &str = "    If false: throw to monkey ";
+#[derive(Debug, Clone, Serialize)]
+pub struct Throw {
+    pub divisible_by: u64,
+    pub true_monkey: usize,
+    pub false_monkey: usize,
+}
 
-            if input[0].starts_with(TEST_PREFIX) {
-                if input[1].starts_with(TRUE_PREFIX) {
-                    if input[2].starts_with(FALSE_PREFIX) {
-                        return Throw {
-                            divisible_by: input[0][TEST_PREFIX.len()..].parse::<u64>().unwrap(),
-                            true_monkey: input[1][TRUE_PREFIX.len()..].parse::<usize>().unwrap(),
-                            false_monkey: input[2][FALSE_PREFIX.len()..].parse::<usize>().unwrap(),
-                        };
-                    }
-                }
-            }
-            panic!("Unexpected monkey test input: {}", input.join("\n"));
+impl TryFrom<&[&str]> for Throw {
+    type Error = ParseError;
+
+    /// Parses the test and both throw targets, with errors given relative to the first of those lines.
+    fn try_from(input: &[&str]) -> Result<Self, Self::Error> {
+        fn line<'a>(input: &[&'a str], i: usize, prefix: &str) -> Result<Scanner<'a>, ParseError> {
+            let line = input.get(i).ok_or_else(|| ParseError::new(DAY, format!("'{}'", prefix.trim()), "end of monkey").at_line(i + 1))?;
+            let mut scanner = Scanner::new(DAY, line);
+            scanner.literal(prefix).map_err(|e| e.at_line(i + 1))?;
+            Ok(scanner)
         }
-    }
 
-    impl Throw {
-        fn throw(&self, item: u64) -> (usize, u64) {
-            if item % self.divisible_by == 0 {
-                (self.true_monkey, item)
-            } else {
-                (self.false_monkey, item)
-            }
+        fn number<T: FromStr>(mut scanner: Scanner, i: usize, what: &str) -> Result<T, ParseError> {
+            let number = scanner.number(what).map_err(|e| e.at_line(i + 1))?;
+            scanner.end().map_err(|e| e.at_line(i + 1))?;
+            Ok(number)
+        }
+
+        let test = line(input, 0, "  Test: divisible by ")?;
+        let column = test.column();
+        let divisible_by = number::<u64>(test, 0, "divisor")?;
+        if divisible_by == 0 {
+            Err(ParseError::new(DAY, "a non-zero divisor", "'0'").at_line(1).at_column(column))?;
         }
+        Ok(Throw {
+            divisible_by,
+            true_monkey: number(line(input, 1, "    If true: throw to monkey ")?, 1, "monkey number")?,
+            false_monkey: number(line(input, 2, "    If false: throw to monkey ")?, 2, "monkey number")?,
+        })
     }
+}
 
-    struct Monkey {
-        items: VecDeque<u64>,
-        operation: Operation,
-        throw: Throw,
-        inspected: usize,
+impl Throw {
+    pub fn throw(&self, item: u64) -> (usize, u64) {
+        if item.is_multiple_of(self.divisible_by) {
+            (self.true_monkey, item)
+        } else {
+            (self.false_monkey, item)
+        }
     }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Monkey {
+    pub items: VecDeque<u64>,
+    pub operation: Operation,
+    pub throw: Throw,
+    pub inspected: usize,
+}
+
+impl TryFrom<&str> for Monkey {
+    type Error = ParseError;
 
-    impl From<&str> for Monkey {
-        fn from(input: &str) -> Self {
-            const ITEMS_PREFIX: &str = "  Starting items: ";
+    /// Parses one monkey's block of lines, with errors given relative to its first line.
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        fn header(line: &str) -> Result<(), ParseError> {
+            let mut scanner = Scanner::new(DAY, line);
+            scanner.literal("Monkey ")?;
+            scanner.number::<usize>("monkey number")?;
+            scanner.literal(":")?;
+            scanner.end()
+        }
 
-            let lines = input.split("\n").collect::<Vec<&str>>();
-            Monkey {
-                items: lines[1][ITEMS_PREFIX.len()..].split(", ").map(|i| i.parse::<u64>().unwrap()).collect(),
-                operation: lines[2].into(),
-                throw: lines[3..].into(),
-                inspected: 0,
+        fn items(line: &str) -> Result<VecDeque<u64>, ParseError> {
+            let mut items = VecDeque::new();
+            let mut scanner = Scanner::new(DAY, line);
+            scanner.literal("  Starting items: ")?;
+            while !scanner.is_empty() {
+                if !items.is_empty() {
+                    scanner.literal(", ")?;
+                }
+                items.push_back(scanner.number("worry level")?);
             }
+            Ok(items)
+        }
+
+        let lines = input.lines().collect::<Vec<&str>>();
+        let line = |i: usize| lines.get(i).copied().unwrap_or_default();
+        header(line(0)).map_err(|e| e.at_line(1))?;
+        Ok(Monkey {
+            items: items(line(1)).map_err(|e| e.at_line(2))?,
+            operation: line(2).parse().map_err(|e: ParseError| e.at_line(3))?,
+            throw: lines.get(3..).unwrap_or_default().try_into().map_err(|e: ParseError| e.after_lines(3))?,
+            inspected: 0,
+        })
+    }
+}
+
+impl Monkey {
+    pub fn turn(&mut self, worry_divisor: u64) -> Result<Vec<(usize, u64)>> {
+        let mut throws = vec![];
+        while let Some(item) = self.items.pop_front() {
+            self.inspected += 1;
+            throws.push(self.throw.throw(self.operation.apply(item)? / worry_divisor));
         }
+        Ok(throws)
     }
+}
 
-    impl Monkey {
-        fn turn(&mut self, worry_divisor: u64) -> Vec<(usize, u64)> {
-            let mut throws = vec![];
-            while let Some(item) = self.items.pop_front() {
-                self.inspected += 1;
-                throws.push(self.throw.throw(self.operation.apply(item) / worry_divisor));
+pub fn load_monkeys(mut reader: impl BufRead) -> Result<Vec<Monkey>> {
+    let mut monkeys: Vec<Monkey> = vec![];
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    let mut line = 0;
+    let mut starts = vec![];
+    for lines in input.split("\n\n") {
+        if lines.trim().is_empty() {
+            continue;
+        }
+        starts.push(line);
+        monkeys.push(lines.try_into().map_err(|e: ParseError| e.after_lines(line))?);
+        line += lines.lines().count() + 1;
+    }
+    if monkeys.len() < 2 {
+        Err(ParseError::new(DAY, "at least two monkeys", monkeys.len().to_string()).at_line(line))?;
+    }
+    for (monkey, start) in monkeys.iter().zip(starts) {
+        for (target, offset) in [(monkey.throw.true_monkey, 5), (monkey.throw.false_monkey, 6)] {
+            if target >= monkeys.len() {
+                Err(ParseError::new(DAY, format!("a monkey number below {}", monkeys.len()), target.to_string()).at_line(start + offset))?;
             }
-            throws
         }
     }
+    Ok(monkeys)
+}
 
-    fn load_monkeys(file: &str) -> Result<Vec<Monkey>> {
-        let mut monkeys = vec![];
-        let mut input = String::new();
-        BufReader::new(File::open(file)?).read_to_string(&mut input)?;
-        for lines in input.split("\n\n") {
-            monkeys.push(lines.into());
+/// The product of every monkey's divisor, which worry levels can be kept below without changing
+/// where any item is thrown.
+pub fn common_multiple(monkies: &[Monkey]) -> Result<u64> {
+    let product = monkies.iter().try_fold(1u64, |product, m| product.checked_mul(m.throw.divisible_by));
+    product.ok_or_else(|| anyhow!("The monkeys' divisors multiply to more than {}", u64::MAX))
+}
+
+/// Gives every monkey a turn in order, keeping worry levels below `max`.
+pub fn play_round(monkies: &mut [Monkey], worry_divisor: u64, max: u64) -> Result<()> {
+    for i in 0..monkies.len() {
+        for (monkey, item) in monkies[i].turn(worry_divisor)? {
+            trace::event(Level::Step, "throw", || json!({ "monkey": i, "worry": item % max, "to": monkey }));
+            monkies[monkey].items.push_back(item % max);
         }
-        Ok(monkeys)
     }
+    Ok(())
+}
 
-    fn calculate_monkey_business(monkies: &mut Vec<Monkey>, rounds: usize, worry_divisor: u64) -> usize {
-        let max = monkies.iter().map(|m| m.throw.divisible_by).reduce(|a, i| a * i).unwrap();
-        for _round in 1..=rounds {
-            for i in 0..monkies.len() {
-                for (monkey, item) in monkies[i].turn(worry_divisor) {
-                    monkies[monkey].items.push_back(item % max);
+pub fn calculate_monkey_business(monkies: &mut [Monkey], rounds: usize, worry_divisor: u64) -> Result<usize> {
+    let max = common_multiple(monkies)?;
+    let inspected_before: usize = monkies.iter().map(|m| m.inspected).sum();
+    for round in 1..=rounds {
+        play_round(monkies, worry_divisor, max)?;
+        trace::event(Level::Summary, "round", || json!({ "round": round, "inspected": monkies.iter().map(|m| m.inspected).collect::<Vec<_>>() }));
+    }
+    metrics::count("rounds", rounds as u64);
+    metrics::count("inspections", (monkies.iter().map(|m| m.inspected).sum::<usize>() - inspected_before) as u64);
+    monkies.sort_unstable_by_key(|m| m.inspected);
+    monkies.reverse();
+    let (first, second) = (monkies[0].inspected, monkies[1].inspected);
+    first.checked_mul(second).ok_or_else(|| anyhow!("The monkey business of {} and {} inspections is too big", first, second))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Params {
+    pub part_1_rounds: usize,
+    pub part_2_rounds: usize,
+    /// How much relief divides worry levels by in part 1. Part 2 has no relief.
+    pub worry_divisor: u64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params { part_1_rounds: 20, part_2_rounds: 10000, worry_divisor: 3 }
+    }
+}
+
+const COMMANDS: [Command; 3] = [
+    Command::new("round", "[n]", "plays n more rounds (default 1) with part 1's relief, showing what each monkey holds"),
+    Command::new("monkeys", "", "how many times each monkey has inspected items"),
+    Command::new("reset", "", "gives the monkeys back their starting items"),
+];
+
+/// Plays rounds of keep away.
+struct Explorer<'a> {
+    day: &'a Day11,
+    monkeys: Vec<Monkey>,
+    rounds: usize,
+}
+
+impl Explore for Explorer<'_> {
+    fn commands(&self) -> &'static [Command] {
+        &COMMANDS
+    }
+
+    fn run(&mut self, command: &str, args: &[&str]) -> Result<String> {
+        match command {
+            "round" => {
+                let max = common_multiple(&self.monkeys)?;
+                for _ in 0..optional_arg(args, 0, "number of rounds", 1)? {
+                    play_round(&mut self.monkeys, self.day.params.worry_divisor, max)?;
+                    self.rounds += 1;
                 }
+                let mut out = format!("After round {}:\n", self.rounds);
+                for (i, monkey) in self.monkeys.iter().enumerate() {
+                    let items: Vec<String> = monkey.items.iter().map(u64::to_string).collect();
+                    out += &format!("Monkey {}: {}\n", i, items.join(", "));
+                }
+                Ok(out)
+            }
+            "monkeys" => Ok(self.monkeys.iter().enumerate().map(|(i, monkey)| format!("Monkey {} inspected items {} times.\n", i, monkey.inspected)).collect()),
+            _ => {
+                self.monkeys = self.day.monkeys.clone();
+                self.rounds = 0;
+                Ok(String::new())
             }
-            // if round == 1 || round == 20 || round % 1000 == 0 {
-            //     println!("== After round {} ==", round);
-            //     for i in 0..monkies.len() {
-            //         println!("Monkey {} inspected items {} times", i, monkies[i].inspected);
-            //     }
-            // }
         }
-        monkies.sort_unstable_by_key(|m| m.inspected);
-        monkies.reverse();
-        monkies[0].inspected * monkies[1].inspected
+    }
+}
+
+pub struct Day11 {
+    monkeys: Vec<Monkey>,
+    params: Params,
+}
+
+impl Solution for Day11 {
+    fn parse(input: &mut dyn BufRead) -> Result<Self> {
+        Ok(Day11 { monkeys: load_monkeys(input)?, params: Params::default() })
+    }
+
+    fn part_1(&self) -> Result<Answer> {
+        Ok(calculate_monkey_business(&mut self.monkeys.clone(), self.params.part_1_rounds, self.params.worry_divisor)?.into())
+    }
+
+    fn part_2(&self) -> Result<Answer> {
+        Ok(calculate_monkey_business(&mut self.monkeys.clone(), self.params.part_2_rounds, 1)?.into())
+    }
+
+    fn model(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(&self.monkeys).ok()
+    }
+
+    fn configure(&mut self, changes: &Changes) -> Result<()> {
+        self.params = params::apply(&self.params, changes)?;
+        if self.params.worry_divisor == 0 {
+            Err(anyhow!("worry_divisor can't be 0"))?;
+        }
+        Ok(())
+    }
+
+    fn params(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(&self.params).ok()
+    }
+
+    fn explorer(&self) -> Option<Box<dyn Explore + '_>> {
+        Some(Box::new(Explorer { day: self, monkeys: self.monkeys.clone(), rounds: 0 }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generate;
+    use crate::open;
+    use crate::property::{self, Lines, Verdict};
+    use crate::repl;
+
+    use super::*;
+
+    #[test]
+    fn traces() -> Result<()> {
+        let monkeys = load_monkeys(open("day11ex.txt")?)?;
+        let (business, events) = trace::record(Level::Step, || calculate_monkey_business(&mut monkeys.clone(), 1, 3));
+        business?;
+        assert_eq!("Monkey 0 throws an item with worry level 500 to monkey 3.", events[0].to_string());
+        assert_eq!("Monkey 3 throws an item with worry level 1046 to monkey 1.", events[events.len() - 2].to_string());
+        assert_eq!("== After round 1 ==\nMonkey 0 inspected items 2 times.\nMonkey 1 inspected items 4 times.\nMonkey 2 inspected items 3 times.\nMonkey 3 inspected items 5 times.", events[events.len() - 1].to_string());
+        let (_, events) = trace::record(Level::Summary, || calculate_monkey_business(&mut monkeys.clone(), 20, 3));
+        assert_eq!(20, events.len());
+        Ok(())
+    }
+
+    #[test]
+    fn explore() -> Result<()> {
+        let out = repl::session(11, "round\nround 19\nmonkeys\nreset\nmonkeys")?;
+        let expected = "After round 1:\nMonkey 0: 20, 23, 27, 26\nMonkey 1: 2080, 25, 167, 207, 401, 1046\nMonkey 2: \nMonkey 3: \n\
+            After round 20:\nMonkey 0: 10, 12, 14, 26, 34\nMonkey 1: 245, 93, 53, 199, 115\nMonkey 2: \nMonkey 3: \n\
+            Monkey 0 inspected items 101 times.\nMonkey 1 inspected items 95 times.\nMonkey 2 inspected items 7 times.\nMonkey 3 inspected items 105 times.\n\
+            Monkey 0 inspected items 0 times.\nMonkey 1 inspected items 0 times.\nMonkey 2 inspected items 0 times.\nMonkey 3 inspected items 0 times.\n";
+        assert_eq!(expected, out);
+        Ok(())
     }
 
     #[test]
     fn part_1() -> Result<()> {
-        println!("Day 11 part 1: {}", calculate_monkey_business(&mut load_monkeys("day11.txt")?, 20, 3));
+        println!("Day 11 part 1: {}", calculate_monkey_business(&mut load_monkeys(open("day11.txt")?)?, 20, 3)?);
         Ok(())
     }
 
     #[test]
     fn part_2() -> Result<()> {
-        println!("Day 11 part 2: \n{}", calculate_monkey_business(&mut load_monkeys("day11.txt")?, 10000, 1));
+        println!("Day 11 part 2: \n{}", calculate_monkey_business(&mut load_monkeys(open("day11.txt")?)?, 10000, 1)?);
         Ok(())
     }
 
     #[test]
     fn part_1_test() -> Result<()> {
-        assert_eq!(10605, calculate_monkey_business(&mut load_monkeys("day11ex.txt")?, 20, 3));
+        assert_eq!(10605, calculate_monkey_business(&mut load_monkeys(open("day11ex.txt")?)?, 20, 3)?);
+        Ok(())
+    }
+
+    #[test]
+    fn bad_monkeys() -> Result<()> {
+        let input = std::fs::read_to_string("day11ex.txt")?;
+        let e = load_monkeys(input.replace("old * 19", "old ^ 19").as_bytes()).unwrap_err();
+        assert_eq!("day 11, line 3, column 24: expected operation sign (+, -, * or /), found '^'", e.to_string());
+        let e = load_monkeys(input.replace("If false: throw to monkey 3\n\nMonkey 3", "If false: throw to monkey 4\n\nMonkey 3").as_bytes()).unwrap_err();
+        assert_eq!("day 11, line 20: expected a monkey number below 4, found 4", e.to_string());
+        let e = load_monkeys(input.replace("divisible by 13", "divisible by 0").as_bytes()).unwrap_err();
+        assert_eq!("day 11, line 18, column 22: expected a non-zero divisor, found '0'", e.to_string());
+        let e = load_monkeys(input.replace("old * 19", "old / 0").as_bytes()).unwrap_err();
+        assert_eq!("day 11, line 3, column 26: expected a non-zero divisor, found '0'", e.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn out_of_range() -> Result<()> {
+        let input = std::fs::read_to_string("day11ex.txt")?;
+        let day = Day11::parse(&mut input.replace("Starting items: 79, 98", &format!("Starting items: 79, {}", u64::MAX)).as_bytes())?;
+        assert_eq!(format!("Worry level {} is out of range after new = old * 19", u64::MAX), day.part_1().unwrap_err().to_string());
+        let day = Day11::parse(&mut input.replace("old + 6", "old - 100").as_bytes())?;
+        assert_eq!("Worry level 54 is out of range after new = old - 100", day.part_2().unwrap_err().to_string());
+
+        let mut monkeys = load_monkeys(input.as_bytes())?;
+        monkeys[0].throw.divisible_by = u64::MAX;
+        assert_eq!(format!("The monkeys' divisors multiply to more than {}", u64::MAX), common_multiple(&monkeys).unwrap_err().to_string());
         Ok(())
     }
 
     #[test]
     fn part_2_test() -> Result<()> {
-        assert_eq!(2713310158, calculate_monkey_business(&mut load_monkeys("day11ex.txt")?, 10000, 1));
+        assert_eq!(2713310158, calculate_monkey_business(&mut load_monkeys(open("day11ex.txt")?)?, 10000, 1)?);
         Ok(())
     }
+
+    #[test]
+    fn business_grows_with_rounds() {
+        property::check(100, |rng, size| Lines(generate::day11(rng, size)), |input| {
+            let Ok(monkeys) = load_monkeys(input.0.as_bytes()) else {
+                return Verdict::Discard;
+            };
+            let (fewer, more) = match (calculate_monkey_business(&mut monkeys.clone(), 20, 3), calculate_monkey_business(&mut monkeys.clone(), 40, 3)) {
+                (Ok(fewer), Ok(more)) => (fewer, more),
+                (Err(e), _) | (_, Err(e)) => return Verdict::Fail(e.to_string()),
+            };
+            Verdict::check(fewer <= more, || format!("{} after 20 rounds but {} after 40", fewer, more))
+        });
+    }
 }
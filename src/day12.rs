@@ -1,113 +1,258 @@
-#[cfg(test)]
-mod tests {
-    use std::collections::HashSet;
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-    use anyhow::Result;
-
-    struct Map {
-        grid: Vec<Vec<u8>>,
-        start: (usize, usize),
-        end: (usize, usize),
-    }
-
-    impl Map {
-        fn neighbours(&self, pos: &(usize, usize)) -> Vec<(usize, usize)> {
-            let mut result = vec![];
-            if pos.0 > 0 {
-                let new = (pos.0 - 1, pos.1);
-                if self.grid[new.0][new.1] <= (self.grid[pos.0][pos.1] + 1) {
-                    result.push(new);
-                }
-            }
-            if pos.0 < (self.grid.len() - 1) {
-                let new = (pos.0 + 1, pos.1);
-                if self.grid[new.0][new.1] <= (self.grid[pos.0][pos.1] + 1) {
-                    result.push(new);
-                }
-            }
-            if pos.1 > 0 {
-                let new = (pos.0, pos.1 - 1);
-                if self.grid[new.0][new.1] <= (self.grid[pos.0][pos.1] + 1) {
-                    result.push(new);
-                }
+use std::collections::{HashSet, VecDeque};
+use std::io::BufRead;
+
+use anyhow::{anyhow, Result};
+
+use crate::differential::Implementation;
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::metrics;
+use crate::parse::ParseError;
+use crate::repl::{arg, Command, Explore};
+use crate::visualize::{Frame, Frames, Rgb, Visualize};
+use crate::{Answer, Solution};
+
+const DAY: u8 = 12;
+
+pub struct Map {
+    pub grid: Grid<u8>,
+    pub start: Point<usize>,
+    pub end: Point<usize>,
+}
+
+impl Map {
+    /// The neighbours of `pos` that are at most one step higher, so can be climbed to.
+    pub fn neighbours(&self, pos: Point<usize>) -> Vec<Point<usize>> {
+        let height = self.grid[pos];
+        self.grid.neighbours(pos).filter(|&new| self.grid[new] <= height + 1).collect()
+    }
+}
+
+pub fn load_map(input: impl BufRead) -> Result<Map> {
+    let mut start = None;
+    let mut end = None;
+    let grid = Grid::parse(DAY, input, "heights", |pos, c| {
+        let error = |expected: &str| ParseError::new(DAY, expected, format!("'{}'", c));
+        match c {
+            'S' if start.is_some() => Err(error("only one start")),
+            'E' if end.is_some() => Err(error("only one end")),
+            'S' => {
+                start = Some(pos);
+                Ok(b'a')
             }
-            if pos.1 < (self.grid[pos.0].len() - 1) {
-                let new = (pos.0, pos.1 + 1);
-                if self.grid[new.0][new.1] <= (self.grid[pos.0][pos.1] + 1) {
-                    result.push(new);
-                }
+            'E' => {
+                end = Some(pos);
+                Ok(b'z')
             }
-            result
+            'a'..='z' => Ok(c as u8),
+            _ => Err(error("a height (a-z), 'S' or 'E'")),
         }
-    }
+    })?;
+    let start = start.ok_or_else(|| ParseError::new(DAY, "a start ('S')", "end of input").at_line(grid.height()))?;
+    let end = end.ok_or_else(|| ParseError::new(DAY, "an end ('E')", "end of input").at_line(grid.height()))?;
+    Ok(Map { grid, start, end })
+}
 
-    fn load_map(file: &str) -> Result<Map> {
-        let mut start = (0usize, 0usize);
-        let mut end = (0usize, 0usize);
-        let mut grid = vec![];
-        for (i, line) in BufReader::new(File::open(file)?).lines().enumerate() {
-            let mut row = vec![];
-            for (j, pos) in line?.as_bytes().into_iter().enumerate() {
-                match *pos {
-                    b'S' => {
-                        start = (i, j);
-                        row.push(b'a');
-                    }
-                    b'E' => {
-                        end = (i, j);
-                        row.push(b'z');
+pub fn shortest_path(map: &Map, start: Point<usize>) -> Option<Vec<Point<usize>>> {
+    search(map, vec![start], |_, _| Ok(())).ok().flatten()
+}
+
+/// Searches breadth first from any of `starts` to the end, one step at a time, returning the first
+/// path to reach it (just the start, if a start is the end). Before each step, `step` is given
+/// everywhere visited so far and the paths whose ends are the frontier; an error from it stops the
+/// search.
+pub fn search(
+    map: &Map,
+    starts: Vec<Point<usize>>,
+    mut step: impl FnMut(&HashSet<Point<usize>>, &[Vec<Point<usize>>]) -> Result<()>,
+) -> Result<Option<Vec<Point<usize>>>> {
+    if starts.contains(&map.end) {
+        return Ok(Some(vec![map.end]));
+    }
+    let mut visited: HashSet<Point<usize>> = starts.iter().copied().collect();
+    let mut paths: Vec<Vec<Point<usize>>> = starts.into_iter().map(|start| vec![start]).collect();
+    let mut expanded = 0;
+    while !paths.is_empty() {
+        step(&visited, &paths)?;
+        let mut new_paths = vec![];
+        for path in paths {
+            expanded += 1;
+            for neighbour in map.neighbours(*path.last().unwrap()) {
+                if !visited.contains(&neighbour) {
+                    let mut path = path.clone();
+                    path.push(neighbour);
+                    if neighbour == map.end {
+                        metrics::count("nodes_expanded", expanded);
+                        return Ok(Some(path));
                     }
-                    other => row.push(other),
+                    visited.insert(neighbour);
+                    new_paths.push(path);
                 }
             }
-            grid.push(row);
         }
-        Ok(Map { grid, start, end })
-    }
-
-    fn shortest_path(map: &Map, start: (usize, usize)) -> Option<Vec<(usize, usize)>> {
-        let mut visited = HashSet::new();
-        let mut paths = vec![vec![start]];
-        while !paths.is_empty() {
-            let mut new_paths = vec![];
-            for path in paths {
-                for neighbour in map.neighbours(path.last().unwrap()).into_iter() {
-                    if !visited.contains(&neighbour) {
-                        let mut path = path.clone();
-                        path.push(neighbour.clone());
-                        if neighbour == map.end {
-                            return Some(path);
-                        }
-                        visited.insert(neighbour);
-                        new_paths.push(path);
-                    }
+        paths = new_paths;
+    }
+    metrics::count("nodes_expanded", expanded);
+    Ok(None)
+}
+
+/// Draws the map with everywhere visited as '.', the frontier as '@' and a path as '*', over the
+/// heights ('a' to 'z') and the start and end ('S' and 'E').
+pub fn draw_search(map: &Map, visited: &HashSet<Point<usize>>, frontier: &[Vec<Point<usize>>], path: &[Point<usize>]) -> Grid<char> {
+    let mut grid = Grid::new(map.grid.width(), map.grid.height(), '.');
+    for (pos, &height) in map.grid.iter() {
+        grid[pos] = height as char;
+    }
+    visited.iter().for_each(|&pos| grid[pos] = '.');
+    frontier.iter().filter_map(|path| path.last()).for_each(|&pos| grid[pos] = '@');
+    path.iter().for_each(|&pos| grid[pos] = '*');
+    grid[map.start] = 'S';
+    grid[map.end] = 'E';
+    grid
+}
+
+/// Heights in shades of grey, the visited area in blue, the frontier in red and the path in yellow.
+fn palette(cell: char) -> Rgb {
+    match cell {
+        'a'..='z' => [(cell as u8 - b'a') * 8 + 40; 3],
+        '.' => [40, 80, 200],
+        '@' => [255, 48, 48],
+        '*' => [255, 220, 0],
+        'S' | 'E' => [0, 255, 0],
+        _ => [0, 0, 0],
+    }
+}
+
+pub fn shortest_of_all_as(map: &Map) -> Option<Vec<Point<usize>>> {
+    let mut shortest: Option<Vec<Point<usize>>> = None;
+    for (pos, &height) in map.grid.iter() {
+        if height == b'a' {
+            if let Some(path) = shortest_path(map, pos) {
+                if shortest.is_none() || shortest.as_ref().unwrap().len() > path.len() {
+                    shortest = Some(path);
                 }
             }
-            paths = new_paths;
         }
-        None
-    }
-
-    fn shortest_of_all_as(map: &Map) -> Option<Vec<(usize, usize)>> {
-        let mut shortest: Option<Vec<(usize, usize)>> = None;
-        for i in 0..map.grid.len() {
-            for j in 0..map.grid[0].len() {
-                if map.grid[i][j] == b'a' {
-                    if let Some(path) = shortest_path(&map, (i, j)) {
-                        if shortest.is_none() || shortest.as_ref().unwrap().len() > path.len() {
-                            shortest = Some(path);
-                        }
-                    }
-                }
+    }
+    shortest
+}
+
+/// How many steps it takes to reach the end from everywhere that can reach it, found with a single
+/// breadth first search backwards from the end rather than a search from each start.
+pub fn steps_to_end(map: &Map) -> Grid<Option<usize>> {
+    let mut steps = Grid::new(map.grid.width(), map.grid.height(), None);
+    steps[map.end] = Some(0);
+    let mut queue = VecDeque::from([map.end]);
+    while let Some(pos) = queue.pop_front() {
+        let next = steps[pos].map(|s| s + 1);
+        // Going backwards, a step can drop any amount but only climb one.
+        for from in map.grid.neighbours(pos) {
+            if steps[from].is_none() && map.grid[pos] <= map.grid[from] + 1 {
+                steps[from] = next;
+                queue.push_back(from);
             }
         }
-        shortest
+    }
+    steps
+}
+
+const COMMANDS: [Command; 1] = [Command::new("path", "<x> <y>", "the shortest path from a square to the end, drawn with '*'")];
+
+/// Finds paths from anywhere on the map.
+struct Explorer<'a> {
+    map: &'a Map,
+}
+
+impl Explore for Explorer<'_> {
+    fn commands(&self) -> &'static [Command] {
+        &COMMANDS
     }
 
+    fn run(&mut self, _command: &str, args: &[&str]) -> Result<String> {
+        let from = Point::new(arg(args, 0, "x")?, arg(args, 1, "y")?);
+        if !self.map.grid.contains(from) {
+            Err(anyhow!("{} is off the map, which is {} by {}", from, self.map.grid.width(), self.map.grid.height()))?;
+        }
+        let path = shortest_path(self.map, from).ok_or_else(|| anyhow!("No path from {} to the end", from))?;
+        Ok(format!("{} steps from {}\n{}", path.len() - 1, from, draw_search(self.map, &HashSet::new(), &[], &path)))
+    }
+}
+
+pub struct Day12 {
+    map: Map,
+}
+
+impl Solution for Day12 {
+    fn parse(input: &mut dyn BufRead) -> Result<Self> {
+        Ok(Day12 { map: load_map(input)? })
+    }
+
+    fn part_1(&self) -> Result<Answer> {
+        let path = shortest_path(&self.map, self.map.start).ok_or_else(|| anyhow!("No path from start to end"))?;
+        Ok((path.len() - 1).into())
+    }
+
+    fn part_2(&self) -> Result<Answer> {
+        let path = shortest_of_all_as(&self.map).ok_or_else(|| anyhow!("No path from any 'a' to end"))?;
+        Ok((path.len() - 1).into())
+    }
+
+    fn visualizer(&self) -> Option<&dyn Visualize> {
+        Some(self)
+    }
+
+    fn explorer(&self) -> Option<Box<dyn Explore + '_>> {
+        Some(Box::new(Explorer { map: &self.map }))
+    }
+
+    fn implementations(&self, part: u8) -> Vec<Implementation<'_>> {
+        vec![Implementation::new("backwards", move || {
+            let steps = steps_to_end(&self.map);
+            let best = match part {
+                1 => steps[self.map.start],
+                _ => self.map.grid.iter().filter(|(_, &height)| height == b'a').filter_map(|(pos, _)| steps[pos]).min(),
+            };
+            Ok(best.ok_or_else(|| anyhow!("No path to the end"))?.into())
+        })]
+    }
+}
+
+/// The search's frontier spreading out a step at a time, then the path it found. Part 2 searches
+/// from every 'a' at once, which finds the same shortest path as searching from each in turn.
+impl Visualize for Day12 {
+    fn visualize(&self, part: u8, frames: &mut Frames) -> Result<()> {
+        let map = &self.map;
+        let starts = match part {
+            1 => vec![map.start],
+            _ => map.grid.iter().filter(|(_, &height)| height == b'a').map(|(pos, _)| pos).collect(),
+        };
+        let mut steps = 0;
+        let path = search(map, starts, |visited, frontier| {
+            let caption = format!("step {}: {} visited, {} on the frontier", steps, visited.len(), frontier.len());
+            steps += 1;
+            frames.frame(|| Frame::new(caption, draw_search(map, visited, frontier, &[])).with_palette(palette))
+        })?;
+        let path = path.ok_or_else(|| anyhow!("No path to the end"))?;
+        let caption = format!("path of {} steps", path.len() - 1);
+        frames.frame(|| Frame::new(caption, draw_search(map, &HashSet::new(), &[], &path)).with_palette(palette))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::differential;
+    use crate::generate;
+    use crate::open;
+    use crate::params::Changes;
+    use crate::property::{self, Lines, Verdict};
+    use crate::repl;
+    use crate::visualize;
+
+    use super::*;
+
     #[test]
     fn part_1() -> Result<()> {
-        let map = load_map("day12.txt")?;
+        let map = load_map(open("day12.txt")?)?;
         println!("Day 12 part 1: {}", shortest_path(&map, map.start).unwrap().len() - 1);
         Ok(())
     }
@@ -115,20 +260,76 @@ mod tests {
     #[test]
     fn part_2() -> Result<()> {
         //commented out as it takes a couple of seconds to run
-        // println!("Day 12 part 2: \n{}", shortest_of_all_as(&load_map("day12.txt")?).unwrap().len() - 1);
+        // println!("Day 12 part 2: \n{}", shortest_of_all_as(&load_map(open("day12.txt")?)?).unwrap().len() - 1);
+        Ok(())
+    }
+
+    #[test]
+    fn visualize() -> Result<()> {
+        let day = Day12::parse(&mut open("day12ex.txt")?)?;
+        let frames = visualize::text_frames(&day, 1)?;
+        assert_eq!("step 0: 1 visited, 1 on the frontier\nSabqponm\nabcryxxl\naccszExk\nacctuvwj\nabdefghi\n", frames[0]);
+        assert_eq!("path of 31 steps\nSab*****\n**c*****\na*c**E**\na*******\nab******\n", frames[frames.len() - 1]);
+        let frames = visualize::text_frames(&day, 2)?;
+        assert!(frames[frames.len() - 1].starts_with("path of 29 steps\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn explore() -> Result<()> {
+        let out = repl::session(12, "path 0 0\npath 2 4\npath 5 2\npath 8 0\npath 0")?;
+        let expected = "31 steps from 0,0\nSab*****\n**c*****\na*c**E**\na*******\nab******\n\
+            25 steps from 2,4\nSab*****\nabc*****\nacc**E**\nacc*****\nab******\n\
+            0 steps from 5,2\nSabqponm\nabcryxxl\naccszExk\nacctuvwj\nabdefghi\n\
+            Error: 8,0 is off the map, which is 8 by 5\nError: Missing y\n";
+        assert_eq!(expected, out);
         Ok(())
     }
 
     #[test]
     fn part_1_test() -> Result<()> {
-        let map = load_map("day12ex.txt")?;
+        let map = load_map(open("day12ex.txt")?)?;
         assert_eq!(31, shortest_path(&map, map.start).unwrap().len() - 1);
+        assert_eq!(Some(vec![map.end]), shortest_path(&map, map.end));
+        // No path steps back onto the start.
+        search(&map, vec![map.start], |_, frontier| {
+            assert!(frontier.iter().all(|path| !path[1..].contains(&map.start)), "{:?}", frontier);
+            Ok(())
+        })?;
         Ok(())
     }
 
     #[test]
     fn part_2_test() -> Result<()> {
-        assert_eq!(29, shortest_of_all_as(&load_map("day12ex.txt")?).unwrap().len() - 1);
+        assert_eq!(29, shortest_of_all_as(&load_map(open("day12ex.txt")?)?).unwrap().len() - 1);
+        Ok(())
+    }
+
+    #[test]
+    fn implementations_agree() -> Result<()> {
+        let day = crate::day(12).unwrap();
+        let example = std::fs::read_to_string("day12ex.txt")?;
+        assert_eq!(2, differential::check(day, 1, "day12.txt", &std::fs::read_to_string("day12.txt")?, &Changes::new()).map_err(|d| anyhow!("{}", d))?);
+        for part in [1, 2] {
+            differential::check(day, part, "day12ex.txt", &example, &Changes::new()).map_err(|d| anyhow!("{}", d))?;
+            // Walled in by 'z's, so there is no path at all.
+            differential::check(day, part, "no path", "Sbz\nzzE\n", &Changes::new()).map_err(|d| anyhow!("{}", d))?;
+            differential::check_generated(day, part, 30, |seed, size| generate::generate(12, seed, size), &Changes::new()).map_err(|d| anyhow!("{}", d))?;
+        }
         Ok(())
     }
+
+    #[test]
+    fn any_a_is_no_further_than_the_start() {
+        property::check(30, |rng, size| Lines(generate::day12(rng, size)), |input| {
+            let Ok(map) = load_map(input.0.as_bytes()) else {
+                return Verdict::Discard;
+            };
+            match (shortest_path(&map, map.start), shortest_of_all_as(&map)) {
+                (Some(start), Some(any)) => Verdict::check(any.len() <= start.len(), || format!("start takes {} steps but the best 'a' takes {}", start.len() - 1, any.len() - 1)),
+                (None, _) => Verdict::Discard,
+                (Some(_), None) => Verdict::Fail("the start is an 'a' with a path, but no 'a' has one".to_string()),
+            }
+        });
+    }
 }
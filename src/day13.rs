@@ -1,99 +1,194 @@
-#[cfg(test)]
-mod tests {
-    use std::cmp::Ordering;
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-    use anyhow::{Context, Result};
-    use serde::Deserialize;
-
-    #[derive(Debug, Clone, Deserialize, PartialEq)]
-    #[serde(untagged)]
-    enum Data {
-        List(Vec<Data>),
-        Integer(u64),
-    }
+use std::cmp::Ordering;
+use std::io::BufRead;
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
 
-    impl TryFrom<&str> for Data {
-        type Error = anyhow::Error;
+use crate::metrics;
+use crate::parse::ParseError;
+use crate::{Answer, Solution};
+
+const DAY: u8 = 13;
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Data {
+    List(Vec<Data>),
+    Integer(u64),
+}
 
-        fn try_from(value: &str) -> Result<Self, Self::Error> {
-            serde_json::from_str(value).with_context(|| format!("deserializing: {}", value))
+impl TryFrom<&str> for Data {
+    type Error = ParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let data: Data = serde_json::from_str(value).map_err(|e| {
+            let found = value.chars().nth(e.column().saturating_sub(1)).map_or("end of line".to_string(), |c| format!("'{}'", c));
+            ParseError::new(DAY, "a packet of lists and integers", found).at_column(e.column())
+        })?;
+        if let Data::Integer(_) = data {
+            Err(ParseError::new(DAY, "a packet starting with '['", format!("'{}'", value)).at_column(1))?;
         }
+        Ok(data)
     }
+}
 
-    fn in_order(a: &Data, b: &Data) -> Ordering {
-        match a {
-            Data::List(l1) => match b {
-                Data::List(l2) => {
-                    let l1_len = l1.len();
-                    let l2_len = l2.len();
-
-                    for (d1, d2) in l1.into_iter().zip(l2.into_iter()) {
-                        match in_order(d1, d2) {
-                            Ordering::Equal => {},
-                            other => return other,
-                        }
-                    }
+pub fn in_order(a: &Data, b: &Data) -> Ordering {
+    match a {
+        Data::List(l1) => match b {
+            Data::List(l2) => {
+                let l1_len = l1.len();
+                let l2_len = l2.len();
 
-                    if l1_len < l2_len { Ordering::Less } else if l1_len == l2_len { Ordering::Equal } else { Ordering::Greater }
+                for (d1, d2) in l1.iter().zip(l2.iter()) {
+                    match in_order(d1, d2) {
+                        Ordering::Equal => {},
+                        other => return other,
+                    }
                 }
-                Data::Integer(i2) => in_order(&Data::List(l1.clone()), &Data::List(vec![Data::Integer(*i2)])),
-            }
-            Data::Integer(i1) => match b {
-                Data::List(l2) => in_order(&Data::List(vec![Data::Integer(*i1)]), &Data::List(l2.clone())),
-                Data::Integer(i2) => if i1 < i2 { Ordering::Less } else if i1 == i2 { Ordering::Equal } else { Ordering::Greater },
+
+                if l1_len < l2_len { Ordering::Less } else if l1_len == l2_len { Ordering::Equal } else { Ordering::Greater }
             }
+            Data::Integer(i2) => in_order(&Data::List(l1.clone()), &Data::List(vec![Data::Integer(*i2)])),
+        }
+        Data::Integer(i1) => match b {
+            Data::List(l2) => in_order(&Data::List(vec![Data::Integer(*i1)]), &Data::List(l2.clone())),
+            Data::Integer(i2) => if i1 < i2 { Ordering::Less } else if i1 == i2 { Ordering::Equal } else { Ordering::Greater },
         }
     }
+}
+
+pub fn are_packets_in_order(packet_pairs: &[(Data, Data)]) -> Vec<bool> {
+    metrics::count("comparisons", packet_pairs.len() as u64);
+    packet_pairs.iter().map(|(p1, p2)| in_order(p1, p2) != Ordering::Greater).collect()
+}
+
+pub fn decoder_key(packets: &mut Vec<Data>) -> usize {
+    let divider_1 = Data::List(vec![Data::List(vec![Data::Integer(2)])]);
+    let divider_2 = Data::List(vec![Data::List(vec![Data::Integer(6)])]);
+    packets.push(divider_1.clone());
+    packets.push(divider_2.clone());
+    let mut comparisons = 0;
+    packets.sort_unstable_by(|a, b| {
+        comparisons += 1;
+        in_order(a, b)
+    });
+    metrics::count("comparisons", comparisons);
+    (packets.iter().position(|p| p == &divider_1).unwrap() + 1) * (packets.iter().position(|p| p == &divider_2).unwrap() + 1)
+}
 
-    fn are_packets_in_order(packet_pairs: Vec<(Data, Data)>) -> Vec<bool> {
-        packet_pairs.iter().map(|(p1, p2)| in_order(p1, p2) != Ordering::Greater).collect()
+pub fn load_packets(input: impl BufRead) -> Result<Vec<Data>> {
+    let mut packets = vec![];
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        if !line.is_empty() {
+            packets.push(Data::try_from(line.as_str()).map_err(|e| e.at_line(i + 1))?);
+        }
     }
+    Ok(packets)
+}
 
-    fn decoder_key(packets: &mut Vec<Data>) -> usize {
-        let divider_1 = Data::List(vec![Data::List(vec![Data::Integer(2)])]);
-        let divider_2 = Data::List(vec![Data::List(vec![Data::Integer(6)])]);
-        packets.push(divider_1.clone());
-        packets.push(divider_2.clone());
-        packets.sort_unstable_by(|a, b| in_order(a, b));
-        (packets.iter().position(|p| p == &divider_1).unwrap() + 1) * (packets.iter().position(|p| p == &divider_2).unwrap() + 1)
+pub fn pair_packets(packets: Vec<Data>) -> Result<Vec<(Data, Data)>> {
+    let mut pairs = vec![];
+    let mut packets = packets.into_iter();
+    while let Some(p1) = packets.next() {
+        pairs.push((p1, packets.next().ok_or_else(|| anyhow!("Odd number of packets"))?));
     }
+    Ok(pairs)
+}
 
-    fn load_packets(file: &str) -> Result<Vec<Data>> {
-        Ok(BufReader::new(File::open(file)?).lines().map(|l| l.unwrap()).filter(|l| !l.is_empty()).map(|l| Data::try_from(l.as_str()).unwrap()).collect())
+pub fn load_packet_pairs(input: impl BufRead) -> Result<Vec<(Data, Data)>> {
+    pair_packets(load_packets(input)?)
+}
+
+/// Sums the (1-based) indices of the pairs that are in the right order.
+pub fn sum_in_order_indices(packet_pairs: &[(Data, Data)]) -> usize {
+    are_packets_in_order(packet_pairs).iter().enumerate().filter(|(_, o)| **o).map(|(i, _)| i + 1).sum::<usize>()
+}
+
+pub struct Day13 {
+    packets: Vec<Data>,
+}
+
+impl Solution for Day13 {
+    fn parse(input: &mut dyn BufRead) -> Result<Self> {
+        Ok(Day13 { packets: load_packets(input)? })
     }
 
-    fn load_packet_pairs(file: &str) -> Result<Vec<(Data, Data)>> {
-        let mut pairs = vec![];
-        let mut packets = load_packets(file)?.into_iter();
-        while let Some(p1) = packets.next() {
-            pairs.push((p1, packets.next().unwrap()));
-        }
-        Ok(pairs)
+    fn part_1(&self) -> Result<Answer> {
+        Ok(sum_in_order_indices(&pair_packets(self.packets.clone())?).into())
+    }
+
+    fn part_2(&self) -> Result<Answer> {
+        Ok(decoder_key(&mut self.packets.clone()).into())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::property::Shrink;
+
+    use crate::generate;
+    use crate::open;
+    use crate::property::{self, Verdict};
+
+    use super::*;
 
     #[test]
     fn part_1() -> Result<()> {
-        let packets = are_packets_in_order(load_packet_pairs("day13.txt")?);
-        println!("Day 13 part 1: {}", packets.iter().enumerate().filter(|(_, o)| **o).map(|(i, _)| i + 1).sum::<usize>());
+        println!("Day 13 part 1: {}", sum_in_order_indices(&load_packet_pairs(open("day13.txt")?)?));
         Ok(())
     }
 
     #[test]
     fn part_2() -> Result<()> {
-        println!("Day 13 part 2: {}", decoder_key(&mut load_packets("day13.txt")?));
+        println!("Day 13 part 2: {}", decoder_key(&mut load_packets(open("day13.txt")?)?));
         Ok(())
     }
 
     #[test]
     fn part_1_test() -> Result<()> {
-        assert_eq!(13, are_packets_in_order(load_packet_pairs("day13ex.txt")?).iter().enumerate().filter(|(_, o)| **o).map(|(i, _)| i + 1).sum::<usize>());
+        assert_eq!(13, sum_in_order_indices(&load_packet_pairs(open("day13ex.txt")?)?));
         Ok(())
     }
 
     #[test]
     fn part_2_test() -> Result<()> {
-        assert_eq!(140, decoder_key(&mut load_packets("day13ex.txt")?));
+        assert_eq!(140, decoder_key(&mut load_packets(open("day13ex.txt")?)?));
         Ok(())
     }
+
+    impl Shrink for Data {
+        fn shrink(&self) -> Vec<Self> {
+            match self {
+                Data::Integer(i) => i.shrink().into_iter().map(Data::Integer).collect(),
+                Data::List(items) => {
+                    // Try each nested list in place of the whole thing, then smaller lists.
+                    let mut candidates: Vec<Data> = items.iter().filter(|i| matches!(i, Data::List(_))).cloned().collect();
+                    candidates.extend(items.shrink().into_iter().map(Data::List));
+                    candidates
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn in_order_is_a_total_order() {
+        let packets = |rng: &mut crate::generate::Rng, size: usize| {
+            let mut packets = load_packets(generate::day13(rng, size.min(3)).as_bytes()).unwrap().into_iter().cycle();
+            (packets.next().unwrap(), packets.next().unwrap(), packets.next().unwrap())
+        };
+        property::check(500, packets, |(a, b, c)| {
+            for (x, y) in [(a, b), (b, c), (a, c)] {
+                if in_order(x, y) != in_order(y, x).reverse() {
+                    return Verdict::Fail(format!("not antisymmetric: {:?} then {:?}", in_order(x, y), in_order(y, x)));
+                }
+            }
+            for (x, y, z) in [(a, b, c), (a, c, b), (b, a, c), (b, c, a), (c, a, b), (c, b, a)] {
+                if in_order(x, y).is_le() && in_order(y, z).is_le() && in_order(x, z).is_gt() {
+                    return Verdict::Fail(format!("not transitive: {:?} <= {:?} <= {:?}", x, y, z));
+                }
+            }
+            Verdict::check(in_order(a, a).is_eq(), || format!("{:?} isn't equal to itself", a))
+        });
+    }
 }
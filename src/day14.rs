@@ -1,149 +1,406 @@
-#[cfg(test)]
-mod tests {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-    use anyhow::Result;
-
-    fn load_rock_structures(file: &str) -> Result<Vec<Vec<u8>>> {
-        let mut rocks = vec![];
-        let mut max_x = 0;
-        let mut max_y = 0;
-        for line in BufReader::new(File::open(file)?).lines() {
-            let mut structure = vec![];
-            for loc in line?.split(" -> ") {
-                let (x, y) = loc.split_once(",").unwrap();
-                let x = x.parse::<usize>()?;
-                let y = y.parse::<usize>()?;
-                max_x = max_x.max(x);
-                max_y = max_y.max(y);
-                structure.push((x, y));
+use std::io::BufRead;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::geometry::{Bounds, Point};
+use crate::grid::Grid;
+use crate::metrics;
+use crate::params::{self, Changes};
+use crate::parse::{ParseError, Scanner};
+use crate::trace::{self, Level};
+use crate::visualize::{Frame, Frames, Rgb, Visualize};
+use crate::{Answer, Solution};
+
+const DAY: u8 = 14;
+
+pub const SAND_SOURCE: Point<usize> = Point::new(500, 0);
+
+/// The furthest from the origin a rock or the sand source may be, which keeps the cave to a few
+/// million squares.
+pub const MAX_COORDINATE: usize = 2000;
+
+/// One line of the scan: a path of rock between points joined by horizontal or vertical lines.
+pub struct RockPath {
+    pub points: Vec<Point<usize>>,
+}
+
+impl FromStr for RockPath {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut scanner = Scanner::new(DAY, s);
+        let mut points: Vec<Point<usize>> = vec![];
+        loop {
+            let column = scanner.column();
+            let x = coordinate(&mut scanner, "x coordinate")?;
+            scanner.literal(",")?;
+            let point = Point::new(x, coordinate(&mut scanner, "y coordinate")?);
+            if let Some(&last) = points.last() {
+                if last.x != point.x && last.y != point.y {
+                    Err(ParseError::new(DAY, format!("a point in line with {}", last), point.to_string()).at_column(column))?;
+                }
+            }
+            points.push(point);
+            if scanner.is_empty() {
+                break;
             }
-            rocks.push(structure);
+            scanner.literal(" -> ")?;
         }
+        Ok(RockPath { points })
+    }
+}
 
-        max_x += 1;
-        max_y += 2;
-        let mut grid = vec![vec![b'.'; max_y]; max_x];
-        for structure in rocks {
-            for slice in structure.windows(2) {
-                let slice_a = slice[0];
-                let slice_b = slice[1];
-                let (a_x, a_y) = slice_a;
-                let (b_x, b_y) = slice_b;
-                if a_x < b_x {
-                    for i in a_x..=b_x {
-                        grid[i][a_y] = b'#';
-                    }
-                } else if a_x > b_x {
-                    for i in b_x..=a_x {
-                        grid[i][a_y] = b'#';
-                    }
-                } else if a_y < b_y {
-                    for i in a_y..=b_y {
-                        grid[a_x][i] = b'#';
-                    }
-                } else if a_y > b_y {
-                    for i in b_y..=a_y {
-                        grid[a_x][i] = b'#';
-                    }
-                } else {
-                    panic!("rock path seems broken: {},{} -> {},{}", a_x, a_y, b_x, b_y);
+/// Reads a coordinate no larger than [`MAX_COORDINATE`].
+fn coordinate(scanner: &mut Scanner, what: &str) -> std::result::Result<usize, ParseError> {
+    let column = scanner.column();
+    let value: usize = scanner.number(what)?;
+    if value > MAX_COORDINATE {
+        Err(ParseError::new(DAY, format!("{} of at most {}", what, MAX_COORDINATE), value.to_string()).at_column(column))?;
+    }
+    Ok(value)
+}
+
+pub fn load_rock_paths(input: impl BufRead) -> Result<Vec<RockPath>> {
+    let mut paths = vec![];
+    for (i, line) in input.lines().enumerate() {
+        paths.push(line?.trim().parse().map_err(|e: ParseError| e.at_line(i + 1))?);
+    }
+    Ok(paths)
+}
+
+/// Draws the scanned rock ('#') into a cave of air ('.'). The cave is two rows deeper than the lowest
+/// rock (or the source), so the bottom row is where sand rests on the floor, and wide enough that
+/// sand piled up to the source can't spill off the right.
+pub fn draw_cave(paths: &[RockPath], source: Point<usize>) -> Grid<char> {
+    let mut bounds = Bounds::new(source);
+    paths.iter().flat_map(|path| &path.points).for_each(|&p| bounds.include(p));
+
+    let max = bounds.max;
+    let mut grid = Grid::new((max.x + 1).max(source.x + max.y + 3), max.y + 2, '.');
+    for path in paths {
+        for slice in path.points.windows(2) {
+            let line = Bounds::of([slice[0], slice[1]]).unwrap();
+            for x in line.min.x..=line.max.x {
+                for y in line.min.y..=line.max.y {
+                    grid[Point::new(x, y)] = '#';
                 }
             }
         }
-        Ok(grid)
     }
+    grid
+}
+
+/// Loads the scan and draws the cave around the puzzle's sand source.
+pub fn load_rock_structures(input: impl BufRead) -> Result<Grid<char>> {
+    Ok(draw_cave(&load_rock_paths(input)?, SAND_SOURCE))
+}
+
+/// What a unit of sand does next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fall {
+    To(Point<usize>),
+    Rest,
+    /// It falls out of the cave.
+    Out,
+}
+
+/// Moves sand one step down, else down and to the left, else down and to the right. With a `floor`
+/// the bottom row rests on it rather than falling into the abyss.
+fn fall(grid: &Grid<char>, sand: Point<usize>, floor: bool) -> Fall {
+    if floor && sand.y + 1 == grid.height() {
+        return Fall::Rest;
+    }
+    for dx in [0, -1, 1] {
+        match grid.step(sand, Point::new(dx, 1)) {
+            Some(next) if grid[next] != '.' => {}
+            Some(next) => return Fall::To(next),
+            None => return Fall::Out,
+        }
+    }
+    Fall::Rest
+}
 
-    fn sand_units_before_abyss(mut grid: Vec<Vec<u8>>) -> usize {
-        let mut count = 0;
-        loop {
-            let mut old = (500, 0);
-            let mut sand = (500, 0);
-            loop {
-                sand.1 += 1;
-                if sand.1 == grid[0].len() {
-                    return count;
-                }
-                if grid[sand.0][sand.1] != b'.' {
-                    if sand.0 == 0 {
-                        return count;
-                    }
-                    sand.0 -= 1;
-                    if grid[sand.0][sand.1] != b'.' {
-                        sand.0 += 2;
-                        if sand.0 == grid.len() {
-                            return count;
-                        }
-                        if grid[sand.0][sand.1] != b'.' {
-                            grid[old.0][old.1] = b'o';
-                            break;
-                        }
-                    }
+/// Drops one unit of sand from the source, returning where it comes to rest, or `None` if it falls
+/// out of the cave. Each step it falls is added to `steps`.
+fn drop_sand(grid: &Grid<char>, source: Point<usize>, floor: bool, steps: &mut u64) -> Option<Point<usize>> {
+    let detail = trace::enabled(Level::Detail);
+    let mut sand = source;
+    loop {
+        match fall(grid, sand, floor) {
+            Fall::To(next) => {
+                sand = next;
+                *steps += 1;
+                if detail {
+                    trace::event(Level::Detail, "fall", || json!({ "to": sand }));
                 }
-                old = sand;
             }
-            count += 1;
+            Fall::Rest => return Some(sand),
+            Fall::Out => return None,
         }
     }
+}
 
-    fn sand_units_with_floor(mut grid: Vec<Vec<u8>>) -> usize {
-        let origin = (500, 0);
-        let mut count = 0;
-        loop {
-            count += 1;
-            let mut old = origin;
-            let mut sand = origin;
+pub fn sand_units_before_abyss(mut grid: Grid<char>, source: Point<usize>) -> usize {
+    let mut count = 0;
+    let mut steps = 0;
+    while grid[source] == '.' {
+        let Some(pos) = drop_sand(&grid, source, false, &mut steps) else {
+            break;
+        };
+        grid[pos] = 'o';
+        count += 1;
+        trace::event(Level::Step, "rest", || json!({ "unit": count, "at": pos }));
+    }
+    metrics::count("sand_units", count as u64);
+    metrics::count("fall_steps", steps);
+    count
+}
+
+pub fn sand_units_with_floor(mut grid: Grid<char>, source: Point<usize>) -> Result<usize> {
+    let mut count = 0;
+    let mut steps = 0;
+    while grid[source] == '.' {
+        let pos = drop_sand(&grid, source, true, &mut steps).ok_or_else(|| anyhow!("Sand fell off the side of the cave"))?;
+        grid[pos] = 'o';
+        count += 1;
+        trace::event(Level::Step, "rest", || json!({ "unit": count, "at": pos }));
+    }
+    metrics::count("sand_units", count as u64);
+    metrics::count("fall_steps", steps);
+    Ok(count)
+}
+
+/// Draws the cave with the source ('+'), any falling sand ('~') and, with a floor, the floor.
+fn draw_cave_state(grid: &Grid<char>, source: Point<usize>, falling: Option<Point<usize>>, floor: bool) -> Grid<char> {
+    let mut cave = Grid::new(grid.width(), grid.height() + floor as usize, '#');
+    for (pos, &cell) in grid.iter() {
+        cave[pos] = cell;
+    }
+    if cave[source] == '.' {
+        cave[source] = '+';
+    }
+    if let Some(sand) = falling {
+        cave[sand] = '~';
+    }
+    cave
+}
+
+/// Rock in grey, sand in yellow and the source in red.
+fn palette(cell: char) -> Rgb {
+    match cell {
+        '#' => [110, 110, 110],
+        'o' => [230, 190, 90],
+        '~' => [255, 230, 150],
+        '+' => [255, 64, 64],
+        _ => [0, 0, 0],
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Params {
+    pub sand_source: Point<usize>,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params { sand_source: SAND_SOURCE }
+    }
+}
+
+pub struct Day14 {
+    paths: Vec<RockPath>,
+    /// The cave drawn around the sand source, redrawn whenever that changes.
+    grid: Grid<char>,
+    params: Params,
+}
+
+impl Solution for Day14 {
+    fn parse(input: &mut dyn BufRead) -> Result<Self> {
+        let paths = load_rock_paths(input)?;
+        Ok(Day14 { grid: draw_cave(&paths, SAND_SOURCE), paths, params: Params::default() })
+    }
+
+    fn part_1(&self) -> Result<Answer> {
+        Ok(sand_units_before_abyss(self.grid.clone(), self.params.sand_source).into())
+    }
+
+    fn part_2(&self) -> Result<Answer> {
+        Ok(sand_units_with_floor(self.grid.clone(), self.params.sand_source)?.into())
+    }
+
+    fn visualizer(&self) -> Option<&dyn Visualize> {
+        Some(self)
+    }
+
+    fn configure(&mut self, changes: &Changes) -> Result<()> {
+        let params: Params = params::apply(&self.params, changes)?;
+        // Part 2's pile spreads a square further left with each row down to the floor, and the cave
+        // starts at x = 0, so the source can't be nearer the left than the pile is deep.
+        let source = params.sand_source;
+        if source.x > MAX_COORDINATE || source.y > MAX_COORDINATE {
+            Err(anyhow!("sand_source {} is too far out: each coordinate must be at most {}", source, MAX_COORDINATE))?;
+        }
+        let floor = self.paths.iter().flat_map(|path| &path.points).map(|p| p.y).fold(source.y, usize::max) + 2;
+        let spread = floor - 1 - source.y;
+        if source.x < spread {
+            Err(anyhow!("sand_source {} is too near the left of the cave: with the floor at y = {}, x must be at least {}", source, floor, spread))?;
+        }
+        self.params = params;
+        self.grid = draw_cave(&self.paths, source);
+        Ok(())
+    }
+
+    fn params(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(&self.params).ok()
+    }
+}
+
+/// Each unit of sand falling a step at a time until it rests, falling into the abyss in part 1 and
+/// onto the floor in part 2.
+impl Visualize for Day14 {
+    fn visualize(&self, part: u8, frames: &mut Frames) -> Result<()> {
+        let (source, floor) = (self.params.sand_source, part == 2);
+        let mut grid = self.grid.clone();
+        let mut units = 0;
+        frames.frame(|| Frame::new("start", draw_cave_state(&grid, source, None, floor)).with_palette(palette))?;
+        while grid[source] == '.' {
+            units += 1;
+            let mut sand = source;
             loop {
-                sand.1 += 1;
-                if sand.1 == grid[0].len() {
-                    grid[old.0][old.1] = b'o';
-                    break;
-                }
-                if grid[sand.0][sand.1] != b'.' {
-                    if sand.0 == 0 {
-                        panic!("Fully left!!!");
+                match fall(&grid, sand, floor) {
+                    Fall::To(next) => {
+                        sand = next;
+                        let caption = format!("unit {} falling at {}", units, sand);
+                        frames.frame(|| Frame::new(caption, draw_cave_state(&grid, source, Some(sand), floor)).with_palette(palette))?;
                     }
-                    sand.0 -= 1;
-                    if grid[sand.0][sand.1] != b'.' {
-                        sand.0 += 2;
-                        if sand.0 == grid.len() {
-                            grid.push(vec![b'.'; grid[0].len()]);
-                        }
-                        if grid[sand.0][sand.1] != b'.' {
-                            if old == origin {
-                                return count;
-                            }
-                            grid[old.0][old.1] = b'o';
-                            break;
-                        }
+                    Fall::Rest => break,
+                    Fall::Out if floor => Err(anyhow!("Sand fell off the side of the cave"))?,
+                    Fall::Out => {
+                        let caption = format!("unit {} fell into the abyss, {} came to rest", units, units - 1);
+                        return frames.frame(|| Frame::new(caption, draw_cave_state(&grid, source, None, floor)).with_palette(palette));
                     }
                 }
-                old = sand;
             }
+            grid[sand] = 'o';
+            let caption = format!("unit {} came to rest at {}", units, sand);
+            frames.frame(|| Frame::new(caption, draw_cave_state(&grid, source, None, floor)).with_palette(palette))?;
         }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generate;
+    use crate::open;
+    use crate::property::{self, Lines, Verdict};
+    use crate::snapshot;
+    use crate::visualize::{self, Options};
+
+    use super::*;
+
+    #[test]
+    fn traces() -> Result<()> {
+        let grid = load_rock_structures(open("day14ex.txt")?)?;
+        let (count, events) = trace::record(Level::Step, || sand_units_before_abyss(grid.clone(), SAND_SOURCE));
+        assert_eq!(count, events.len());
+        assert_eq!("Sand unit 1 comes to rest at 500,8", events[0].to_string());
+        assert_eq!("Sand unit 24 comes to rest at 495,8", events[23].to_string());
+        let ((_, counted), events) = trace::record(Level::Detail, || metrics::record(|| sand_units_before_abyss(grid, SAND_SOURCE)));
+        assert_eq!(counted["fall_steps"] as usize, events.iter().filter(|event| event.kind == "fall").count());
+        assert_eq!(("fall", "  falls to 500,1"), (events[0].kind.as_str(), events[0].to_string().as_str()));
+        Ok(())
     }
 
     #[test]
     fn part_1() -> Result<()> {
-        println!("Day 14 part 1: {}", sand_units_before_abyss(load_rock_structures("day14.txt")?));
+        println!("Day 14 part 1: {}", sand_units_before_abyss(load_rock_structures(open("day14.txt")?)?, SAND_SOURCE));
         Ok(())
     }
 
     #[test]
     fn part_2() -> Result<()> {
-        println!("Day 14 part 2: {}", sand_units_with_floor(load_rock_structures("day14.txt")?));
+        println!("Day 14 part 2: {}", sand_units_with_floor(load_rock_structures(open("day14.txt")?)?, SAND_SOURCE)?);
         Ok(())
     }
 
     #[test]
     fn part_1_test() -> Result<()> {
-        assert_eq!(24, sand_units_before_abyss(load_rock_structures("day14ex.txt")?));
+        assert_eq!(24, sand_units_before_abyss(load_rock_structures(open("day14ex.txt")?)?, SAND_SOURCE));
+        Ok(())
+    }
+
+    /// The last frame of a part's simulation on the example, cropped to a region.
+    fn last_frame(part: u8, region: Bounds<usize>) -> Result<String> {
+        let day = Day14::parse(&mut open("day14ex.txt")?)?;
+        let mut out = vec![];
+        let mut frames = Frames::new(Options { region: Some(region), ..Options::default() }, &mut out)?;
+        day.visualize(part, &mut frames)?;
+        Ok(String::from_utf8(out)?.split_terminator("\n\n").last().unwrap().to_string() + "\n")
+    }
+
+    #[test]
+    fn visualize() -> Result<()> {
+        let abyss = last_frame(1, Bounds { min: Point::new(494, 0), max: Point::new(503, 9) })?;
+        snapshot::assert_snapshot("day14ex_abyss", &abyss);
+        let floor = last_frame(2, Bounds { min: Point::new(488, 0), max: Point::new(512, 11) })?;
+        snapshot::assert_snapshot("day14ex_floor", &floor);
+
+        let frames = visualize::text_frames(&Day14::parse(&mut open("day14ex.txt")?)?, 2)?;
+        assert!(frames[frames.len() - 1].starts_with("unit 93 came to rest at 500,0\n"));
         Ok(())
     }
 
+    #[test]
+    fn broken_path() {
+        let e = load_rock_structures("498,4 -> 498,6 -> 496,7\n".as_bytes()).unwrap_err();
+        assert_eq!("day 14, line 1, column 19: expected a point in line with 498,6, found 496,7", e.to_string());
+        let e = load_rock_structures("498,4 -> 498\n".as_bytes()).unwrap_err();
+        assert_eq!("day 14, line 1, column 13: expected ',', found end of line", e.to_string());
+        let e = load_rock_structures("498,4 -> 498,100001\n".as_bytes()).unwrap_err();
+        assert_eq!("day 14, line 1, column 14: expected y coordinate of at most 2000, found 100001", e.to_string());
+    }
+
     #[test]
     fn part_2_test() -> Result<()> {
-        assert_eq!(93, sand_units_with_floor(load_rock_structures("day14ex.txt")?));
+        assert_eq!(93, sand_units_with_floor(load_rock_structures(open("day14ex.txt")?)?, SAND_SOURCE)?);
+        Ok(())
+    }
+
+    #[test]
+    fn moved_source() -> Result<()> {
+        let mut day = Day14::parse(&mut open("day14ex.txt")?)?;
+        let mut changes = Changes::new();
+        changes.insert("sand_source".to_string(), json!({ "x": 10, "y": 0 }));
+        day.configure(&changes)?;
+        assert_eq!(Answer::Number(0), day.part_1()?);
+        assert_eq!(Answer::Number(121), day.part_2()?);
+
+        changes.insert("sand_source".to_string(), json!({ "x": 9, "y": 0 }));
+        let e = day.configure(&changes).unwrap_err();
+        assert_eq!("sand_source 9,0 is too near the left of the cave: with the floor at y = 11, x must be at least 10", e.to_string());
+        assert_eq!(Some(json!({ "sand_source": { "x": 10, "y": 0 } })), day.params());
+
+        changes.insert("sand_source".to_string(), json!({ "x": 100000, "y": 0 }));
+        let e = day.configure(&changes).unwrap_err();
+        assert_eq!("sand_source 100000,0 is too far out: each coordinate must be at most 2000", e.to_string());
         Ok(())
     }
+
+    #[test]
+    fn floor_holds_at_least_as_much_sand() {
+        property::check(100, |rng, size| Lines(generate::day14(rng, size)), |input| {
+            let Ok(grid) = load_rock_structures(input.0.as_bytes()) else {
+                return Verdict::Discard;
+            };
+            let abyss = sand_units_before_abyss(grid.clone(), SAND_SOURCE);
+            match sand_units_with_floor(grid, SAND_SOURCE) {
+                Ok(floor) => Verdict::check(floor >= abyss, || format!("{} units with the floor but {} without", floor, abyss)),
+                Err(e) => Verdict::Fail(e.to_string()),
+            }
+        });
+    }
 }
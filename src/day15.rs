@@ -1,150 +1,328 @@
-#[cfg(test)]
-mod tests {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-    use anyhow::Result;
-
-    struct SensorBeacon {
-        s_x: i64,
-        s_y: i64,
-        b_x: i64,
-        b_y: i64,
-        md: i64,
-    }
-
-    impl SensorBeacon {
-        fn new(s_x: i64, s_y: i64, b_x: i64, b_y: i64) -> Self {
-            let md = manhattan_distance((s_x, s_y), (b_x, b_y));
-            SensorBeacon { s_x, s_y, b_x, b_y, md }
-        }
-    }
+use std::io::BufRead;
 
-    impl TryFrom<&str> for SensorBeacon {
-        type Error = anyhow::Error;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
 
-        fn try_from(value: &str) -> Result<Self, Self::Error> {
-            let line = value.replace("Sensor at x=", "").replace(" y=", "").replace(" closest beacon is at x=", "");
-            let (sensor, beacon) = line.split_once(":").unwrap();
-            let (s_x, s_y) = sensor.split_once(",").unwrap();
-            let (b_x, b_y) = beacon.split_once(",").unwrap();
-            Ok(SensorBeacon::new(s_x.parse::<i64>()?, s_y.parse::<i64>()?, b_x.parse::<i64>()?, b_y.parse::<i64>()?))
-        }
+use crate::differential::Implementation;
+use crate::geometry::{Bounds, Direction, Point};
+use crate::metrics;
+use crate::params::{self, Changes};
+use crate::parse::{ParseError, Scanner};
+use crate::repl::{arg, Command, Explore};
+use crate::{Answer, Solution};
+
+const DAY: u8 = 15;
+
+#[derive(Debug, Serialize)]
+pub struct SensorBeacon {
+    pub sensor: Point<i64>,
+    pub beacon: Point<i64>,
+    pub md: i64,
+}
+
+impl SensorBeacon {
+    pub fn new(sensor: Point<i64>, beacon: Point<i64>) -> Self {
+        SensorBeacon { sensor, beacon, md: sensor.manhattan(beacon) }
+    }
+
+    /// Whether the sensor can see `point`, so that it can't hold an undetected beacon.
+    pub fn covers(&self, point: Point<i64>) -> bool {
+        self.sensor.manhattan(point) <= self.md
     }
+}
+
+impl TryFrom<&str> for SensorBeacon {
+    type Error = ParseError;
 
-    fn manhattan_distance(a: (i64, i64), b: (i64, i64)) -> i64 {
-        (a.0 - b.0).abs() + (a.1 - b.1).abs()
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut scanner = Scanner::new(DAY, value);
+        scanner.literal("Sensor at x=")?;
+        let s_x = scanner.number("sensor x")?;
+        scanner.literal(", y=")?;
+        let s_y = scanner.number("sensor y")?;
+        scanner.literal(": closest beacon is at x=")?;
+        let b_x = scanner.number("beacon x")?;
+        scanner.literal(", y=")?;
+        let b_y = scanner.number("beacon y")?;
+        scanner.end()?;
+        Ok(SensorBeacon::new(Point::new(s_x, s_y), Point::new(b_x, b_y)))
     }
+}
 
-    fn load_sensors_and_beacons(file: &str) -> Result<Vec<SensorBeacon>> {
-        Ok(BufReader::new(File::open(file)?).lines().map(|l| l.unwrap().as_str().try_into().unwrap()).collect())
+pub fn load_sensors_and_beacons(input: impl BufRead) -> Result<Vec<SensorBeacon>> {
+    let mut sbs = vec![];
+    for (i, line) in input.lines().enumerate() {
+        sbs.push(line?.trim().try_into().map_err(|e: ParseError| e.at_line(i + 1))?);
     }
+    Ok(sbs)
+}
+
+pub fn positions_where_beacon_not_present(sbs: &[SensorBeacon], y: i64) -> i64 {
+    let Some(bounds) = Bounds::of(sbs.iter().flat_map(|sb| [sb.sensor, sb.beacon])) else {
+        return 0;
+    };
+    let bounds = bounds.expand(sbs.iter().map(|sb| sb.md).max().unwrap_or(0));
 
-    fn positions_where_beacon_not_present(sbs: &Vec<SensorBeacon>, y: i64) -> i64 {
-        let mut min_x = 0;
-        let mut max_x = 0;
-        let mut max_md = 0;
-        for sb in sbs {
-            min_x = min_x.min(sb.s_x).min(sb.b_x);
-            max_x = max_x.max(sb.s_x).max(sb.b_x);
-            max_md = max_md.max(sb.md);
+    let mut count = 0;
+    let mut checks = 0;
+    for x in bounds.min.x..=bounds.max.x {
+        let point = Point::new(x, y);
+        if sbs.iter().any(|sb| {
+            checks += 1;
+            sb.beacon != point && sb.covers(point)
+        }) {
+            count += 1;
         }
-        min_x -= max_md;
-        max_x += max_md;
-
-        let mut count = 0;
-        for x in min_x..=max_x {
-            let mut sensor_range = false;
-            for sb in sbs {
-                if sb.b_x == x && sb.b_y == y {
-                    continue;
-                }
-                if !sensor_range && manhattan_distance((x, y), (sb.s_x, sb.s_y)) <= sb.md {
-                    sensor_range = true;
-                }
-            }
-            if sensor_range {
-                count += 1;
-            }
+    }
+    metrics::count("sensor_checks", checks);
+    count
+}
+
+/// The same as [`positions_where_beacon_not_present`], but merging the ranges each sensor covers
+/// along the row rather than checking every point on it.
+pub fn positions_where_beacon_not_present_merging(sbs: &[SensorBeacon], y: i64) -> i64 {
+    let mut ranges: Vec<(i64, i64)> = sbs
+        .iter()
+        .filter_map(|sb| {
+            let reach = sb.md - (sb.sensor.y - y).abs();
+            (reach >= 0).then_some((sb.sensor.x - reach, sb.sensor.x + reach))
+        })
+        .collect();
+    ranges.sort_unstable();
+
+    let mut count = 0;
+    let mut covered_to = i64::MIN;
+    for (start, end) in ranges {
+        if end > covered_to {
+            count += end - start.max(covered_to.saturating_add(1)) + 1;
+            covered_to = end;
         }
-        count
     }
+    // Each known beacon on the row is taken back out of the count, unless a sensor whose closest
+    // beacon is elsewhere also reaches it, as the row scan counts those too.
+    let mut beacons: Vec<Point<i64>> = sbs.iter().map(|sb| sb.beacon).filter(|beacon| beacon.y == y).collect();
+    beacons.sort_unstable_by_key(|beacon| beacon.x);
+    beacons.dedup();
+    let hidden = beacons.iter().filter(|&&beacon| sbs.iter().filter(|sb| sb.covers(beacon)).all(|sb| sb.beacon == beacon)).count();
+    count - hidden as i64
+}
 
-    fn tuning_frequency(sbs: &Vec<SensorBeacon>, max_dimensions: i64) -> i64 {
-        fn uncovered(sbs: &Vec<SensorBeacon>, x: i64, y: i64) -> bool {
-            for sb in sbs {
-                if manhattan_distance((x, y), (sb.s_x, sb.s_y)) <= sb.md {
-                    return false;
+/// Finds the only point within `0..=max_dimensions` on both axes that no sensor covers, giving its
+/// frequency as `x * multiplier + y`, or `None` if the sensors cover every point. It must be just
+/// outside some sensor's range, so only the points around each sensor's perimeter are checked.
+pub fn tuning_frequency(sbs: &[SensorBeacon], max_dimensions: i64, multiplier: i64) -> Option<i64> {
+    let area = Bounds { min: Point::ORIGIN, max: Point::new(max_dimensions, max_dimensions) };
+    let mut checks = 0;
+    let mut covered = |point| {
+        sbs.iter().any(|sb| {
+            checks += 1;
+            sb.covers(point)
+        })
+    };
+    for sb in sbs {
+        let perimeter = sb.md + 1;
+        // Walk from the top corner around the diamond, turning right at each corner.
+        let mut direction = Direction::Right;
+        let mut point = sb.sensor + Direction::Up.unit() * perimeter;
+        for _ in 0..4 {
+            let diagonal = direction.unit() + direction.turn_right().unit();
+            for _ in 0..perimeter {
+                if area.contains(point) && !covered(point) {
+                    metrics::count("sensor_checks", checks);
+                    return Some(multiplier * point.x + point.y);
                 }
+                point += diagonal;
             }
-            true
+            direction = direction.turn_right();
         }
+    }
+    metrics::count("sensor_checks", checks);
+    None
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Params {
+    /// The row part 1 counts positions in.
+    pub row: i64,
+    /// The largest coordinate the distress beacon can have in part 2.
+    pub bound: i64,
+    pub frequency_multiplier: i64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params { row: 2000000, bound: 4000000, frequency_multiplier: 4000000 }
+    }
+}
+
+const COMMANDS: [Command; 1] = [Command::new("covered", "<x> <y>", "which sensors rule out a beacon at a position")];
 
-        fn coords_valid(x: i64, y: i64, max_dimensions: i64) -> bool {
-            x >= 0 && y >= 0 && x <= max_dimensions && y <= max_dimensions
+/// Looks up positions against the sensors.
+struct Explorer<'a> {
+    sbs: &'a [SensorBeacon],
+}
+
+impl Explore for Explorer<'_> {
+    fn commands(&self) -> &'static [Command] {
+        &COMMANDS
+    }
+
+    fn run(&mut self, _command: &str, args: &[&str]) -> Result<String> {
+        let point = Point::new(arg(args, 0, "x")?, arg(args, 1, "y")?);
+        if self.sbs.iter().any(|sb| sb.beacon == point) {
+            return Ok(format!("{} is a beacon\n", point));
+        }
+        let covering: Vec<&SensorBeacon> = self.sbs.iter().filter(|sb| sb.covers(point)).collect();
+        if covering.is_empty() {
+            return Ok(format!("{} isn't covered by any sensor, so could hold the distress beacon\n", point));
+        }
+        let mut out = format!("{} is covered by:\n", point);
+        for sb in covering {
+            out += &format!("  sensor at {}, {} away, reaching {} to its beacon at {}\n", sb.sensor, sb.sensor.manhattan(point), sb.md, sb.beacon);
         }
+        Ok(out)
+    }
+}
 
-        for sb in sbs {
-            let perimeter = sb.md + 1;
+pub struct Day15 {
+    sbs: Vec<SensorBeacon>,
+    params: Params,
+}
 
-            //nw
-            let mut y = sb.s_y;
-            for x in (sb.s_x - perimeter)..sb.s_x {
-                if coords_valid(x, y, max_dimensions) && uncovered(sbs, x, y) {
-                    return 4000000 * x + y;
-                }
-                y -= 1;
-            }
-            //ne
-            let mut y = sb.s_y - perimeter;
-            for x in sb.s_x..(sb.s_x + perimeter) {
-                if coords_valid(x, y, max_dimensions) && uncovered(sbs, x, y) {
-                    return 4000000 * x + y;
-                }
-                y += 1;
-            }
-            //sw
-            let mut y = sb.s_y + 1;
-            for x in (sb.s_x - perimeter + 1)..sb.s_x {
-                if coords_valid(x, y, max_dimensions) && uncovered(sbs, x, y) {
-                    return 4000000 * x + y;
-                }
-                y += 1;
-            }
-            //se
-            let mut y = sb.s_y + perimeter;
-            for x in (sb.s_x - perimeter)..=sb.s_x {
-                if coords_valid(x, y, max_dimensions) && uncovered(sbs, x, y) {
-                    return 4000000 * x + y;
-                }
-                y -= 1;
-            }
+impl Solution for Day15 {
+    fn parse(input: &mut dyn BufRead) -> Result<Self> {
+        Ok(Day15 { sbs: load_sensors_and_beacons(input)?, params: Params::default() })
+    }
+
+    fn part_1(&self) -> Result<Answer> {
+        Ok(positions_where_beacon_not_present(&self.sbs, self.params.row).into())
+    }
+
+    fn part_2(&self) -> Result<Answer> {
+        let bound = self.params.bound;
+        let frequency = tuning_frequency(&self.sbs, bound, self.params.frequency_multiplier)
+            .ok_or_else(|| ParseError::new(DAY, format!("a position within 0..={} no sensor covers", bound), "every one covered"))?;
+        Ok(frequency.into())
+    }
+
+    fn model(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(&self.sbs).ok()
+    }
+
+    fn configure(&mut self, changes: &Changes) -> Result<()> {
+        self.params = params::apply(&self.params, changes)?;
+        Ok(())
+    }
+
+    fn params(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(&self.params).ok()
+    }
+
+    fn explorer(&self) -> Option<Box<dyn Explore + '_>> {
+        Some(Box::new(Explorer { sbs: &self.sbs }))
+    }
+
+    fn implementations(&self, part: u8) -> Vec<Implementation<'_>> {
+        match part {
+            1 => vec![Implementation::new("merging", || Ok(positions_where_beacon_not_present_merging(&self.sbs, self.params.row).into()))],
+            _ => vec![],
         }
-        0
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use crate::differential;
+    use crate::generate::Rng;
+    use crate::open;
+    use crate::property::{self, Lines, Verdict};
+    use crate::repl;
+
+    use super::*;
 
     #[test]
     fn part_1() -> Result<()> {
-        //commented out as it takes several seconds to run
-        // println!("Day 15 part 1: {}", positions_where_beacon_not_present(&load_sensors_and_beacons("day15.txt")?, 2000000));
+        println!("Day 15 part 1: {}", positions_where_beacon_not_present_merging(&load_sensors_and_beacons(open("day15.txt")?)?, 2000000));
         Ok(())
     }
 
     #[test]
     fn part_2() -> Result<()> {
-        //commented out as it takes several seconds to run
-        // println!("Day 15 part 2: {}", tuning_frequency(&load_sensors_and_beacons("day15.txt")?, 4000000));
+        let frequency = tuning_frequency(&load_sensors_and_beacons(open("day15.txt")?)?, 4000000, 4000000).ok_or_else(|| anyhow!("No uncovered position"))?;
+        println!("Day 15 part 2: {}", frequency);
+        Ok(())
+    }
+
+    #[test]
+    fn explore() -> Result<()> {
+        let out = repl::session(15, "covered 14 11\ncovered 2 10\ncovered 8 9\ncovered x 1")?;
+        let expected = "14,11 isn't covered by any sensor, so could hold the distress beacon\n2,10 is a beacon\n\
+            8,9 is covered by:\n  sensor at 8,7, 2 away, reaching 9 to its beacon at 2,10\n\
+            Error: Invalid x 'x'\n";
+        assert_eq!(expected, out);
         Ok(())
     }
 
     #[test]
     fn part_1_test() -> Result<()> {
-        assert_eq!(26, positions_where_beacon_not_present(&load_sensors_and_beacons("day15ex.txt")?, 10));
+        assert_eq!(26, positions_where_beacon_not_present(&load_sensors_and_beacons(open("day15ex.txt")?)?, 10));
         Ok(())
     }
 
+    #[test]
+    fn bad_sensor() {
+        let e = load_sensors_and_beacons("Sensor at x=2, y=18: closest beacon at x=-2, y=15\n".as_bytes()).unwrap_err();
+        assert_eq!("day 15, line 1, column 20: expected ': closest beacon is at x=', found ':'", e.to_string());
+    }
+
     #[test]
     fn part_2_test() -> Result<()> {
-        assert_eq!(56000011, tuning_frequency(&load_sensors_and_beacons("day15ex.txt")?, 20));
+        assert_eq!(Some(56000011), tuning_frequency(&load_sensors_and_beacons(open("day15ex.txt")?)?, 20, 4000000));
+        let mut day = Day15::parse(&mut "Sensor at x=5, y=5: closest beacon is at x=20, y=20\n".as_bytes())?;
+        day.configure(&Changes::from_iter([("bound".to_string(), 10.into())]))?;
+        assert_eq!("day 15: expected a position within 0..=10 no sensor covers, found every one covered", day.part_2().unwrap_err().to_string());
         Ok(())
     }
+
+    /// Small sensors in a small area, as the generated puzzle inputs are too big to scan quickly.
+    fn sensors(rng: &mut Rng, size: usize) -> String {
+        let lines: Vec<String> = (0..size.min(8))
+            .map(|_| {
+                let mut coord = || rng.range_i64(0..=20);
+                format!("Sensor at x={}, y={}: closest beacon is at x={}, y={}", coord(), coord(), coord(), coord())
+            })
+            .collect();
+        lines.join("\n")
+    }
+
+    #[test]
+    fn implementations_agree() -> Result<()> {
+        let day = crate::day(15).unwrap();
+        let example = std::fs::read_to_string("day15ex.txt")?;
+        for row in [-5, 0, 9, 10, 11, 30] {
+            let changes = Changes::from_iter([("row".to_string(), row.into())]);
+            assert_eq!(2, differential::check(day, 1, "day15ex.txt", &example, &changes).map_err(|d| anyhow!("{}", d))?);
+            differential::check_generated(day, 1, 300, |seed, size| Some(sensors(&mut Rng::new(seed), size)), &changes).map_err(|d| anyhow!("{}", d))?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn tuning_frequency_is_uncovered() {
+        property::check(300, |rng, size| Lines(sensors(rng, size)), |input| {
+            let Ok(sbs) = load_sensors_and_beacons(input.0.as_bytes()) else {
+                return Verdict::Discard;
+            };
+            let Some(frequency) = tuning_frequency(&sbs, 20, 4000000) else {
+                return Verdict::Discard;
+            };
+            let point = Point::new(frequency / 4000000, frequency % 4000000);
+            Verdict::check(
+                (0..=20).contains(&point.x) && (0..=20).contains(&point.y) && !sbs.iter().any(|sb| sb.covers(point)),
+                || format!("{} is outside the area or covered", point),
+            )
+        });
+    }
 }
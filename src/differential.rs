@@ -0,0 +1,188 @@
+//! Differential testing: a day can offer other implementations of a part alongside its own, such as
+//! an optimized rewrite, and they are run against each other on generated and real inputs. When
+//! they disagree the input is shrunk to the smallest one they still disagree on.
+
+use std::fmt;
+
+use anyhow::Result;
+
+use crate::generate::generate;
+use crate::params::Changes;
+use crate::property::{self, Text, Verdict};
+use crate::{Answer, Day};
+
+/// The name the day's own implementation of a part ([`Solution::part`](crate::Solution::part)) is
+/// reported under.
+pub const REFERENCE: &str = "reference";
+
+/// Another way of solving a part of a parsed input.
+pub struct Implementation<'a> {
+    pub name: &'static str,
+    pub run: Box<dyn Fn() -> Result<Answer> + 'a>,
+}
+
+impl<'a> Implementation<'a> {
+    pub fn new(name: &'static str, run: impl Fn() -> Result<Answer> + 'a) -> Self {
+        Implementation { name, run: Box::new(run) }
+    }
+}
+
+/// What one implementation made of an input: its answer, or the error it gave.
+pub type Outcome = (&'static str, Result<Answer, String>);
+
+/// Runs every implementation of a part on an input, the reference first. Returns `None` if the input
+/// doesn't parse or take the parameter changes, as then there is nothing to compare.
+pub fn run_all(day: &Day, part: u8, input: &str, changes: &Changes) -> Option<Vec<Outcome>> {
+    let mut solution = (day.parse)(&mut input.as_bytes()).ok()?;
+    solution.configure(changes).ok()?;
+    let mut outcomes = vec![(REFERENCE, solution.part(part).map_err(|e| e.to_string()))];
+    for implementation in solution.implementations(part) {
+        outcomes.push((implementation.name, (implementation.run)().map_err(|e| e.to_string())));
+    }
+    Some(outcomes)
+}
+
+/// How many implementations a day has of a part, found by parsing a small generated input. Days
+/// without a generator count as having none.
+pub fn implementations(day: &Day, part: u8) -> usize {
+    let Some(input) = generate(day.number, 0, 1) else {
+        return 0;
+    };
+    (day.parse)(&mut input.as_bytes()).map_or(0, |solution| 1 + solution.implementations(part).len())
+}
+
+/// Whether every implementation gave the same answer, or all of them an error. Error messages can
+/// differ between implementations, so aren't compared.
+pub fn agree(outcomes: &[Outcome]) -> bool {
+    outcomes.windows(2).all(|pair| match (&pair[0].1, &pair[1].1) {
+        (Ok(a), Ok(b)) => a == b,
+        (Err(_), Err(_)) => true,
+        _ => false,
+    })
+}
+
+/// Implementations disagreeing on an input, shrunk from the one that first showed it.
+#[derive(Debug)]
+pub struct Disagreement {
+    pub day: u8,
+    pub part: u8,
+    /// Where the original input came from, such as a file name or generator seed.
+    pub source: String,
+    /// The smallest input found that they still disagree on.
+    pub input: String,
+    pub shrinks: usize,
+    pub outcomes: Vec<Outcome>,
+}
+
+impl fmt::Display for Disagreement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Day {:02} part {} implementations disagree on {}, shrunk {} times to:", self.day, self.part, self.source, self.shrinks)?;
+        write!(f, "{}", self.input)?;
+        if !self.input.ends_with('\n') {
+            writeln!(f)?;
+        }
+        for (name, outcome) in &self.outcomes {
+            match outcome {
+                Ok(answer) => writeln!(f, "  {}: {}", name, answer.to_string().trim_end())?,
+                Err(e) => writeln!(f, "  {}: error, {}", name, e)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks that every implementation of a part agrees on an input, returning how many there are (one
+/// if the day has no others), or the smallest input they disagree on.
+pub fn check(day: &Day, part: u8, source: &str, input: &str, changes: &Changes) -> Result<usize, Box<Disagreement>> {
+    let property = |text: &Text| match run_all(day, part, &text.0, changes) {
+        None => Verdict::Discard,
+        Some(outcomes) => Verdict::check(agree(&outcomes), String::new),
+    };
+    let Some(outcomes) = run_all(day, part, input, changes) else {
+        return Ok(0);
+    };
+    if agree(&outcomes) {
+        return Ok(outcomes.len());
+    }
+    let (smallest, _, shrinks) = property::shrink(Text(input.to_string()), String::new(), &property);
+    let outcomes = run_all(day, part, &smallest.0, changes).unwrap_or(outcomes);
+    Err(Box::new(Disagreement { day: day.number, part, source: source.to_string(), input: smallest.0, shrinks, outcomes }))
+}
+
+/// Checks a part on `seeds` generated inputs, with sizes cycling up to 30 like the property tests.
+pub fn check_generated(day: &Day, part: u8, seeds: u64, generate: impl Fn(u64, usize) -> Option<String>, changes: &Changes) -> Result<usize, Box<Disagreement>> {
+    let mut implementations = 0;
+    for seed in 0..seeds {
+        let size = 1 + seed as usize % 30;
+        let Some(input) = generate(seed, size) else {
+            break;
+        };
+        implementations = implementations.max(check(day, part, &format!("generated input (seed {}, size {})", seed, size), &input, changes)?);
+    }
+    Ok(implementations)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufRead;
+
+    use super::*;
+    use crate::Solution;
+
+    /// Adds up numbers, one per line, with a rewrite that forgets numbers of 50 or more.
+    struct Sum {
+        numbers: Vec<i64>,
+    }
+
+    impl Solution for Sum {
+        fn parse(input: &mut dyn BufRead) -> Result<Self> {
+            Ok(Sum { numbers: input.lines().map(|line| Ok(line?.trim().parse()?)).collect::<Result<_>>()? })
+        }
+
+        fn part_1(&self) -> Result<Answer> {
+            Ok(self.numbers.iter().sum::<i64>().into())
+        }
+
+        fn part_2(&self) -> Result<Answer> {
+            Ok(self.numbers.len().into())
+        }
+
+        fn implementations(&self, part: u8) -> Vec<Implementation<'_>> {
+            match part {
+                1 => vec![Implementation::new("small", || Ok(self.numbers.iter().filter(|&&n| n < 50).sum::<i64>().into()))],
+                _ => vec![],
+            }
+        }
+    }
+
+    fn boxed(input: &mut dyn BufRead) -> Result<Box<dyn Solution>> {
+        Ok(Box::new(Sum::parse(input)?))
+    }
+
+    #[test]
+    fn disagreements() {
+        let day = Day { number: 99, parse: boxed };
+        assert_eq!(Ok(2), check(&day, 1, "small", "1\n2\n", &Changes::new()).map_err(|d| d.to_string()));
+        assert_eq!(Ok(1), check(&day, 2, "big", "70\n", &Changes::new()).map_err(|d| d.to_string()));
+        assert_eq!(Ok(0), check(&day, 1, "junk", "x\n", &Changes::new()).map_err(|d| d.to_string()));
+
+        let disagreement = check(&day, 1, "numbers", "3\n70\n12\n99\n", &Changes::new()).unwrap_err();
+        assert_eq!(1, disagreement.input.lines().count());
+        assert!(disagreement.input.trim().parse::<i64>().unwrap() >= 50);
+        let report = disagreement.to_string();
+        assert!(report.starts_with("Day 99 part 1 implementations disagree on numbers"), "{}", report);
+        assert!(report.ends_with("  small: 0\n"), "{}", report);
+
+        let generate = |seed: u64, _| Some(format!("{}\n", seed * 10));
+        let disagreement = check_generated(&day, 1, 10, generate, &Changes::new()).unwrap_err();
+        assert!(disagreement.source.contains("seed 5"), "{}", disagreement);
+    }
+
+    #[test]
+    fn registered() {
+        assert_eq!(2, implementations(crate::day(6).unwrap(), 2));
+        assert_eq!(2, implementations(crate::day(15).unwrap(), 1));
+        assert_eq!(1, implementations(crate::day(15).unwrap(), 2));
+        assert_eq!(1, implementations(crate::day(1).unwrap(), 1));
+    }
+}
@@ -0,0 +1,188 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+
+use crate::params::Changes;
+use crate::{Answer, Day, NotSolved};
+
+/// One part of one day to run on an input file.
+#[derive(Clone)]
+pub struct Task {
+    pub day: &'static Day,
+    pub input: String,
+    /// Changes to the day's parameters for this input.
+    pub params: Changes,
+    pub part: u8,
+}
+
+/// How a task ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Solved(Answer),
+    /// Parsing or the part itself failed, or the solver panicked.
+    Failed(String),
+    /// The part isn't written yet, failing with [`NotSolved`].
+    NotSolved,
+    /// The solver was still running when its budget ran out.
+    TimedOut,
+}
+
+/// A task with how it ended and how long it ran for (its budget, if it timed out).
+pub struct Finished {
+    pub task: Task,
+    pub outcome: Outcome,
+    pub elapsed: Duration,
+}
+
+/// Parses a task's input and runs its part, turning errors and panics into [`Outcome::Failed`], or
+/// [`Outcome::NotSolved`] for a part that isn't written.
+fn run_task(task: &Task) -> Outcome {
+    let solve = || task.day.load_with(&task.input, &task.params).with_context(|| format!("parsing {}", task.input))?.part(task.part);
+    match panic::catch_unwind(AssertUnwindSafe(solve)) {
+        Ok(Ok(answer)) => Outcome::Solved(answer),
+        Ok(Err(e)) if e.downcast_ref::<NotSolved>().is_some() => Outcome::NotSolved,
+        Ok(Err(e)) => Outcome::Failed(format!("{:#}", e)),
+        Err(panic) => Outcome::Failed(format!("panicked: {}", panic_message(&*panic))),
+    }
+}
+
+/// What a caught panic said, where it said anything.
+pub(crate) fn panic_message(panic: &(dyn Any + Send)) -> String {
+    let message = panic.downcast_ref::<&str>().map(|s| s.to_string()).or_else(|| panic.downcast_ref::<String>().cloned());
+    message.unwrap_or_else(|| "unknown cause".to_string())
+}
+
+/// Runs tasks on up to `jobs` threads at once, giving each one `timeout` of wall-clock time, and
+/// returns them finished in the order they were given.
+///
+/// Threads can't be stopped from outside, so a solver that times out is abandoned rather than
+/// killed: its slot is given to the next task and whatever it eventually returns is ignored. It
+/// keeps using a CPU until it finishes or the process exits.
+pub fn run_parallel(tasks: Vec<Task>, jobs: usize, timeout: Duration) -> Vec<Finished> {
+    let jobs = jobs.max(1);
+    let mut outcomes: Vec<Option<(Outcome, Duration)>> = vec![None; tasks.len()];
+    let mut queue = tasks.iter().cloned().enumerate();
+    let mut running: HashMap<usize, Instant> = HashMap::new();
+    let (sender, receiver) = mpsc::channel();
+
+    loop {
+        while running.len() < jobs {
+            let Some((index, task)) = queue.next() else {
+                break;
+            };
+            let sender = sender.clone();
+            let started = Instant::now();
+            thread::spawn(move || {
+                let outcome = run_task(&task);
+                // The receiver is gone if the whole run has finished without waiting for this task.
+                let _ = sender.send((index, outcome, started.elapsed()));
+            });
+            running.insert(index, started);
+        }
+
+        let Some(&first_started) = running.values().min() else {
+            break;
+        };
+        let wait = (first_started + timeout).saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(wait) {
+            Ok((index, outcome, elapsed)) => {
+                // Results from abandoned tasks are dropped here, as they are no longer running.
+                if running.remove(&index).is_some() {
+                    outcomes[index] = Some((outcome, elapsed));
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                running.retain(|&index, started| {
+                    if started.elapsed() < timeout {
+                        return true;
+                    }
+                    outcomes[index] = Some((Outcome::TimedOut, timeout));
+                    false
+                });
+            }
+            Err(RecvTimeoutError::Disconnected) => unreachable!("the sender is held until the loop ends"),
+        }
+    }
+
+    tasks
+        .into_iter()
+        .zip(outcomes)
+        .map(|(task, outcome)| {
+            let (outcome, elapsed) = outcome.expect("every task is finished or timed out");
+            Finished { task, outcome, elapsed }
+        })
+        .collect()
+}
+
+/// Runs `f` on its own thread, giving it `timeout` of wall-clock time, and returns what it gave or
+/// `None` if it was still running (or panicked). As with [`run_parallel`], a task that times out is abandoned and
+/// keeps its thread until it finishes.
+pub fn with_timeout<T: Send + 'static>(timeout: Duration, f: impl FnOnce() -> T + Send + 'static) -> Option<T> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        // The receiver is gone if the task timed out.
+        let _ = sender.send(f());
+    });
+    receiver.recv_timeout(timeout).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Condvar, Mutex};
+
+    use super::*;
+
+    fn task(day: u8, input: &str, part: u8) -> Task {
+        Task { day: crate::day(day).unwrap(), input: input.to_string(), params: Changes::new(), part }
+    }
+
+    #[test]
+    fn runs_in_order() {
+        let tasks = vec![task(12, "day12ex.txt", 1), task(12, "day12ex.txt", 2), task(7, "day07ex.txt", 1), task(7, "nope.txt", 1)];
+        let finished = run_parallel(tasks, 3, Duration::from_secs(60));
+        let outcomes: Vec<_> = finished.iter().map(|f| &f.outcome).collect();
+        assert_eq!(Outcome::Solved(Answer::Number(31)), *outcomes[0]);
+        assert_eq!(Outcome::Solved(Answer::Number(29)), *outcomes[1]);
+        assert_eq!(Outcome::Solved(Answer::Number(95437)), *outcomes[2]);
+        assert!(matches!(outcomes[3], Outcome::Failed(e) if e.starts_with("parsing nope.txt")));
+    }
+
+    /// Released by [`times_out`] once the blocked task has been given up on.
+    static RELEASE: (Mutex<bool>, Condvar) = (Mutex::new(false), Condvar::new());
+
+    /// A day whose parsing waits until [`RELEASE`], so it overruns any budget until the test lets it
+    /// finish.
+    static BLOCKED: Day = Day {
+        number: 99,
+        parse: |_| {
+            let (released, condvar) = &RELEASE;
+            let _released = condvar.wait_while(released.lock().unwrap(), |released| !*released).unwrap();
+            Err(anyhow::anyhow!("released"))
+        },
+    };
+
+    #[test]
+    fn times_out() {
+        let blocked = Task { day: &BLOCKED, ..task(12, "day12ex.txt", 1) };
+        let finished = run_parallel(vec![blocked, task(12, "day12ex.txt", 1)], 1, Duration::from_millis(100));
+        *RELEASE.0.lock().unwrap() = true;
+        RELEASE.1.notify_all();
+        assert_eq!(Outcome::TimedOut, finished[0].outcome);
+        assert_eq!(Duration::from_millis(100), finished[0].elapsed);
+        assert_eq!(Outcome::Solved(Answer::Number(31)), finished[1].outcome);
+    }
+
+    #[test]
+    fn single_task_times_out() {
+        assert_eq!(Some(2), with_timeout(Duration::from_secs(60), || 1 + 1));
+        let (release, blocked) = mpsc::channel::<()>();
+        assert_eq!(None, with_timeout(Duration::from_millis(10), move || blocked.recv().is_ok()));
+        // Dropping the sender wakes the abandoned task, which then finishes.
+        drop(release);
+    }
+}
@@ -0,0 +1,438 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+
+/// A small seeded pseudo-random number generator (SplitMix64), so generated inputs can be
+/// reproduced from their seed without pulling in a dependency.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in the inclusive range, which must not be empty.
+    pub fn range(&mut self, range: RangeInclusive<u64>) -> u64 {
+        let span = range.end() - range.start();
+        match span.checked_add(1) {
+            Some(count) => range.start() + self.next_u64() % count,
+            None => self.next_u64(),
+        }
+    }
+
+    /// A number in the inclusive range, for signed coordinates.
+    pub fn range_i64(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let span = range.end().abs_diff(*range.start());
+        range.start().wrapping_add_unsigned(self.range(0..=span))
+    }
+
+    /// A number below `n`, which must not be zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        self.range(0..=n as u64 - 1) as usize
+    }
+
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.range(1..=100) <= percent
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// Generates one day's puzzle input of roughly `size` units (elves, lines, monkeys, ...).
+pub type Generator = fn(&mut Rng, usize) -> String;
+
+/// The input generator for a day, if there is one.
+pub fn generator(day: u8) -> Option<Generator> {
+    Some(match day {
+        1 => day01,
+        2 => day02,
+        3 => day03,
+        4 => day04,
+        5 => day05,
+        6 => day06,
+        7 => day07,
+        8 => day08,
+        9 => day09,
+        10 => day10,
+        11 => day11,
+        12 => day12,
+        13 => day13,
+        14 => day14,
+        15 => day15,
+        _ => return None,
+    })
+}
+
+/// Generates a day's input from a seed, so the same seed and size always give the same input.
+pub fn generate(day: u8, seed: u64, size: usize) -> Option<String> {
+    Some(generator(day)?(&mut Rng::new(seed), size))
+}
+
+const LETTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// `size` elves (at least three) each carrying a few snacks.
+pub fn day01(rng: &mut Rng, size: usize) -> String {
+    let mut out = String::new();
+    for elf in 0..size.max(3) {
+        if elf > 0 {
+            out.push('\n');
+        }
+        for _ in 0..rng.range(1..=15) {
+            writeln!(out, "{}", rng.range(1000..=60000)).unwrap();
+        }
+    }
+    out
+}
+
+/// `size` rounds of the strategy guide.
+pub fn day02(rng: &mut Rng, size: usize) -> String {
+    let mut out = String::new();
+    for _ in 0..size {
+        writeln!(out, "{} {}", rng.pick(&['A', 'B', 'C']), rng.pick(&['X', 'Y', 'Z'])).unwrap();
+    }
+    out
+}
+
+/// `size` groups of three rucksacks. Each rucksack has exactly one item type in both compartments,
+/// and each group has exactly one badge carried by all three elves.
+pub fn day03(rng: &mut Rng, size: usize) -> String {
+    let mut out = String::new();
+    for _ in 0..size {
+        let mut letters = LETTERS.to_vec();
+        rng.shuffle(&mut letters);
+        // Each elf gets its own letters so that only the badge is shared by the whole group.
+        let (badge, rest) = letters.split_first().unwrap();
+        for own in rest.chunks(rest.len() / 3).take(3) {
+            let half = rng.range(2..=own.len() as u64 / 2) as usize;
+            let mut items = own[..2 * half - 2].to_vec();
+            items.push(*badge);
+            rng.shuffle(&mut items);
+            // The first `half - 1` items fill the first compartment, then the shared item goes in
+            // both, then the rest fill the second compartment.
+            let (first, rest) = items.split_at(half - 1);
+            let (shared, second) = rest.split_first().unwrap();
+            out.extend(first.iter().map(|&b| b as char));
+            out.push(*shared as char);
+            out.push(*shared as char);
+            out.extend(second.iter().map(|&b| b as char));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// `size` pairs of section assignments.
+pub fn day04(rng: &mut Rng, size: usize) -> String {
+    let mut out = String::new();
+    for _ in 0..size {
+        let mut pair = || {
+            let start = rng.range(1..=99);
+            (start, rng.range(start..=99))
+        };
+        let ((a, b), (c, d)) = (pair(), pair());
+        writeln!(out, "{}-{},{}-{}", a, b, c, d).unwrap();
+    }
+    out
+}
+
+/// A drawing of up to nine stacks and `size` moves. Moves never empty a stack, so every stack has a
+/// crate on top at the end.
+pub fn day05(rng: &mut Rng, size: usize) -> String {
+    let count = rng.range(2..=9) as usize;
+    let mut stacks: Vec<Vec<u8>> = (0..count).map(|_| (0..rng.range(1..=8)).map(|_| *rng.pick(&LETTERS[26..])).collect()).collect();
+    if stacks.iter().all(|s| s.len() == 1) {
+        // With more crates than stacks there is always a stack that can spare one to move.
+        stacks[0].push(b'Z');
+    }
+
+    let mut out = String::new();
+    let height = stacks.iter().map(Vec::len).max().unwrap();
+    for level in (0..height).rev() {
+        let row: Vec<String> = stacks.iter().map(|s| s.get(level).map_or("   ".to_string(), |&c| format!("[{}]", c as char))).collect();
+        writeln!(out, "{}", row.join(" ")).unwrap();
+    }
+    let numbers: Vec<String> = (1..=count).map(|n| format!(" {} ", n)).collect();
+    writeln!(out, "{}\n", numbers.join(" ")).unwrap();
+
+    for _ in 0..size {
+        let from = loop {
+            let from = rng.below(count);
+            if stacks[from].len() > 1 {
+                break from;
+            }
+        };
+        let to = (from + rng.range(1..=count as u64 - 1) as usize) % count;
+        let moved = rng.range(1..=stacks[from].len() as u64 - 1) as usize;
+        let at = stacks[from].len() - moved;
+        let crates = stacks[from].split_off(at);
+        stacks[to].extend(crates);
+        writeln!(out, "move {} from {} to {}", moved, from + 1, to + 1).unwrap();
+    }
+    out
+}
+
+/// A datastream of `size` characters (at least 14) that contains a run of 14 different ones.
+pub fn day06(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(14);
+    // Only a few distinct letters, so that markers don't turn up by chance too early.
+    let mut stream: Vec<u8> = (0..size).map(|_| *rng.pick(&LETTERS[..6])).collect();
+    let mut marker = LETTERS[..26].to_vec();
+    rng.shuffle(&mut marker);
+    let at = rng.below(size - 13);
+    stream[at..at + 14].copy_from_slice(&marker[..14]);
+    String::from_utf8(stream).unwrap() + "\n"
+}
+
+/// A shell transcript exploring a filesystem of `size` directories besides the root.
+pub fn day07(rng: &mut Rng, size: usize) -> String {
+    fn explore(rng: &mut Rng, dir: usize, children: &[Vec<usize>], out: &mut String) {
+        writeln!(out, "$ ls").unwrap();
+        let mut names = BTreeSet::new();
+        let mut dirs = vec![];
+        for &child in &children[dir] {
+            let name = loop {
+                let name = word(rng, 1..=6);
+                if names.insert(name.clone()) {
+                    break name;
+                }
+            };
+            writeln!(out, "dir {}", name).unwrap();
+            dirs.push((child, name));
+        }
+        for _ in 0..rng.range(1..=5) {
+            let name = word(rng, 1..=6) + if rng.chance(50) { ".txt" } else { "" };
+            if names.insert(name.clone()) {
+                writeln!(out, "{} {}", rng.range(1000..=300_000), name).unwrap();
+            }
+        }
+        for (child, name) in dirs {
+            writeln!(out, "$ cd {}", name).unwrap();
+            explore(rng, child, children, out);
+            writeln!(out, "$ cd ..").unwrap();
+        }
+    }
+
+    // Directory 0 is the root, and every other directory is inside one numbered before it.
+    let mut children = vec![vec![]; size + 1];
+    for dir in 1..=size {
+        children[rng.below(dir)].push(dir);
+    }
+    let mut out = "$ cd /\n".to_string();
+    explore(rng, 0, &children, &mut out);
+    out
+}
+
+fn word(rng: &mut Rng, len: RangeInclusive<u64>) -> String {
+    (0..rng.range(len)).map(|_| *rng.pick(&LETTERS[..26]) as char).collect()
+}
+
+/// A `size` by `size` grid of tree heights.
+pub fn day08(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let mut out = String::new();
+    for _ in 0..size {
+        out.extend((0..size).map(|_| char::from(b'0' + rng.range(0..=9) as u8)));
+        out.push('\n');
+    }
+    out
+}
+
+/// `size` motions of the rope's head.
+pub fn day09(rng: &mut Rng, size: usize) -> String {
+    let mut out = String::new();
+    for _ in 0..size {
+        writeln!(out, "{} {}", rng.pick(&['U', 'D', 'L', 'R']), rng.range(1..=20)).unwrap();
+    }
+    out
+}
+
+/// A program of `size` instructions.
+pub fn day10(rng: &mut Rng, size: usize) -> String {
+    let mut out = String::new();
+    for _ in 0..size {
+        if rng.chance(30) {
+            writeln!(out, "noop").unwrap();
+        } else {
+            writeln!(out, "addx {}", rng.range_i64(-20..=20)).unwrap();
+        }
+    }
+    out
+}
+
+/// Between two and eight monkeys (`size` clamped to that), each testing for a different prime so
+/// that worry levels stay small enough to square.
+pub fn day11(rng: &mut Rng, size: usize) -> String {
+    let count = size.clamp(2, 8);
+    let mut primes = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+    rng.shuffle(&mut primes);
+
+    let mut out = String::new();
+    for (i, divisor) in primes.iter().take(count).enumerate() {
+        let items: Vec<String> = (0..rng.range(0..=6)).map(|_| rng.range(40..=99).to_string()).collect();
+        let operation = match rng.range(0..=2) {
+            0 => format!("+ {}", rng.range(1..=8)),
+            1 => format!("* {}", rng.range(2..=19)),
+            _ => "* old".to_string(),
+        };
+        let mut other = || (i + rng.range(1..=count as u64 - 1) as usize) % count;
+        let (if_true, if_false) = (other(), other());
+        if i > 0 {
+            out.push('\n');
+        }
+        writeln!(out, "Monkey {}:", i).unwrap();
+        writeln!(out, "  Starting items: {}", items.join(", ")).unwrap();
+        writeln!(out, "  Operation: new = old {}", operation).unwrap();
+        writeln!(out, "  Test: divisible by {}", divisor).unwrap();
+        writeln!(out, "    If true: throw to monkey {}", if_true).unwrap();
+        writeln!(out, "    If false: throw to monkey {}", if_false).unwrap();
+    }
+    out
+}
+
+/// A heightmap `size` wide (at least 26) and half as tall, rising from the start in the top left to
+/// the end in the bottom right. Some cells are dug out lower, but the top row and right column are
+/// left alone so there is always a path.
+pub fn day12(rng: &mut Rng, size: usize) -> String {
+    let width = size.max(26);
+    let height = width / 2;
+    let steps = width + height - 2;
+    let mut out = String::new();
+    for y in 0..height {
+        for x in 0..width {
+            let ramp = ((x + y) * 25 / steps) as u64;
+            let c = if (x, y) == (0, 0) {
+                'S'
+            } else if (x, y) == (width - 1, height - 1) {
+                'E'
+            } else if y > 0 && x < width - 1 && rng.chance(30) {
+                char::from(b'a' + rng.range(ramp.saturating_sub(3)..=ramp) as u8)
+            } else {
+                char::from(b'a' + ramp as u8)
+            };
+            out.push(c);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// `size` pairs of packets.
+pub fn day13(rng: &mut Rng, size: usize) -> String {
+    fn packet(rng: &mut Rng, depth: usize) -> String {
+        let items: Vec<String> = (0..rng.range(0..=4))
+            .map(|_| if depth < 4 && rng.chance(30) { packet(rng, depth + 1) } else { rng.range(0..=10).to_string() })
+            .collect();
+        format!("[{}]", items.join(","))
+    }
+
+    let mut out = String::new();
+    for i in 0..size {
+        if i > 0 {
+            out.push('\n');
+        }
+        writeln!(out, "{}\n{}", packet(rng, 0), packet(rng, 0)).unwrap();
+    }
+    out
+}
+
+/// `size` paths of rock below and around the sand source.
+pub fn day14(rng: &mut Rng, size: usize) -> String {
+    let mut out = String::new();
+    for _ in 0..size {
+        let mut point = (rng.range(470..=530), rng.range(2..=40));
+        let mut points = vec![format!("{},{}", point.0, point.1)];
+        for i in 0..rng.range(1..=4) {
+            if i % 2 == 0 {
+                point.0 = rng.range(point.0.saturating_sub(8)..=point.0 + 8);
+            } else {
+                point.1 = rng.range(point.1.saturating_sub(8).max(1)..=point.1 + 8);
+            }
+            points.push(format!("{},{}", point.0, point.1));
+        }
+        writeln!(out, "{}", points.join(" -> ")).unwrap();
+    }
+    out
+}
+
+/// Sensors that cover everything within 4000000 of a hidden distress beacon except the beacon
+/// itself, plus `size` more that don't reach it. Part 2 always has exactly one answer.
+pub fn day15(rng: &mut Rng, size: usize) -> String {
+    const BOUND: i64 = 4000000;
+    let hidden = (rng.range_i64(0..=BOUND), rng.range_i64(0..=BOUND));
+    let mut out = String::new();
+    let sensor = |out: &mut String, s: (i64, i64), b: (i64, i64)| {
+        writeln!(out, "Sensor at x={}, y={}: closest beacon is at x={}, y={}", s.0, s.1, b.0, b.1).unwrap();
+    };
+
+    // A sensor diagonally `BOUND` away in each direction, reaching one short of the hidden beacon,
+    // covers every other point within `BOUND` of it on both axes.
+    for (dx, dy) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+        let s = (hidden.0 + dx * BOUND, hidden.1 + dy * BOUND);
+        sensor(&mut out, s, (s.0 - dx * (2 * BOUND - 1), s.1));
+    }
+    for _ in 0..size {
+        let s = (rng.range_i64(0..=BOUND), rng.range_i64(0..=BOUND));
+        let reach = (s.0 - hidden.0).abs() + (s.1 - hidden.1).abs() - 1;
+        if reach < 1 {
+            continue;
+        }
+        let dx = rng.range_i64(0..=reach.min(BOUND));
+        sensor(&mut out, s, (s.0 + dx, s.1 - (reach.min(BOUND) - dx)));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded() {
+        assert_eq!(generate(9, 7, 20), generate(9, 7, 20));
+        assert_ne!(generate(9, 7, 20), generate(9, 8, 20));
+        assert!(generate(16, 7, 20).is_none());
+        let mut rng = Rng::new(1);
+        for _ in 0..1000 {
+            assert!((-3..=3).contains(&rng.range_i64(-3..=3)));
+        }
+    }
+
+    #[test]
+    fn round_trip() -> anyhow::Result<()> {
+        // A newly scaffolded day has no generator until one is written.
+        for day in crate::days().iter().filter(|day| generator(day.number).is_some()) {
+            for seed in 0..5 {
+                for size in [0, 1, 10, 30] {
+                    let input = generate(day.number, seed, size).unwrap();
+                    let solution = (day.parse)(&mut input.as_bytes()).map_err(|e| e.context(format!("seed {} size {}:\n{}", seed, size, input)))?;
+                    // Day 15's scans cover millions of points whatever the input size.
+                    if day.number != 15 {
+                        for part in [1, 2] {
+                            solution.part(part).map_err(|e| e.context(format!("part {} seed {} size {}:\n{}", part, seed, size, input)))?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
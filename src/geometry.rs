@@ -0,0 +1,261 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use serde::{Deserialize, Serialize};
+
+/// A number that can be used as a coordinate.
+pub trait Coord: Copy + Ord + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> {
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// The distance between two values, which can't overflow even for unsigned types.
+    fn distance(self, other: Self) -> Self;
+}
+
+macro_rules! coord {
+    ($($t:ty),*) => {
+        $(impl Coord for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn distance(self, other: Self) -> Self {
+                self.max(other) - self.min(other)
+            }
+        })*
+    };
+}
+
+coord!(i32, i64, isize, u32, u64, usize);
+
+/// A point (or a vector between points) on a 2D plane. Like [`crate::grid::Grid`], `y` counts down
+/// the screen, so [`Direction::Up`] is towards negative `y`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Point { x, y }
+    }
+}
+
+impl<T: Coord> Point<T> {
+    pub const ORIGIN: Self = Point::new(T::ZERO, T::ZERO);
+
+    pub fn manhattan(self, other: Self) -> T {
+        self.x.distance(other.x) + self.y.distance(other.y)
+    }
+
+    /// The distance counting diagonal steps as one, so all eight neighbours are at distance 1.
+    pub fn chebyshev(self, other: Self) -> T {
+        self.x.distance(other.x).max(self.y.distance(other.y))
+    }
+
+    /// Whether the points are the same or next to each other, including diagonally.
+    pub fn is_touching(self, other: Self) -> bool {
+        self.chebyshev(other) <= T::ONE
+    }
+}
+
+impl<T: Coord + Neg<Output = T>> Point<T> {
+    /// Turns a vector a quarter turn clockwise about the origin, as it appears on screen.
+    pub fn rotate_right(self) -> Self {
+        Point::new(-self.y, self.x)
+    }
+
+    /// Turns a vector a quarter turn anticlockwise about the origin, as it appears on screen.
+    pub fn rotate_left(self) -> Self {
+        Point::new(self.y, -self.x)
+    }
+
+    /// Clamps each component to -1, 0 or 1, giving the single step (including diagonals) that
+    /// moves towards where this vector points.
+    pub fn signum(self) -> Self {
+        let one = |v: T| v.clamp(-T::ONE, T::ONE);
+        Point::new(one(self.x), one(self.y))
+    }
+}
+
+impl Point<usize> {
+    /// Moves by a signed vector, if the result doesn't go below zero.
+    pub fn checked_add_signed(self, delta: Point<isize>) -> Option<Self> {
+        Some(Point::new(self.x.checked_add_signed(delta.x)?, self.y.checked_add_signed(delta.y)?))
+    }
+}
+
+impl<T> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Point { x, y }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl<T: Coord> Add for Point<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T: Coord> AddAssign for Point<T> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<T: Coord> Sub for Point<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T: Coord> SubAssign for Point<T> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl<T: Coord> Mul<T> for Point<T> {
+    type Output = Self;
+
+    fn mul(self, scale: T) -> Self {
+        Point::new(self.x * scale, self.y * scale)
+    }
+}
+
+/// One of the four orthogonal directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+    /// A one-step vector in this direction.
+    pub fn unit<T: Coord + Neg<Output = T>>(self) -> Point<T> {
+        match self {
+            Direction::Up => Point::new(T::ZERO, -T::ONE),
+            Direction::Down => Point::new(T::ZERO, T::ONE),
+            Direction::Left => Point::new(-T::ONE, T::ZERO),
+            Direction::Right => Point::new(T::ONE, T::ZERO),
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    /// The direction a quarter turn clockwise.
+    pub fn turn_right(self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    /// The direction a quarter turn anticlockwise.
+    pub fn turn_left(self) -> Self {
+        self.turn_right().opposite()
+    }
+}
+
+/// The smallest axis-aligned rectangle holding a set of points, with inclusive bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bounds<T> {
+    pub min: Point<T>,
+    pub max: Point<T>,
+}
+
+impl<T: Coord> Bounds<T> {
+    pub fn new(point: Point<T>) -> Self {
+        Bounds { min: point, max: point }
+    }
+
+    /// The bounds of some points, or `None` if there are none.
+    pub fn of(points: impl IntoIterator<Item = Point<T>>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let mut bounds = Bounds::new(points.next()?);
+        points.for_each(|p| bounds.include(p));
+        Some(bounds)
+    }
+
+    /// Grows the bounds to hold `point`.
+    pub fn include(&mut self, point: Point<T>) {
+        self.min = Point::new(self.min.x.min(point.x), self.min.y.min(point.y));
+        self.max = Point::new(self.max.x.max(point.x), self.max.y.max(point.y));
+    }
+
+    /// Grows the bounds by `margin` on every side.
+    pub fn expand(self, margin: T) -> Self {
+        Bounds { min: self.min - Point::new(margin, margin), max: self.max + Point::new(margin, margin) }
+    }
+
+    pub fn contains(&self, point: Point<T>) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) && (self.min.y..=self.max.y).contains(&point.y)
+    }
+
+    pub fn width(&self) -> T {
+        self.max.x - self.min.x + T::ONE
+    }
+
+    pub fn height(&self) -> T {
+        self.max.y - self.min.y + T::ONE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances() {
+        let (a, b) = (Point::new(2i64, 18), Point::new(-2, 15));
+        assert_eq!(7, a.manhattan(b));
+        assert_eq!(4, a.chebyshev(b));
+        assert_eq!(Point::new(-1, -1), (b - a).signum());
+        assert!(Point::new(3usize, 4).is_touching(Point::new(4, 5)));
+        assert!(!Point::new(3usize, 4).is_touching(Point::new(5, 4)));
+        assert_eq!(None, Point::new(0usize, 4).checked_add_signed(Direction::Left.unit()));
+    }
+
+    #[test]
+    fn rotation() {
+        for direction in Direction::ALL {
+            assert_eq!(direction.turn_right().unit::<i32>(), direction.unit().rotate_right());
+            assert_eq!(direction.turn_left().unit::<i32>(), direction.unit().rotate_left());
+            assert_eq!(direction, direction.turn_left().turn_right());
+        }
+        assert_eq!(Direction::Right, Direction::Up.turn_right());
+    }
+
+    #[test]
+    fn bounds() {
+        let bounds = Bounds::of([Point::new(3, -1), Point::new(-2, 4), Point::new(0, 0)]).unwrap();
+        assert_eq!(Bounds { min: Point::new(-2, -1), max: Point::new(3, 4) }, bounds);
+        assert_eq!((6, 6), (bounds.width(), bounds.height()));
+        assert!(bounds.contains(Point::new(3, 4)));
+        assert!(!bounds.expand(1).contains(Point::new(5, 0)));
+        assert_eq!(None, Bounds::<i32>::of([]));
+    }
+}
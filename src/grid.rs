@@ -0,0 +1,212 @@
+use std::fmt;
+use std::io::BufRead;
+use std::ops::{Index, IndexMut};
+
+use anyhow::Result;
+
+use crate::geometry::{Bounds, Direction, Point};
+use crate::parse::ParseError;
+
+/// A rectangular grid stored contiguously row by row. Positions are points where `x` is the column
+/// and `y` is the row, counting down from the top.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Grid { width, height, cells: vec![fill; width * height] }
+    }
+
+    /// The part of the grid within `bounds`, clipped to its edges, or `None` if they don't overlap.
+    pub fn crop(&self, bounds: Bounds<usize>) -> Option<Self> {
+        let max = Point::new(bounds.max.x.min(self.width.checked_sub(1)?), bounds.max.y.min(self.height.checked_sub(1)?));
+        if bounds.min.x > max.x || bounds.min.y > max.y {
+            return None;
+        }
+        let cells = (bounds.min.y..=max.y).flat_map(|y| self.row(y)[bounds.min.x..=max.x].iter().cloned()).collect();
+        Some(Grid { width: max.x - bounds.min.x + 1, height: max.y - bounds.min.y + 1, cells })
+    }
+}
+
+impl<T> Grid<T> {
+    /// Parses a grid with one row per line and one cell per character. `cell` is given each
+    /// character and its position; errors it returns get the line and column filled in. `what`
+    /// names the cells in the errors for ragged or empty input, e.g. "trees".
+    pub fn parse(day: u8, input: impl BufRead, what: &str, mut cell: impl FnMut(Point<usize>, char) -> Result<T, ParseError>) -> Result<Self> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = vec![];
+        for (y, line) in input.lines().enumerate() {
+            let line = line?;
+            let line = line.trim_end();
+            let mut row_width = 0;
+            for (x, c) in line.chars().enumerate() {
+                cells.push(cell(Point::new(x, y), c).map_err(|e| e.at_line(y + 1).at_column(x + 1))?);
+                row_width += 1;
+            }
+            match width {
+                None => width = Some(row_width),
+                Some(width) if width != row_width => Err(ParseError::new(day, format!("{} {}", width, what), format!("{} {}", row_width, what)).at_line(y + 1))?,
+                Some(_) => {}
+            }
+            height += 1;
+        }
+        match width {
+            Some(width) if width > 0 => Ok(Grid { width, height, cells }),
+            _ => Err(ParseError::new(day, format!("a row of {}", what), "an empty line").at_line(1))?,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, pos: Point<usize>) -> bool {
+        pos.x < self.width && pos.y < self.height
+    }
+
+    pub fn get(&self, pos: Point<usize>) -> Option<&T> {
+        self.contains(pos).then(|| &self.cells[pos.y * self.width + pos.x])
+    }
+
+    pub fn get_mut(&mut self, pos: Point<usize>) -> Option<&mut T> {
+        if self.contains(pos) {
+            Some(&mut self.cells[pos.y * self.width + pos.x])
+        } else {
+            None
+        }
+    }
+
+    /// Every position in the grid, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Point<usize>> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Point::new(x, y)))
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point<usize>, &T)> {
+        self.positions().zip(&self.cells)
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "column {} is outside a grid {} wide", x, self.width);
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    /// The position `delta` away from `pos`, if it is still inside the grid.
+    pub fn step(&self, pos: Point<usize>, delta: Point<isize>) -> Option<Point<usize>> {
+        pos.checked_add_signed(delta).filter(|&pos| self.contains(pos))
+    }
+
+    /// The up to four orthogonal neighbours of `pos` that are inside the grid.
+    pub fn neighbours(&self, pos: Point<usize>) -> impl Iterator<Item = Point<usize>> + '_ {
+        Direction::ALL.into_iter().filter_map(move |d| self.step(pos, d.unit()))
+    }
+
+    /// The up to eight neighbours of `pos`, including diagonals, that are inside the grid.
+    pub fn neighbours_8(&self, pos: Point<usize>) -> impl Iterator<Item = Point<usize>> + '_ {
+        Direction::ALL
+            .into_iter()
+            .flat_map(|d| [d.unit(), d.unit() + d.turn_right().unit()])
+            .filter_map(move |delta| self.step(pos, delta))
+    }
+
+    /// The positions from `pos` (not included) stepping by `delta` until the edge of the grid.
+    pub fn ray(&self, pos: Point<usize>, delta: Point<isize>) -> impl Iterator<Item = Point<usize>> + '_ {
+        std::iter::successors(self.step(pos, delta), move |&p| self.step(p, delta))
+    }
+
+    /// The rays from `pos` to each edge, in the order of [`Direction::ALL`].
+    pub fn rays(&self, pos: Point<usize>) -> [impl Iterator<Item = Point<usize>> + '_; 4] {
+        Direction::ALL.map(|d| self.ray(pos, d.unit()))
+    }
+}
+
+impl<T> Index<Point<usize>> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Point<usize>) -> &T {
+        match self.get(pos) {
+            Some(cell) => cell,
+            None => panic!("{} is outside a {}x{} grid", pos, self.width, self.height),
+        }
+    }
+}
+
+impl<T> IndexMut<Point<usize>> for Grid<T> {
+    fn index_mut(&mut self, pos: Point<usize>) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        match self.get_mut(pos) {
+            Some(cell) => cell,
+            None => panic!("{} is outside a {}x{} grid", pos, width, height),
+        }
+    }
+}
+
+/// Draws the grid one row per line, with each cell's own Display next to each other.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..self.height {
+            for cell in self.row(y) {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(input: &str) -> Result<Grid<u32>> {
+        Grid::parse(0, input.as_bytes(), "digits", |_, c| c.to_digit(10).ok_or_else(|| ParseError::new(0, "a digit", format!("'{}'", c))))
+    }
+
+    #[test]
+    fn access() -> Result<()> {
+        let mut grid = digits("123\n456\n")?;
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!(6, grid[Point::new(2, 1)]);
+        assert_eq!(None, grid.get(Point::new(3, 0)));
+        assert_eq!(&[4, 5, 6], grid.row(1));
+        assert_eq!(vec![&2, &5], grid.column(1).collect::<Vec<_>>());
+        grid[Point::new(0, 0)] = 7;
+        assert_eq!("723\n456\n", grid.to_string());
+        assert_eq!("23\n56\n", grid.crop(Bounds { min: Point::new(1, 0), max: Point::new(5, 5) }).unwrap().to_string());
+        assert_eq!(None, grid.crop(Bounds::new(Point::new(3, 0))));
+        Ok(())
+    }
+
+    #[test]
+    fn neighbours() -> Result<()> {
+        let grid = digits("123\n456\n789\n")?;
+        assert_eq!(vec![Point::new(0, 1), Point::new(1, 0)], grid.neighbours(Point::ORIGIN).collect::<Vec<_>>());
+        assert_eq!(4, grid.neighbours(Point::new(1, 1)).count());
+        assert_eq!(8, grid.neighbours_8(Point::new(1, 1)).count());
+        assert_eq!(3, grid.neighbours_8(Point::new(2, 2)).count());
+        assert_eq!(vec![Point::new(1, 1), Point::new(0, 1)], grid.ray(Point::new(2, 1), Direction::Left.unit()).collect::<Vec<_>>());
+        assert_eq!(0, grid.ray(Point::new(2, 1), Point::new(1, 1)).count());
+        Ok(())
+    }
+
+    #[test]
+    fn bad_grids() {
+        assert_eq!("day 00, line 2, column 2: expected a digit, found 'x'", digits("12\n3x\n").unwrap_err().to_string());
+        assert_eq!("day 00, line 2: expected 2 digits, found 3 digits", digits("12\n345\n").unwrap_err().to_string());
+        assert_eq!("day 00, line 1: expected a row of digits, found an empty line", digits("").unwrap_err().to_string());
+    }
+}
@@ -0,0 +1,123 @@
+//! Finding puzzle inputs. By default each day reads a loose `dayNN.txt` from the working directory;
+//! with an inputs directory configured, inputs are grouped by user as `<dir>/<user>/dayNN.txt` so
+//! that several people's inputs can be kept and run side by side.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::Day;
+
+/// The environment variable naming the inputs directory, used when no directory is passed in.
+pub const INPUTS_ENV: &str = "ADVENT2022_INPUTS";
+
+/// The environment variable naming whose inputs to use, used when no user is passed in.
+pub const USER_ENV: &str = "ADVENT2022_USER";
+
+/// Where to look for inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inputs {
+    /// The inputs directory, or `None` for loose files in the working directory.
+    dir: Option<PathBuf>,
+    /// Whose inputs to use when a day's input isn't asked for by user.
+    user: Option<String>,
+}
+
+impl Inputs {
+    pub fn new(dir: Option<PathBuf>, user: Option<String>) -> Self {
+        Inputs { dir, user }
+    }
+
+    /// Uses the given directory and user, falling back to [`INPUTS_ENV`] and [`USER_ENV`].
+    pub fn configured(dir: Option<&str>, user: Option<&str>) -> Self {
+        let env = |name| std::env::var(name).ok().filter(|v: &String| !v.is_empty());
+        Inputs::new(dir.map(PathBuf::from).or_else(|| env(INPUTS_ENV).map(PathBuf::from)), user.map(str::to_string).or_else(|| env(USER_ENV)))
+    }
+
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    /// The users with inputs, in name order: the subdirectories of the inputs directory.
+    pub fn users(&self) -> Result<Vec<String>> {
+        let dir = self.dir.as_deref().ok_or_else(|| anyhow!("No inputs directory; set {} or pass --inputs", INPUTS_ENV))?;
+        let mut users = vec![];
+        for entry in std::fs::read_dir(dir).with_context(|| format!("reading inputs directory {}", dir.display()))? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                users.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+        users.sort();
+        Ok(users)
+    }
+
+    /// The input file for a day: the configured user's, or with no user the only one there is. With
+    /// no inputs directory it is the day's loose file in the working directory.
+    pub fn resolve(&self, day: &Day) -> Result<String> {
+        let Some(dir) = &self.dir else {
+            let file = day.input_file();
+            if !Path::new(&file).is_file() {
+                Err(anyhow!("No input for day {:02}: {} is not in the working directory (or set {} to use an inputs directory)", day.number, file, INPUTS_ENV))?;
+            }
+            return Ok(file);
+        };
+        match &self.user {
+            Some(user) => self.resolve_for(day, user),
+            None => match self.users()?.as_slice() {
+                [user] => self.resolve_for(day, user),
+                [] => Err(anyhow!("Inputs directory {} has no user directories", dir.display())),
+                users => Err(anyhow!("Inputs directory {} has inputs for {}; pick one with --user or {}", dir.display(), users.join(", "), USER_ENV)),
+            },
+        }
+    }
+
+    /// A particular user's input file for a day.
+    pub fn resolve_for(&self, day: &Day, user: &str) -> Result<String> {
+        let dir = self.dir.as_deref().ok_or_else(|| anyhow!("No inputs directory to find {}'s inputs in; set {} or pass --inputs", user, INPUTS_ENV))?;
+        let user_dir = dir.join(user);
+        if !user_dir.is_dir() {
+            Err(anyhow!("No inputs for user '{}': {} is not a directory (users: {})", user, user_dir.display(), self.users()?.join(", ")))?;
+        }
+        let file = user_dir.join(day.input_file());
+        if !file.is_file() {
+            Err(anyhow!("No input for day {:02} for user '{}': {} does not exist", day.number, user, file.display()))?;
+        }
+        Ok(file.display().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn resolve() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("advent2022-inputs-{}", std::process::id()));
+        fs::create_dir_all(dir.join("alice"))?;
+        fs::create_dir_all(dir.join("bob"))?;
+        fs::copy("day07ex.txt", dir.join("alice/day07.txt"))?;
+        fs::write(dir.join("notes.txt"), "not a user")?;
+        let day = crate::day(7).unwrap();
+
+        let inputs = Inputs::new(Some(dir.clone()), None);
+        assert_eq!(vec!["alice", "bob"], inputs.users()?);
+        assert_eq!(dir.join("alice/day07.txt").display().to_string(), inputs.resolve_for(day, "alice")?);
+        let error = inputs.resolve(day).unwrap_err().to_string();
+        assert!(error.contains("alice, bob"), "{}", error);
+        let error = inputs.resolve_for(day, "bob").unwrap_err().to_string();
+        assert!(error.contains("day 07 for user 'bob'"), "{}", error);
+        let error = inputs.resolve_for(day, "carol").unwrap_err().to_string();
+        assert!(error.contains("users: alice, bob"), "{}", error);
+
+        let inputs = Inputs::new(Some(dir.clone()), Some("alice".to_string()));
+        assert_eq!(95437.to_string(), day.load(&inputs.resolve(day)?)?.part_1()?.to_string());
+
+        assert_eq!("day07.txt", Inputs::new(None, None).resolve(day)?);
+        assert!(Inputs::new(None, None).users().is_err());
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
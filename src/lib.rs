@@ -1,15 +1,238 @@
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+
+pub mod answers;
+pub mod bench;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod differential;
+pub mod executor;
+pub mod generate;
+pub mod geometry;
+pub mod grid;
+pub mod inputs;
+pub mod metrics;
+pub mod output;
+pub mod params;
+pub mod parse;
+pub mod property;
+pub mod repl;
+pub mod scaffold;
+pub mod serve;
+#[cfg(test)]
+mod snapshot;
+pub mod status;
+pub mod trace;
+pub mod visualize;
+pub mod watch;
+
+/// The answer to one part of a puzzle. In JSON numbers stay numbers and text is a string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(number) => write!(f, "{}", number),
+            Answer::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+/// Numbers too big for an `i64` become text rather than wrapping around.
+macro_rules! answer_from_number {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(value: $t) -> Self {
+                i64::try_from(value).map(Answer::Number).unwrap_or_else(|_| Answer::Text(value.to_string()))
+            }
+        })*
+    };
+}
+
+answer_from_number!(i32, i64, u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+/// The error a part gives until it is written, as a new day's parts do (see [`scaffold`]), so that
+/// [`status`] can tell it apart from a part that fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotSolved;
+
+impl fmt::Display for NotSolved {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not solved yet")
+    }
+}
+
+impl std::error::Error for NotSolved {}
+
+/// Opens a puzzle input file so it can be handed to any of the loaders.
+pub fn open(file: &str) -> Result<BufReader<File>> {
+    Ok(BufReader::new(File::open(file).with_context(|| format!("opening {}", file))?))
+}
+
+/// A solver for one day's puzzle, holding that day's parsed input.
+pub trait Solution {
+    fn parse(input: &mut dyn BufRead) -> Result<Self> where Self: Sized;
+    fn part_1(&self) -> Result<Answer>;
+    fn part_2(&self) -> Result<Answer>;
+
+    /// Runs part 1 or part 2 by number.
+    fn part(&self, part: u8) -> Result<Answer> {
+        match part {
+            1 => self.part_1(),
+            2 => self.part_2(),
+            other => Err(anyhow!("No such part: {}", other)),
+        }
+    }
+
+    /// The parsed input as JSON, for days whose models can be serialized.
+    fn model(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    /// Changes some of the puzzle's parameters from their defaults (see [`params`]). Days without
+    /// parameters reject any change.
+    fn configure(&mut self, changes: &params::Changes) -> Result<()> {
+        match changes.keys().next() {
+            Some(name) => Err(anyhow!("Unknown parameter '{}', this day has none", name)),
+            None => Ok(()),
+        }
+    }
+
+    /// The puzzle's parameters as JSON, for days that have any.
+    fn params(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    /// The day's simulation, for days that can draw their steps.
+    fn visualizer(&self) -> Option<&dyn visualize::Visualize> {
+        None
+    }
+
+    /// A session of commands over the parsed input for the [`repl`], for days that have any.
+    fn explorer(&self) -> Option<Box<dyn repl::Explore + '_>> {
+        None
+    }
+
+    /// Other implementations of a part, checked against [`Solution::part`] by [`differential`]
+    /// testing.
+    fn implementations(&self, _part: u8) -> Vec<differential::Implementation<'_>> {
+        vec![]
+    }
+}
+
+/// A registered day: its number and how to parse its input into a solution.
+pub struct Day {
+    pub number: u8,
+    pub parse: fn(&mut dyn BufRead) -> Result<Box<dyn Solution>>,
+}
+
+impl Day {
+    /// Parses this day's solution from an input file, with the default parameters.
+    pub fn load(&self, file: &str) -> Result<Box<dyn Solution>> {
+        (self.parse)(&mut open(file)?)
+    }
+
+    /// Parses this day's solution from an input file, then changes some of its parameters.
+    pub fn load_with(&self, file: &str, changes: &params::Changes) -> Result<Box<dyn Solution>> {
+        let mut solution = self.load(file)?;
+        solution.configure(changes)?;
+        Ok(solution)
+    }
+
+    /// The name of this day's input file, which is looked for in the working directory or a user's
+    /// inputs directory (see [`inputs::Inputs`]).
+    pub fn input_file(&self) -> String {
+        format!("day{:02}.txt", self.number)
+    }
+}
+
+fn boxed<S: Solution + 'static>(input: &mut dyn BufRead) -> Result<Box<dyn Solution>> {
+    Ok(Box::new(S::parse(input)?))
+}
+
+const DAYS: [Day; 15] = [
+    Day { number: 1, parse: boxed::<day01::Day01> },
+    Day { number: 2, parse: boxed::<day02::Day02> },
+    Day { number: 3, parse: boxed::<day03::Day03> },
+    Day { number: 4, parse: boxed::<day04::Day04> },
+    Day { number: 5, parse: boxed::<day05::Day05> },
+    Day { number: 6, parse: boxed::<day06::Day06> },
+    Day { number: 7, parse: boxed::<day07::Day07> },
+    Day { number: 8, parse: boxed::<day08::Day08> },
+    Day { number: 9, parse: boxed::<day09::Day09> },
+    Day { number: 10, parse: boxed::<day10::Day10> },
+    Day { number: 11, parse: boxed::<day11::Day11> },
+    Day { number: 12, parse: boxed::<day12::Day12> },
+    Day { number: 13, parse: boxed::<day13::Day13> },
+    Day { number: 14, parse: boxed::<day14::Day14> },
+    Day { number: 15, parse: boxed::<day15::Day15> },
+];
+
+/// All registered days, in order.
+pub fn days() -> &'static [Day] {
+    &DAYS
+}
+
+/// Looks up a registered day by its number.
+pub fn day(number: u8) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.number == number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry() -> Result<()> {
+        for (i, day) in days().iter().enumerate() {
+            assert_eq!(i + 1, day.number as usize);
+        }
+        assert!(day(0).is_none());
+        assert!(day(days().len() as u8 + 1).is_none());
+        let solution = day(10).unwrap().load("day10ex.txt")?;
+        assert_eq!(Answer::Number(13140), solution.part_1()?);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_from_str() -> Result<()> {
+        let solution = (day(9).unwrap().parse)(&mut "R 4\nU 4\nL 3\nD 1\nR 4\nD 1\nL 5\nR 2\n".as_bytes())?;
+        assert_eq!(Answer::Number(13), solution.part_1()?);
+        Ok(())
+    }
+
+    #[test]
+    fn big_numbers() {
+        assert_eq!(Answer::Number(i64::MAX), Answer::from(i64::MAX as u64));
+        assert_eq!(Answer::Text("18446744073709551615".to_string()), Answer::from(u64::MAX));
+        assert_eq!(Answer::Number(-1), Answer::from(-1i32));
+    }
+}
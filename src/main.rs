@@ -0,0 +1,909 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::io::{BufWriter, IsTerminal, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::rc::Rc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+
+use advent2022::answers::{single_line, verify_day, Answers, Outcome, ANSWERS_FILE};
+use advent2022::bench::{bench_day, Report, Runtimes, BENCH_FILE, TABLE_HEADER};
+use advent2022::differential::{self, Disagreement};
+use advent2022::executor::{self, run_parallel, Task};
+use advent2022::generate::generate;
+use advent2022::inputs::Inputs;
+use advent2022::output::{self, DayOutput, PartOutput};
+use advent2022::geometry::{Bounds, Point};
+use advent2022::params::{parse_change, Changes, Config, PARAMS_FILE};
+use advent2022::repl::repl;
+use advent2022::scaffold::new_day;
+use advent2022::serve::{Server, SOLVE_TIMEOUT};
+use advent2022::status::status;
+use advent2022::trace::{self, Level};
+use advent2022::visualize::{Frames, Options, Target};
+use advent2022::watch::{watch_files, Rerun, Watch};
+use advent2022::{day, days, open, Day, NotSolved};
+
+const USAGE: &str = "Usage:
+  advent2022 run <day|all> [part] [--input <path|->] [--json] [--model]
+                 [--jobs <n>] [--timeout <secs>]
+  advent2022 bench <day|all> [--runs <n>] [--input <path>] [--json <path>]
+  advent2022 verify [day|all] [--input <path>] [--record]
+  advent2022 status [day|all] [--json] [--bench <path>] [--jobs <n>] [--timeout <secs>]
+  advent2022 batch <day|all> [--jobs <n>] [--timeout <secs>] [--record]
+  advent2022 params <day> [--input <path>]
+  advent2022 visualize <day> [part] [--input <path>] [--every <n>]
+                 [--region <x1,y1,x2,y2>] [--delay <ms>] [--ppm <dir>] [--scale <n>]
+  advent2022 trace <day> [part] [--input <path>] [--verbosity <level>] [--out <path>]
+  advent2022 replay [log|-] [--verbosity <level>]
+  advent2022 watch <day|all> [--input <path>] [--poll <ms>]
+  advent2022 generate <day> [--seed <n>] [--size <n>]
+  advent2022 differential <day|all> [part] [--seeds <n>] [--input <path>]
+  advent2022 repl <day> [--input <path>]
+  advent2022 serve [--host <addr>] [--port <n>] [--timeout <secs>] [--param <name=value>]...
+  advent2022 new-day <day>
+
+run, bench, verify, status, batch, params, visualize, trace, watch, differential and repl also take [--inputs <dir>] [--user <name>]
+[--params <path>] [--param <name=value>]...
+
+Inputs  Without --input, each day's input is dayNN.txt in the working
+        directory. Given an inputs directory (--inputs, or the
+        ADVENT2022_INPUTS environment variable) it is <dir>/<user>/dayNN.txt
+        instead, for the user given by --user or ADVENT2022_USER, which can be
+        left out when the directory holds a single user's inputs.
+Params  Numbers a puzzle gives in its text, such as day 15's row, can be
+        changed for each input in params.json (or the --params file), keyed
+        by day and input like answers.json, and for every input with --param,
+        whose value is JSON or else a string.
+run     Runs the given day (or every registered day) and prints the answers.
+        Without a part both parts are run. The input is found as above, or
+        read from stdin when it is piped in and neither --inputs nor --user
+        is given; '-' reads stdin explicitly. --json prints one JSON object per day instead, and
+        --model adds the parsed input to it where the day supports that.
+        'all' runs the parts in parallel (--jobs, default one per CPU), giving
+        each a wall-clock budget (--timeout, default 60 seconds) after which
+        it is reported as timed out.
+bench   Times parsing and each part over repeated runs (default 10) and prints
+        min/median/max, optionally writing a JSON report to compare later.
+        Alongside the timings are counts of the work some parts do, such as
+        nodes expanded on day 12 or sensor checks on day 15.
+verify  Checks answers against answers.json for the default input and every
+        input with a recorded answer, reporting pass/fail/missing. Missing
+        answers are recorded with --record, or after asking when interactive.
+status  Lists every registered day's parts: whether each is written yet,
+        whether its answer on the day's input matches answers.json (a star),
+        how many of the examples with recorded answers it gets right and its
+        median time in the last benchmark report (bench.json, as written by
+        'bench all --json bench.json', or --bench), '-' if it has none. The
+        parts run in parallel as with 'run all', with the same --jobs and
+        --timeout. --json prints the same as JSON.
+generate
+        Prints a random but valid input for a day. The same seed (default 0)
+        always gives the same input; size (default 100) scales it, e.g. the
+        number of elves, moves or monkeys.
+batch   Runs the given days on every user's inputs in the inputs directory,
+        listing each user's answers side by side and checking them against
+        answers.json like verify does.
+params  Prints the parameters a day would use for its input, as JSON.
+visualize
+        Draws each step of a day's simulation (part 1 by default) for days 5,
+        9, 10, 12 and 14, as text or, with --ppm, as numbered PPM images in a
+        directory with each cell --scale pixels square (default 4). --every
+        draws only every nth step and --region only the cells between two
+        corners. With --delay the terminal is cleared between steps to play
+        them as an animation.
+trace   Runs a day (both parts, or the given one) writing what its
+        simulation does as JSON lines, one event per line, to stdout or --out:
+        monkeys' throws and rounds on day 11, rope moves on day 9, CPU cycles
+        on day 10 and sand coming to rest on day 14, ending with the answer.
+        --verbosity is summary (rounds and the like), step (the default) or
+        detail (everything, such as each place sand falls through).
+replay  Reads a trace log (a file, or stdin by default) and prints its events
+        as text, those above --verbosity left out.
+watch   Runs the given days, then re-runs a day whenever its input changes,
+        showing how each answer changed. Changes are picked up with file
+        system notifications, or by checking every --poll milliseconds when
+        given (or when notifications aren't available). Stop with Ctrl-C.
+differential
+        Runs every implementation of a part that a day offers (days 6, 12 and
+        15 have optimized rewrites) on its input and --seeds generated inputs
+        (default 30), reporting any disagreement on the smallest input that
+        still shows it.
+repl    Loads a day's input and reads commands for exploring it, such as
+        'du /a/e' on day 7, 'step 1' and 'stacks' on day 5, 'round' and
+        'monkeys' on day 11, 'path x y' on day 12 and 'covered x y' on day 15.
+        'help' lists a day's commands and 'part 1' solves a part.
+serve   Answers puzzles over HTTP on localhost (port 8022 by default) for
+        tools that can't link the crate. GET /days lists the registered days;
+        POST /days/{n}/parts/{p} runs a part on the request body, taking
+        parameter changes from the query string (?row=10) as well as --param,
+        and responds with JSON holding the answer or error and timings. Each
+        part gets --timeout (default 30 seconds) to run. It listens on
+        127.0.0.1 unless --host says otherwise, and warns when that is
+        reachable from other machines.
+new-day Starts the next day from the crate root (days are added in order):
+        writes src/dayNN.rs with a Solution skeleton and an example test to
+        fill in, registers it in src/lib.rs, and creates empty dayNN.txt and
+        dayNNex.txt files.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the command line, returning whether everything that was run succeeded.
+fn run(args: &[String]) -> Result<bool> {
+    match args.first().map(String::as_str) {
+        Some("run") => run_command(&Args::parse(&args[1..], &["--input", "--jobs", "--timeout"], &["--json", "--model"])?),
+        Some("bench") => bench_command(&Args::parse(&args[1..], &["--runs", "--input", "--json"], &[])?),
+        Some("generate") => generate_command(&Args::parse(&args[1..], &["--seed", "--size"], &[])?),
+        Some("verify") => verify_command(&Args::parse(&args[1..], &["--input"], &["--record"])?),
+        Some("batch") => batch_command(&Args::parse(&args[1..], &["--jobs", "--timeout"], &["--record"])?),
+        Some("params") => params_command(&Args::parse(&args[1..], &["--input"], &[])?),
+        Some("status") => status_command(&Args::parse(&args[1..], &["--bench", "--jobs", "--timeout"], &["--json"])?),
+        Some("visualize") => visualize_command(&Args::parse(&args[1..], &["--input", "--every", "--region", "--delay", "--ppm", "--scale"], &[])?),
+        Some("trace") => trace_command(&Args::parse(&args[1..], &["--input", "--verbosity", "--out"], &[])?),
+        Some("replay") => replay_command(&Args::parse(&args[1..], &["--verbosity"], &[])?),
+        Some("watch") => watch_command(&Args::parse(&args[1..], &["--input", "--poll"], &[])?),
+        Some("differential") => differential_command(&Args::parse(&args[1..], &["--seeds", "--input"], &[])?),
+        Some("repl") => repl_command(&Args::parse(&args[1..], &["--input"], &[])?),
+        Some("serve") => serve_command(&Args::parse(&args[1..], &["--host", "--port", "--timeout"], &[])?),
+        Some("new-day") => new_day_command(&Args::parse(&args[1..], &[], &[])?),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Ok(true)
+        }
+        Some(other) => Err(anyhow!("Unknown command '{}'\n\n{}", other, USAGE)),
+        None => Err(anyhow!("No command given\n\n{}", USAGE)),
+    }
+}
+
+/// The options taken by every subcommand that finds and runs inputs.
+const INPUT_OPTIONS: [&str; 4] = ["--inputs", "--user", "--params", "--param"];
+
+/// A subcommand's arguments, split into positional arguments, `--name value` options (which can be
+/// repeated) and `--name` flags.
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, Vec<String>>,
+    flags: HashSet<String>,
+}
+
+impl Args {
+    fn parse(args: &[String], options: &[&str], flags: &[&str]) -> Result<Self> {
+        let mut result = Args { positional: vec![], options: HashMap::new(), flags: HashSet::new() };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if options.contains(&arg.as_str()) || INPUT_OPTIONS.contains(&arg.as_str()) {
+                let value = args.next().ok_or_else(|| anyhow!("{} needs a value", arg))?;
+                result.options.entry(arg.clone()).or_default().push(value.clone());
+            } else if flags.contains(&arg.as_str()) {
+                result.flags.insert(arg.clone());
+            } else if arg.starts_with("--") {
+                Err(anyhow!("Unknown option '{}'\n\n{}", arg, USAGE))?;
+            } else {
+                result.positional.push(arg.clone());
+            }
+        }
+        Ok(result)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+
+    /// The last value given for an option.
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name)?.last().map(String::as_str)
+    }
+
+    /// Every value given for an option, in order.
+    fn all_options(&self, name: &str) -> &[String] {
+        self.options.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    fn parsed_option<T: std::str::FromStr>(&self, name: &str, default: T) -> Result<T> {
+        match self.option(name) {
+            Some(value) => value.parse().map_err(|_| anyhow!("Invalid value for {}: '{}'", name, value)),
+            None => Ok(default),
+        }
+    }
+
+    /// An option giving a number of seconds, which may be fractional.
+    fn duration_option(&self, name: &str, default: Duration) -> Result<Duration> {
+        match self.option(name) {
+            Some(value) => value.parse().ok().and_then(|seconds| Duration::try_from_secs_f64(seconds).ok()).ok_or_else(|| anyhow!("Invalid value for {}: '{}'", name, value)),
+            None => Ok(default),
+        }
+    }
+
+    /// Where to find inputs, from `--inputs` and `--user` or the environment.
+    fn inputs(&self) -> Inputs {
+        Inputs::configured(self.option("--inputs"), self.option("--user"))
+    }
+
+    /// The parameter changes from `--params` (or params.json) and `--param`.
+    fn config(&self) -> Result<Config> {
+        let mut config = Config::load(self.option("--params").unwrap_or(PARAMS_FILE))?;
+        for change in self.all_options("--param") {
+            let (name, value) = parse_change(change)?;
+            config.set(name, value);
+        }
+        Ok(config)
+    }
+
+    /// The `--jobs` and `--timeout` options for running parts in parallel.
+    fn parallelism(&self) -> Result<(usize, Duration)> {
+        let jobs = self.parsed_option("--jobs", std::thread::available_parallelism().map_or(1, |n| n.get()))?;
+        Ok((jobs, self.duration_option("--timeout", Duration::from_secs(60))?))
+    }
+}
+
+/// Resolves a day number, or `all`, to registered days.
+fn targets(target: &str) -> Result<Vec<&'static Day>> {
+    if target == "all" {
+        return Ok(days().iter().collect());
+    }
+    let number = target.parse::<u8>().with_context(|| format!("Invalid day '{}'", target))?;
+    Ok(vec![day(number).ok_or_else(|| anyhow!("Day {} is not registered", number))?])
+}
+
+fn run_command(args: &Args) -> Result<bool> {
+    let (target, part) = match args.positional.as_slice() {
+        [target] => (target, None),
+        [target, part] => (target, Some(parse_part(part)?)),
+        _ => Err(anyhow!("Expected a day and an optional part\n\n{}", USAGE))?,
+    };
+
+    let format = if args.flag("--json") || args.flag("--model") { Format::Json } else { Format::Text };
+    let with_model = args.flag("--model");
+    if target == "all" {
+        if args.option("--input").is_some() {
+            Err(anyhow!("--input can't be used with 'all'"))?;
+        }
+        let (jobs, timeout) = args.parallelism()?;
+        let mut ok = true;
+        for output in run_all(&args.inputs(), &args.config()?, part, jobs, timeout, with_model) {
+            ok &= print_output(&output, format);
+        }
+        return Ok(ok);
+    }
+
+    let day = targets(target)?[0];
+    let input = match args.option("--input") {
+        Some("-") => None,
+        Some(path) => Some(path.to_string()),
+        None if !std::io::stdin().is_terminal() && args.option("--inputs").is_none() && args.option("--user").is_none() => None,
+        None => Some(args.inputs().resolve(day)?),
+    };
+    let format = if format == Format::Text && part.is_some() { Format::Bare } else { format };
+    let changes = args.config()?.for_input(day.number, input.as_deref().unwrap_or("stdin"));
+    Ok(print_output(&day_output(day, input.as_deref(), &changes, part, with_model), format))
+}
+
+fn parse_part(part: &str) -> Result<u8> {
+    match part {
+        "1" => Ok(1),
+        "2" => Ok(2),
+        other => Err(anyhow!("Invalid part '{}', expected 1 or 2", other)),
+    }
+}
+
+/// How `run` prints what it found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// Answers labelled with their day and part.
+    Text,
+    /// Just the answer, for a single part of a single day.
+    Bare,
+    /// One JSON object per day.
+    Json,
+}
+
+/// Runs one or both parts of a day on an input file, or stdin when there is none.
+fn day_output(day: &Day, input: Option<&str>, changes: &Changes, part: Option<u8>, with_model: bool) -> DayOutput {
+    let parts = part.map_or(vec![1, 2], |p| vec![p]);
+    match input {
+        Some(file) => match open(file) {
+            Ok(mut reader) => output::run_day(day, &mut reader, file, changes, &parts, with_model),
+            Err(e) => DayOutput { day: day.number, input: file.to_string(), error: Some(format!("{:#}", e)), parts: vec![], model: None },
+        },
+        None => output::run_day(day, &mut std::io::stdin().lock(), "stdin", changes, &parts, with_model),
+    }
+}
+
+/// Runs one or both parts of every day on its input in parallel. Parse failures are reported
+/// against each part, as every part parses its own copy of the input.
+fn run_all(inputs: &Inputs, config: &Config, part: Option<u8>, jobs: usize, timeout: Duration, with_model: bool) -> Vec<DayOutput> {
+    let parts = part.map_or(vec![1, 2], |p| vec![p]);
+    let resolved: Vec<_> = days().iter().map(|day| (day, inputs.resolve(day))).collect();
+    let tasks = resolved
+        .iter()
+        .filter_map(|(day, input)| Some((day, input.as_ref().ok()?)))
+        .flat_map(|(day, input)| parts.iter().map(|&part| Task { day, input: input.clone(), params: config.for_input(day.number, input), part }))
+        .collect();
+    let mut finished = run_parallel(tasks, jobs, timeout).into_iter().peekable();
+
+    let mut outputs = vec![];
+    for (day, input) in resolved {
+        let input = match input {
+            Ok(input) => input,
+            Err(e) => {
+                outputs.push(DayOutput { day: day.number, input: day.input_file(), error: Some(format!("{:#}", e)), parts: vec![], model: None });
+                continue;
+            }
+        };
+        let mut output = DayOutput { day: day.number, input, error: None, parts: vec![], model: None };
+        while let Some(f) = finished.next_if(|f| f.task.day.number == day.number) {
+            let (answer, error) = match f.outcome {
+                executor::Outcome::Solved(answer) => (Some(answer), None),
+                executor::Outcome::Failed(e) => (None, Some(e)),
+                executor::Outcome::NotSolved => (None, Some(NotSolved.to_string())),
+                executor::Outcome::TimedOut => (None, Some(format!("timed out after {:?}", f.elapsed))),
+            };
+            output.parts.push(PartOutput { part: f.task.part, answer, error });
+        }
+        if with_model {
+            output.model = day.load_with(&output.input, &config.for_input(day.number, &output.input)).ok().and_then(|solution| solution.model());
+        }
+        outputs.push(output);
+    }
+    outputs
+}
+
+/// Prints a day's answers to stdout and, except as JSON, failures to stderr, returning whether
+/// everything succeeded.
+fn print_output(output: &DayOutput, format: Format) -> bool {
+    if format == Format::Json {
+        match serde_json::to_string(&output) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Error: Day {:02}: {}", output.day, e),
+        }
+        return output.is_ok();
+    }
+
+    if let Some(e) = &output.error {
+        eprintln!("Error: Day {:02}: {}", output.day, e);
+    }
+    for part in &output.parts {
+        match (&part.answer, &part.error) {
+            (Some(answer), _) => {
+                let answer = answer.to_string();
+                let answer = answer.trim_end();
+                if format == Format::Bare {
+                    println!("{}", answer);
+                } else if answer.contains('\n') {
+                    println!("Day {:02} part {}:\n{}", output.day, part.part, answer);
+                } else {
+                    println!("Day {:02} part {}: {}", output.day, part.part, answer);
+                }
+            }
+            (None, e) => eprintln!("Error: Day {:02} part {}: {}", output.day, part.part, e.as_deref().unwrap_or("no answer")),
+        }
+    }
+    output.is_ok()
+}
+
+fn bench_command(args: &Args) -> Result<bool> {
+    let [target] = args.positional.as_slice() else {
+        Err(anyhow!("Expected a day\n\n{}", USAGE))?
+    };
+    let days = targets(target)?;
+    if days.len() > 1 && args.option("--input").is_some() {
+        Err(anyhow!("--input can't be used with 'all'"))?;
+    }
+    let runs = args.parsed_option("--runs", 10)?;
+    let inputs = args.inputs();
+    let config = args.config()?;
+
+    let mut ok = true;
+    let mut report = Report { runs, days: vec![] };
+    println!("{}", TABLE_HEADER);
+    for day in days {
+        let bench = match args.option("--input") {
+            Some(input) => bench_day(day, input, &config.for_input(day.number, input), runs),
+            None => inputs.resolve(day).and_then(|input| bench_day(day, &input, &config.for_input(day.number, &input), runs)),
+        };
+        match bench {
+            Ok(bench) => {
+                print!("{}", bench);
+                report.days.push(bench);
+            }
+            Err(e) => {
+                eprintln!("Error: Day {:02}: {:#}", day.number, e);
+                ok = false;
+            }
+        }
+    }
+
+    if let Some(json) = args.option("--json") {
+        std::fs::write(json, serde_json::to_string_pretty(&report)?).with_context(|| format!("writing {}", json))?;
+    }
+    Ok(ok)
+}
+
+fn verify_command(args: &Args) -> Result<bool> {
+    let days = match args.positional.as_slice() {
+        [] => days().iter().collect(),
+        [target] => targets(target)?,
+        _ => Err(anyhow!("Expected at most one day\n\n{}", USAGE))?,
+    };
+    if days.len() > 1 && args.option("--input").is_some() {
+        Err(anyhow!("--input can't be used with 'all'"))?;
+    }
+    let interactive = std::io::stdin().is_terminal();
+    let source = args.inputs();
+    let config = args.config()?;
+
+    let mut answers = Answers::load(ANSWERS_FILE)?;
+    let mut recorded = false;
+    let (mut passed, mut failed, mut missing, mut errors) = (0, 0, 0, 0);
+    for day in days {
+        let inputs = match args.option("--input") {
+            Some(input) => vec![input.to_string()],
+            None => {
+                let mut inputs = vec![];
+                match source.resolve(day) {
+                    Ok(input) => inputs.push(input),
+                    Err(e) => {
+                        eprintln!("Error: {:#}", e);
+                        errors += 1;
+                    }
+                }
+                for input in answers.inputs(day.number) {
+                    if !inputs.iter().any(|i| i == input) {
+                        inputs.push(input.to_string());
+                    }
+                }
+                inputs
+            }
+        };
+        for input in inputs {
+            for check in verify_day(day, &input, &config.for_input(day.number, &input), &answers) {
+                println!("{}", check);
+                match &check.outcome {
+                    Outcome::Pass => passed += 1,
+                    Outcome::Fail { .. } => failed += 1,
+                    Outcome::Error(_) => errors += 1,
+                    Outcome::Missing { actual } => {
+                        missing += 1;
+                        if args.flag("--record") || (interactive && confirm("Record this answer?")?) {
+                            answers.set(check.day, &check.input, check.part, actual);
+                            recorded = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if recorded {
+        answers.save(ANSWERS_FILE)?;
+        println!("Recorded new answers in {}", ANSWERS_FILE);
+    }
+    println!("{} passed, {} failed, {} missing, {} errors", passed, failed, missing, errors);
+    Ok(failed == 0 && errors == 0)
+}
+
+fn batch_command(args: &Args) -> Result<bool> {
+    let [target] = args.positional.as_slice() else {
+        Err(anyhow!("Expected a day\n\n{}", USAGE))?
+    };
+    let days = targets(target)?;
+    let inputs = args.inputs();
+    let users = inputs.users()?;
+    if users.is_empty() {
+        Err(anyhow!("No user directories in {}", inputs.dir().map_or(String::new(), |d| d.display().to_string())))?;
+    }
+    let (jobs, timeout) = args.parallelism()?;
+
+    // Every user's input for every day, if they have one, and the parts run on each.
+    let resolved: Vec<(&Day, &str, Option<String>)> =
+        days.iter().flat_map(|&day| users.iter().map(|user| (day, user.as_str(), inputs.resolve_for(day, user).ok())).collect::<Vec<_>>()).collect();
+    let config = args.config()?;
+    let tasks = resolved
+        .iter()
+        .filter_map(|(day, _, input)| Some((*day, input.clone()?)))
+        .flat_map(|(day, input)| [1, 2].map(|part| Task { day, input: input.clone(), params: config.for_input(day.number, &input), part }))
+        .collect();
+    let finished = run_parallel(tasks, jobs, timeout);
+
+    let mut answers = Answers::load(ANSWERS_FILE)?;
+    let mut recorded = false;
+    let (mut passed, mut failed, mut new, mut errors, mut skipped) = (0, 0, 0, 0, 0);
+    let width = users.iter().map(String::len).max().unwrap_or(0);
+    for day in days {
+        for part in [1, 2] {
+            println!("Day {:02} part {}:", day.number, part);
+            let mut solved = vec![];
+            for (_, user, input) in resolved.iter().filter(|(d, _, _)| d.number == day.number) {
+                let Some(input) = input else {
+                    println!("  {:width$}  no input", user);
+                    skipped += 1;
+                    continue;
+                };
+                let f = finished.iter().find(|f| f.task.input == *input && f.task.part == part).expect("every input was run");
+                let result = match &f.outcome {
+                    executor::Outcome::Solved(answer) => {
+                        let actual = answer.to_string();
+                        solved.push(actual.clone());
+                        match answers.check(day.number, input, part, actual.clone()) {
+                            Outcome::Pass => {
+                                passed += 1;
+                                format!("{}  pass", single_line(&actual))
+                            }
+                            Outcome::Fail { expected, .. } => {
+                                failed += 1;
+                                format!("{}  FAIL, expected {}", single_line(&actual), single_line(&expected))
+                            }
+                            _ => {
+                                new += 1;
+                                if args.flag("--record") {
+                                    answers.set(day.number, input, part, &actual);
+                                    recorded = true;
+                                }
+                                format!("{}  new", single_line(&actual))
+                            }
+                        }
+                    }
+                    executor::Outcome::Failed(e) => {
+                        errors += 1;
+                        format!("ERROR, {}", e)
+                    }
+                    executor::Outcome::NotSolved => {
+                        errors += 1;
+                        format!("ERROR, {}", NotSolved)
+                    }
+                    executor::Outcome::TimedOut => {
+                        errors += 1;
+                        format!("ERROR, timed out after {:?}", f.elapsed)
+                    }
+                };
+                println!("  {:width$}  {}", user, result);
+            }
+            // Different inputs should almost never share an answer, so this usually means the
+            // solver isn't reading its input properly.
+            if solved.len() > 1 && solved.iter().all(|a| *a == solved[0]) {
+                println!("  every user got the same answer");
+            }
+        }
+    }
+
+    if recorded {
+        answers.save(ANSWERS_FILE)?;
+        println!("Recorded new answers in {}", ANSWERS_FILE);
+    }
+    println!("{} users: {} passed, {} failed, {} new, {} errors, {} without input", users.len(), passed, failed, new, errors, skipped);
+    Ok(failed == 0 && errors == 0)
+}
+
+fn params_command(args: &Args) -> Result<bool> {
+    let [target] = args.positional.as_slice() else {
+        Err(anyhow!("Expected a day\n\n{}", USAGE))?
+    };
+    let day = targets(target)?;
+    let [day] = day.as_slice() else {
+        Err(anyhow!("Expected a single day"))?
+    };
+    let input = match args.option("--input") {
+        Some(input) => input.to_string(),
+        None => args.inputs().resolve(day)?,
+    };
+    let solution = day.load_with(&input, &args.config()?.for_input(day.number, &input))?;
+    match solution.params() {
+        Some(params) => println!("{}", serde_json::to_string_pretty(&params)?),
+        None => println!("Day {:02} has no parameters", day.number),
+    }
+    Ok(true)
+}
+
+fn status_command(args: &Args) -> Result<bool> {
+    let days = match args.positional.as_slice() {
+        [] => days().iter().collect(),
+        [target] => targets(target)?,
+        _ => Err(anyhow!("Expected at most one day\n\n{}", USAGE))?,
+    };
+    let inputs = args.inputs();
+    let config = args.config()?;
+    let answers = Answers::load(ANSWERS_FILE)?;
+    let runtimes = Runtimes::load(args.option("--bench").unwrap_or(BENCH_FILE))?;
+    let (jobs, timeout) = args.parallelism()?;
+    let status = status(days.into_iter().map(|day| (day, inputs.resolve(day))).collect(), &answers, &config, &runtimes, jobs, timeout);
+    if args.flag("--json") {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+    } else {
+        print!("{}", status);
+    }
+    Ok(true)
+}
+
+fn visualize_command(args: &Args) -> Result<bool> {
+    let (target, part) = match args.positional.as_slice() {
+        [target] => (target, 1),
+        [target, part] => (target, parse_part(part)?),
+        _ => Err(anyhow!("Expected a day and an optional part\n\n{}", USAGE))?,
+    };
+    let day = targets(target)?;
+    let [day] = day.as_slice() else {
+        Err(anyhow!("Expected a single day"))?
+    };
+    let input = match args.option("--input") {
+        Some(input) => input.to_string(),
+        None => args.inputs().resolve(day)?,
+    };
+    let solution = day.load_with(&input, &args.config()?.for_input(day.number, &input))?;
+    let simulation = solution.visualizer().ok_or_else(|| anyhow!("Day {} has nothing to visualize", day.number))?;
+
+    let target = match args.option("--ppm") {
+        Some(dir) => Target::Ppm { dir: dir.into(), scale: args.parsed_option("--scale", 4)? },
+        None if args.option("--delay").is_some() => Target::Text { delay: Some(Duration::from_millis(args.parsed_option("--delay", 0)?)) },
+        None => Target::Text { delay: None },
+    };
+    let region = args.option("--region").map(parse_region).transpose()?;
+    let options = Options { every: args.parsed_option("--every", 1)?, region, target };
+    let mut stdout = std::io::stdout().lock();
+    let mut frames = Frames::new(options, &mut stdout)?;
+    simulation.visualize(part, &mut frames)?;
+    if let Some(dir) = args.option("--ppm") {
+        println!("Wrote {} of {} frames to {}", frames.drawn(), frames.seen(), dir);
+    }
+    Ok(true)
+}
+
+/// The `--verbosity` given, or `default`.
+fn verbosity(args: &Args, default: Level) -> Result<Level> {
+    Ok(args.option("--verbosity").map(str::parse).transpose()?.unwrap_or(default))
+}
+
+fn trace_command(args: &Args) -> Result<bool> {
+    let (target, parts) = match args.positional.as_slice() {
+        [target] => (target, vec![1, 2]),
+        [target, part] => (target, vec![parse_part(part)?]),
+        _ => Err(anyhow!("Expected a day and an optional part\n\n{}", USAGE))?,
+    };
+    let day = targets(target)?;
+    let [day] = day.as_slice() else {
+        Err(anyhow!("Expected a single day"))?
+    };
+    let input = match args.option("--input") {
+        Some(input) => input.to_string(),
+        None => args.inputs().resolve(day)?,
+    };
+    let solution = day.load_with(&input, &args.config()?.for_input(day.number, &input))?;
+    let out: Box<dyn Write> = match args.option("--out") {
+        Some(path) => Box::new(std::fs::File::create(path).with_context(|| format!("creating {}", path))?),
+        None => Box::new(std::io::stdout()),
+    };
+
+    // Events are written as they come, keeping the first write error to report afterwards.
+    let sink = Rc::new(RefCell::new((BufWriter::new(out), None)));
+    let tracing = Rc::clone(&sink);
+    let write = move |event: &trace::Event| {
+        let (out, error) = &mut *tracing.borrow_mut();
+        if error.is_none() {
+            *error = serde_json::to_writer(&mut *out, event).map_err(anyhow::Error::from).and_then(|_| Ok(writeln!(out)?)).err();
+        }
+    };
+    let result = trace::trace(verbosity(args, Level::Step)?, write, || -> Result<()> {
+        for part in parts {
+            let answer = solution.part(part).with_context(|| format!("Day {:02} part {}", day.number, part))?;
+            trace::event(Level::Summary, "answer", || serde_json::json!({ "day": day.number, "part": part, "answer": answer }));
+        }
+        Ok(())
+    });
+    result?;
+    let (out, error) = &mut *sink.borrow_mut();
+    if let Some(e) = error.take() {
+        Err(e.context("writing the trace"))?;
+    }
+    out.flush().context("writing the trace")?;
+    Ok(true)
+}
+
+fn replay_command(args: &Args) -> Result<bool> {
+    let verbosity = verbosity(args, Level::Detail)?;
+    let mut stdout = std::io::stdout().lock();
+    let mut print = |event: &trace::Event| Ok(writeln!(stdout, "{}", event)?);
+    match args.positional.as_slice() {
+        [] => trace::replay(std::io::stdin().lock(), verbosity, &mut print)?,
+        [log] if log == "-" => trace::replay(std::io::stdin().lock(), verbosity, &mut print)?,
+        [log] => trace::replay(open(log)?, verbosity, &mut print)?,
+        _ => Err(anyhow!("Expected at most one log\n\n{}", USAGE))?,
+    }
+    Ok(true)
+}
+
+fn watch_command(args: &Args) -> Result<bool> {
+    let [target] = args.positional.as_slice() else {
+        Err(anyhow!("Expected a day\n\n{}", USAGE))?
+    };
+    let days = targets(target)?;
+    if days.len() > 1 && args.option("--input").is_some() {
+        Err(anyhow!("--input can't be used with 'all'"))?;
+    }
+    let inputs = args.inputs();
+    let config = args.config()?;
+    let mut watch = Watch::new();
+    for day in days {
+        let input = match args.option("--input") {
+            Some(input) => input.to_string(),
+            None => match inputs.resolve(day) {
+                Ok(input) => input,
+                // Watching everything shouldn't stop at a day without an input.
+                Err(e) if target == "all" => {
+                    eprintln!("Error: Day {:02}: {:#}", day.number, e);
+                    continue;
+                }
+                Err(e) => Err(e)?,
+            },
+        };
+        watch.add(day, &input, config.for_input(day.number, &input))?;
+    }
+    let paths = watch.paths();
+    if paths.is_empty() {
+        Err(anyhow!("No inputs to watch"))?;
+    }
+
+    print_reruns(&watch.run(None));
+    println!("Watching {} input{} for changes", paths.len(), if paths.len() == 1 { "" } else { "s" });
+    let poll = args.option("--poll").map(|_| args.parsed_option("--poll", 0).map(Duration::from_millis)).transpose()?;
+    watch_files(&paths, poll, |changed| {
+        print_reruns(&watch.run(Some(changed)));
+        Ok(true)
+    })?;
+    Ok(true)
+}
+
+fn print_reruns(reruns: &[Rerun]) {
+    for rerun in reruns {
+        println!("Day {:02} ({}):", rerun.day, rerun.input);
+        for change in &rerun.changes {
+            println!("  {}", change);
+        }
+    }
+}
+
+/// Parses a region given by two opposite corners, `x1,y1,x2,y2`.
+fn parse_region(region: &str) -> Result<Bounds<usize>> {
+    let numbers: Vec<usize> = region.split(',').map(|n| n.trim().parse()).collect::<Result<_, _>>().map_err(|_| anyhow!("Invalid region '{}'", region))?;
+    let [x1, y1, x2, y2] = numbers[..] else {
+        Err(anyhow!("Invalid region '{}', expected x1,y1,x2,y2", region))?
+    };
+    Ok(Bounds::of([Point::new(x1, y1), Point::new(x2, y2)]).unwrap())
+}
+
+fn confirm(question: &str) -> Result<bool> {
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush()?;
+    let mut reply = String::new();
+    std::io::stdin().read_line(&mut reply)?;
+    Ok(matches!(reply.trim(), "y" | "Y" | "yes"))
+}
+
+fn differential_command(args: &Args) -> Result<bool> {
+    let (target, parts) = match args.positional.as_slice() {
+        [target] => (target, vec![1, 2]),
+        [target, part] => (target, vec![parse_part(part)?]),
+        _ => Err(anyhow!("Expected a day and an optional part\n\n{}", USAGE))?,
+    };
+    let days = targets(target)?;
+    if days.len() > 1 && args.option("--input").is_some() {
+        Err(anyhow!("--input can't be used with 'all'"))?;
+    }
+    let seeds = args.parsed_option("--seeds", 30)?;
+    let inputs = args.inputs();
+    let config = args.config()?;
+
+    let mut ok = true;
+    for day in days {
+        let input = match args.option("--input") {
+            Some(input) => Ok(input.to_string()),
+            None => inputs.resolve(day),
+        };
+        for &part in &parts {
+            let implementations = differential::implementations(day, part);
+            if implementations < 2 {
+                if target != "all" {
+                    println!("Day {:02} part {} has a single implementation", day.number, part);
+                }
+                continue;
+            }
+            let mut checked = vec![];
+            let mut outcome: Result<(), Box<Disagreement>> = Ok(());
+            if let Ok(file) = &input {
+                let text = std::fs::read_to_string(file).with_context(|| format!("reading {}", file))?;
+                outcome = differential::check(day, part, file, &text, &config.for_input(day.number, file)).map(|_| checked.push(file.clone()));
+            }
+            if outcome.is_ok() {
+                outcome = differential::check_generated(day, part, seeds, |seed, size| generate(day.number, seed, size), &Changes::new())
+                    .map(|_| checked.push(format!("{} generated inputs", seeds)));
+            }
+            match outcome {
+                Ok(()) => println!("Day {:02} part {}: {} implementations agree on {}", day.number, part, implementations, checked.join(" and ")),
+                Err(disagreement) => {
+                    print!("{}", disagreement);
+                    ok = false;
+                }
+            }
+        }
+    }
+    Ok(ok)
+}
+
+fn repl_command(args: &Args) -> Result<bool> {
+    let [target] = args.positional.as_slice() else {
+        Err(anyhow!("Expected a day\n\n{}", USAGE))?
+    };
+    let day = targets(target)?;
+    let [day] = day.as_slice() else {
+        Err(anyhow!("Expected a single day"))?
+    };
+    let input = match args.option("--input") {
+        Some(input) => input.to_string(),
+        None => args.inputs().resolve(day)?,
+    };
+    let solution = day.load_with(&input, &args.config()?.for_input(day.number, &input))?;
+    let stdin = std::io::stdin();
+    let prompt = if stdin.is_terminal() { format!("day{:02}> ", day.number) } else { String::new() };
+    if solution.explorer().is_none() && stdin.is_terminal() {
+        println!("Day {} has no commands of its own, only 'part' (try 'help')", day.number);
+    }
+    repl(&*solution, stdin.lock(), &mut std::io::stdout().lock(), &prompt)?;
+    Ok(true)
+}
+
+fn serve_command(args: &Args) -> Result<bool> {
+    if !args.positional.is_empty() {
+        Err(anyhow!("serve takes no arguments\n\n{}", USAGE))?;
+    }
+    let mut changes = Changes::new();
+    for change in args.all_options("--param") {
+        let (name, value) = parse_change(change)?;
+        changes.insert(name, value);
+    }
+    let server = Server::bind((args.option("--host").unwrap_or("127.0.0.1"), args.parsed_option("--port", 8022)?), changes)?
+        .with_timeout(args.duration_option("--timeout", SOLVE_TIMEOUT)?);
+    let addr = server.local_addr()?;
+    if !addr.ip().is_loopback() {
+        eprintln!("Warning: {} can be reached from other machines, and the server has no authentication", addr.ip());
+    }
+    println!("Listening on http://{}", addr);
+    server.serve()?;
+    Ok(true)
+}
+
+fn new_day_command(args: &Args) -> Result<bool> {
+    let [number] = args.positional.as_slice() else {
+        Err(anyhow!("Expected a day\n\n{}", USAGE))?
+    };
+    let number = number.parse::<u8>().with_context(|| format!("Invalid day '{}'", number))?;
+    for file in new_day(Path::new("."), number)? {
+        println!("Wrote {}", file.display());
+    }
+    println!("Next: paste the example into day{:02}ex.txt and its answer into part_1_test, your input into day{:02}.txt,", number, number);
+    println!("then add a generator for it in src/generate.rs.");
+    Ok(true)
+}
+
+fn generate_command(args: &Args) -> Result<bool> {
+    let [target] = args.positional.as_slice() else {
+        Err(anyhow!("Expected a day\n\n{}", USAGE))?
+    };
+    let day = targets(target)?;
+    let [day] = day.as_slice() else {
+        Err(anyhow!("Expected a single day"))?
+    };
+    let input = generate(day.number, args.parsed_option("--seed", 0)?, args.parsed_option("--size", 100)?)
+        .ok_or_else(|| anyhow!("Day {} has no input generator", day.number))?;
+    print!("{}", input);
+    Ok(true)
+}
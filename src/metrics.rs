@@ -0,0 +1,63 @@
+//! Counting the work a solver does, such as nodes expanded or items inspected, to explain its
+//! timings without reaching for a profiler.
+//!
+//! Solvers call [`count`] as they go; nothing is kept unless the caller is inside [`record`], so
+//! counting costs a thread-local lookup otherwise. Counts are kept per thread, which matches how
+//! parts are run, and hot loops should total their counts locally and report them once.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+/// Counts by name, such as `nodes_expanded`.
+pub type Counters = BTreeMap<&'static str, u64>;
+
+thread_local! {
+    static RECORDING: RefCell<Option<Counters>> = const { RefCell::new(None) };
+}
+
+/// Adds `n` to a counter, if this thread is recording.
+pub fn count(name: &'static str, n: u64) {
+    RECORDING.with(|recording| {
+        if let Some(counters) = recording.borrow_mut().as_mut() {
+            *counters.entry(name).or_default() += n;
+        }
+    });
+}
+
+/// Runs `f`, returning what it counted along with its result. Recording can be nested, with the
+/// outer recording also getting the inner one's counts.
+pub fn record<T>(f: impl FnOnce() -> T) -> (T, Counters) {
+    let outer = RECORDING.with(|recording| recording.replace(Some(Counters::new())));
+    let result = f();
+    let counters = RECORDING.with(|recording| recording.replace(outer)).unwrap_or_default();
+    for (name, n) in &counters {
+        count(name, *n);
+    }
+    (result, counters)
+}
+
+/// Formats counters as `name n` pairs, e.g. "rounds 20, inspections 101".
+pub fn format_counters(counters: &Counters) -> String {
+    counters.iter().map(|(name, n)| format!("{} {}", name, n)).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records() {
+        count("ignored", 1);
+        let ((), outer) = record(|| {
+            count("outer", 1);
+            let ((), inner) = record(|| {
+                count("inner", 2);
+                count("inner", 3);
+            });
+            assert_eq!(Counters::from([("inner", 5)]), inner);
+        });
+        assert_eq!(Counters::from([("inner", 5), ("outer", 1)]), outer);
+        assert_eq!("inner 5, outer 1", format_counters(&outer));
+        assert!(record(|| ()).1.is_empty());
+    }
+}
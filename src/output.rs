@@ -0,0 +1,93 @@
+use std::io::BufRead;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::params::Changes;
+use crate::{Answer, Day};
+
+/// What running one part produced: its answer, or why it failed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PartOutput {
+    pub part: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer: Option<Answer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Everything running a day on one input produced, in the shape written as JSON output. `error` is
+/// set, with no parts, if the input couldn't be found or parsed, or its parameters are invalid.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DayOutput {
+    pub day: u8,
+    pub input: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub parts: Vec<PartOutput>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<Value>,
+}
+
+impl DayOutput {
+    /// Whether parsing and every part that was run succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none() && self.parts.iter().all(|p| p.error.is_none())
+    }
+}
+
+/// Parses `input` (named `input_name` in the output), changes its parameters and runs the given
+/// parts, including the parsed model when asked to and the day has one.
+pub fn run_day(day: &Day, input: &mut dyn BufRead, input_name: &str, changes: &Changes, parts: &[u8], with_model: bool) -> DayOutput {
+    let mut output = DayOutput { day: day.number, input: input_name.to_string(), error: None, parts: vec![], model: None };
+    let mut solution = match (day.parse)(input) {
+        Ok(solution) => solution,
+        Err(e) => {
+            output.error = Some(format!("parsing {}: {:#}", input_name, e));
+            return output;
+        }
+    };
+    if let Err(e) = solution.configure(changes) {
+        output.error = Some(format!("{:#}", e));
+        return output;
+    }
+
+    for &part in parts {
+        output.parts.push(match solution.part(part) {
+            Ok(answer) => PartOutput { part, answer: Some(answer), error: None },
+            Err(e) => PartOutput { part, answer: None, error: Some(format!("{:#}", e)) },
+        });
+    }
+    if with_model {
+        output.model = solution.model();
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::open;
+
+    use super::*;
+
+    #[test]
+    fn json() -> anyhow::Result<()> {
+        let output = run_day(crate::day(7).unwrap(), &mut open("day07ex.txt")?, "day07ex.txt", &Changes::new(), &[1, 2], true);
+        assert!(output.is_ok());
+        let json = serde_json::to_value(&output)?;
+        assert_eq!(95437, json["parts"][0]["answer"]);
+        assert_eq!(24933642, json["parts"][1]["answer"]);
+        assert_eq!(584, json["model"]["dir"]["items"]["a"]["dir"]["items"]["e"]["dir"]["items"]["i"]["file"]);
+        assert!(json.get("error").is_none());
+
+        let output = run_day(crate::day(10).unwrap(), &mut open("day10ex.txt")?, "day10ex.txt", &Changes::new(), &[2], false);
+        assert!(serde_json::to_value(&output)?["parts"][0]["answer"].as_str().unwrap().starts_with("##..##"));
+        assert!(output.model.is_none());
+
+        let output = run_day(crate::day(9).unwrap(), &mut "X 1\n".as_bytes(), "stdin", &Changes::new(), &[1], false);
+        assert!(!output.is_ok());
+        assert!(output.parts.is_empty());
+        assert!(output.error.unwrap().starts_with("parsing stdin: "));
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+//! Puzzle parameters: the numbers a puzzle states in its text rather than its input, such as how
+//! many rounds to play. Each day that has any keeps them in a `Params` struct whose defaults are
+//! the real puzzle's, and they can be changed by name for examples and variant puzzles.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// The checked-in parameters for inputs that don't use the defaults, relative to the crate root.
+pub const PARAMS_FILE: &str = "params.json";
+
+/// Parameter changes by name, as JSON values.
+pub type Changes = Map<String, Value>;
+
+/// Returns a copy of `params` with some of them changed, failing on names it doesn't have or values
+/// of the wrong type.
+pub fn apply<P: Serialize + DeserializeOwned>(params: &P, changes: &Changes) -> Result<P> {
+    let Value::Object(mut fields) = serde_json::to_value(params)? else {
+        Err(anyhow!("Parameters must be a struct"))?
+    };
+    for (name, value) in changes {
+        if !fields.contains_key(name) {
+            let names: Vec<&str> = fields.keys().map(String::as_str).collect();
+            Err(anyhow!("Unknown parameter '{}', expected one of {}", name, names.join(", ")))?;
+        }
+        fields.insert(name.clone(), value.clone());
+    }
+    serde_json::from_value(Value::Object(fields)).with_context(|| format!("Invalid parameters {}", Value::Object(changes.clone())))
+}
+
+/// Parses a `name=value` change. The value is read as JSON, or taken as a string if it isn't any.
+pub fn parse_change(change: &str) -> Result<(String, Value)> {
+    let (name, value) = change.split_once('=').ok_or_else(|| anyhow!("Expected name=value, found '{}'", change))?;
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+    Ok((name.trim().to_string(), value))
+}
+
+/// Which parameters to change for each input: those recorded for particular inputs in a params
+/// file, then any given for every input.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Config {
+    inputs: BTreeMap<u8, BTreeMap<String, Changes>>,
+    everywhere: Changes,
+}
+
+impl Config {
+    /// Loads the changes recorded in a params file, keyed by day and then input name like the
+    /// answers store. A missing file records none.
+    pub fn load(file: &str) -> Result<Self> {
+        if !Path::new(file).exists() {
+            return Ok(Config::default());
+        }
+        let json = std::fs::read_to_string(file).with_context(|| format!("reading {}", file))?;
+        Ok(Config { inputs: serde_json::from_str(&json).with_context(|| format!("parsing {}", file))?, everywhere: Changes::new() })
+    }
+
+    /// Changes a parameter for every input, overriding any recorded change.
+    pub fn set(&mut self, name: String, value: Value) {
+        self.everywhere.insert(name, value);
+    }
+
+    /// The changes to make for a day's input.
+    pub fn for_input(&self, day: u8, input: &str) -> Changes {
+        let mut changes = self.inputs.get(&day).and_then(|inputs| inputs.get(input)).cloned().unwrap_or_default();
+        changes.extend(self.everywhere.clone());
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Params {
+        rounds: usize,
+        name: String,
+    }
+
+    #[test]
+    fn changes() -> Result<()> {
+        let params = Params { rounds: 20, name: "monkeys".to_string() };
+        let mut changes = Changes::new();
+        for change in ["rounds=10000", "name=elves"] {
+            let (name, value) = parse_change(change)?;
+            changes.insert(name, value);
+        }
+        assert_eq!(Params { rounds: 10000, name: "elves".to_string() }, apply(&params, &changes)?);
+
+        let (name, value) = parse_change("round=1")?;
+        let e = apply(&params, &Changes::from_iter([(name, value)])).unwrap_err();
+        assert_eq!("Unknown parameter 'round', expected one of name, rounds", e.to_string());
+        let (name, value) = parse_change("rounds=lots")?;
+        assert!(apply(&params, &Changes::from_iter([(name, value)])).is_err());
+        assert!(parse_change("rounds").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn config() -> Result<()> {
+        let mut config = Config::load(PARAMS_FILE)?;
+        assert_eq!(Some(&Value::from(10)), config.for_input(15, "day15ex.txt").get("row"));
+        assert!(config.for_input(15, "day15.txt").is_empty());
+        config.set("row".to_string(), Value::from(11));
+        assert_eq!(Some(&Value::from(11)), config.for_input(15, "day15ex.txt").get("row"));
+        assert_eq!(Some(&Value::from(20)), config.for_input(15, "day15ex.txt").get("bound"));
+        Ok(())
+    }
+}
@@ -0,0 +1,198 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A problem found while parsing puzzle input, with where it was found and what was expected there.
+///
+/// Lines and columns are 1-based; 0 means the position is not known (e.g. a line parser that has not
+/// yet been told which line it was given).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+    pub found: String,
+}
+
+impl ParseError {
+    pub fn new(day: u8, expected: impl Into<String>, found: impl Into<String>) -> Self {
+        ParseError {
+            day,
+            line: 0,
+            column: 0,
+            expected: expected.into(),
+            found: found.into(),
+        }
+    }
+
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line = line;
+        self
+    }
+
+    pub fn at_column(mut self, column: usize) -> Self {
+        self.column = column;
+        self
+    }
+
+    /// Moves an error from a parser that was given a block of lines so that it is relative to the
+    /// whole input, where the block started after `lines` earlier lines.
+    pub fn after_lines(mut self, lines: usize) -> Self {
+        self.line += lines;
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day {:02}", self.day)?;
+        if self.line > 0 {
+            write!(f, ", line {}", self.line)?;
+        }
+        if self.column > 0 {
+            write!(f, ", column {}", self.column)?;
+        }
+        write!(f, ": expected {}, found {}", self.expected, self.found)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Walks along one line of input, keeping track of the column so errors can say where they happened.
+pub struct Scanner<'a> {
+    day: u8,
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(day: u8, input: &'a str) -> Self {
+        Scanner { day, input, pos: 0 }
+    }
+
+    /// The 1-based column of the next unread character.
+    pub fn column(&self) -> usize {
+        self.input[..self.pos].chars().count() + 1
+    }
+
+    pub fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.input.len()
+    }
+
+    /// An error at the current column, describing the next token as what was found.
+    pub fn error(&self, expected: impl Into<String>) -> ParseError {
+        ParseError::new(self.day, expected, self.next_token()).at_column(self.column())
+    }
+
+    fn next_token(&self) -> String {
+        match self.rest().split_whitespace().next() {
+            Some(token) if self.rest().starts_with(token) => format!("'{}'", token),
+            _ => match self.rest().chars().next() {
+                Some(c) => format!("'{}'", c),
+                None => "end of line".to_string(),
+            },
+        }
+    }
+
+    /// Consumes `literal` if the input continues with it.
+    pub fn accept(&mut self, literal: &str) -> bool {
+        if self.rest().starts_with(literal) {
+            self.pos += literal.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn literal(&mut self, literal: &str) -> Result<(), ParseError> {
+        if self.accept(literal) {
+            Ok(())
+        } else {
+            Err(self.error(format!("'{}'", literal)))
+        }
+    }
+
+    pub fn char(&mut self, what: &str) -> Result<char, ParseError> {
+        let c = self.rest().chars().next().ok_or_else(|| self.error(what))?;
+        self.pos += c.len_utf8();
+        Ok(c)
+    }
+
+    /// Consumes the next character if it is one of `options`.
+    pub fn one_of(&mut self, what: &str, options: &[char]) -> Result<char, ParseError> {
+        match self.rest().chars().next() {
+            Some(c) if options.contains(&c) => {
+                self.pos += c.len_utf8();
+                Ok(c)
+            }
+            _ => Err(self.error(what)),
+        }
+    }
+
+    /// Consumes an optionally negative integer.
+    pub fn number<T: FromStr>(&mut self, what: &str) -> Result<T, ParseError> {
+        let rest = self.rest();
+        let sign = usize::from(rest.starts_with('-'));
+        let len = sign + rest[sign..].bytes().take_while(|b| b.is_ascii_digit()).count();
+        let number = rest[..len].parse::<T>().map_err(|_| self.error(what))?;
+        self.pos += len;
+        Ok(number)
+    }
+
+    /// Consumes everything up to (but not including) `delimiter`, or to the end of the line.
+    pub fn until(&mut self, delimiter: &str) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(delimiter).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    /// Consumes the rest of the line, which must not be empty.
+    pub fn word(&mut self, what: &str) -> Result<&'a str, ParseError> {
+        if self.is_empty() {
+            Err(self.error(what))?;
+        }
+        let rest = self.rest();
+        self.pos = self.input.len();
+        Ok(rest)
+    }
+
+    pub fn end(&self) -> Result<(), ParseError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error("end of line"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scanner() -> Result<(), ParseError> {
+        let mut s = Scanner::new(15, "Sensor at x=-2, y=18");
+        s.literal("Sensor at x=")?;
+        assert_eq!(-2, s.number::<i64>("x")?);
+        s.literal(", y=")?;
+        assert_eq!(18, s.number::<i64>("y")?);
+        s.end()
+    }
+
+    #[test]
+    fn errors() {
+        let mut s = Scanner::new(9, "R x");
+        assert_eq!(Ok('R'), s.one_of("direction", &['U', 'D', 'L', 'R']));
+        s.literal(" ").unwrap();
+        let e = s.number::<i64>("step count").unwrap_err().at_line(4);
+        assert_eq!(ParseError { day: 9, line: 4, column: 3, expected: "step count".to_string(), found: "'x'".to_string() }, e);
+        assert_eq!("day 09, line 4, column 3: expected step count, found 'x'", e.to_string());
+        assert_eq!("day 01, column 1: expected end of line, found 'x'", Scanner::new(1, "x").end().unwrap_err().to_string());
+    }
+}
@@ -0,0 +1,194 @@
+//! A small property-testing harness: checks a property against many generated values and, when one
+//! fails, shrinks it to a minimal failing value before reporting it. The shrinking is also used by
+//! [`differential`](crate::differential) to report the smallest input implementations disagree on.
+
+use std::fmt;
+
+use crate::generate::Rng;
+
+/// How many shrinking steps to take before giving up and reporting the smallest failure so far.
+const MAX_SHRINKS: usize = 1000;
+
+/// What a property made of one value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail(String),
+    /// The value isn't one the property applies to, e.g. shrinking made an input that won't parse.
+    Discard,
+}
+
+impl Verdict {
+    /// Passes if `holds`, otherwise fails with the message.
+    pub fn check(holds: bool, message: impl FnOnce() -> String) -> Self {
+        if holds {
+            Verdict::Pass
+        } else {
+            Verdict::Fail(message())
+        }
+    }
+}
+
+/// A value that can propose smaller versions of itself to try when it makes a property fail.
+pub trait Shrink: Clone + fmt::Debug {
+    /// Smaller candidates, most aggressive first.
+    fn shrink(&self) -> Vec<Self>;
+}
+
+/// Puzzle input text, shrunk by removing lines.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Lines(pub String);
+
+impl fmt::Debug for Lines {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\n{}", self.0)
+    }
+}
+
+impl Shrink for Lines {
+    fn shrink(&self) -> Vec<Self> {
+        let lines: Vec<&str> = self.0.lines().collect();
+        remove_chunks(&lines).into_iter().map(|lines| Lines(lines.iter().map(|l| format!("{}\n", l)).collect())).collect()
+    }
+}
+
+/// Puzzle input text, shrunk by removing lines and then characters within lines, for inputs that
+/// are a single long line.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Text(pub String);
+
+impl fmt::Debug for Text {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\n{}", self.0)
+    }
+}
+
+impl Shrink for Text {
+    fn shrink(&self) -> Vec<Self> {
+        let mut candidates: Vec<Self> = Lines(self.0.clone()).shrink().into_iter().map(|Lines(text)| Text(text)).collect();
+        let lines: Vec<&str> = self.0.lines().collect();
+        for (i, line) in lines.iter().enumerate() {
+            let chars: Vec<char> = line.chars().collect();
+            for shorter in remove_chunks(&chars) {
+                let mut candidate = lines.clone();
+                let shorter: String = shorter.into_iter().collect();
+                candidate[i] = &shorter;
+                candidates.push(Text(candidate.iter().map(|l| format!("{}\n", l)).collect()));
+            }
+        }
+        candidates
+    }
+}
+
+/// Copies of `items` with chunks removed, from halves down to single items.
+fn remove_chunks<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
+    let mut candidates = vec![];
+    let mut chunk = items.len() / 2;
+    while chunk > 0 {
+        for start in (0..items.len()).step_by(chunk) {
+            let mut candidate = items[..start].to_vec();
+            candidate.extend_from_slice(&items[(start + chunk).min(items.len())..]);
+            candidates.push(candidate);
+        }
+        chunk /= 2;
+    }
+    if items.len() == 1 {
+        candidates.push(vec![]);
+    }
+    candidates
+}
+
+impl<T: Shrink> Shrink for Vec<T> {
+    fn shrink(&self) -> Vec<Self> {
+        let mut candidates = remove_chunks(self);
+        for (i, item) in self.iter().enumerate() {
+            for smaller in item.shrink() {
+                let mut candidate = self.clone();
+                candidate[i] = smaller;
+                candidates.push(candidate);
+            }
+        }
+        candidates
+    }
+}
+
+impl Shrink for u64 {
+    fn shrink(&self) -> Vec<Self> {
+        let mut candidates = vec![0, self / 2, self.saturating_sub(1)];
+        candidates.retain(|c| c < self);
+        candidates.dedup();
+        candidates
+    }
+}
+
+impl<A: Shrink, B: Shrink, C: Shrink> Shrink for (A, B, C) {
+    fn shrink(&self) -> Vec<Self> {
+        let (a, b, c) = self;
+        let mut candidates: Vec<Self> = a.shrink().into_iter().map(|a| (a, b.clone(), c.clone())).collect();
+        candidates.extend(b.shrink().into_iter().map(|b| (a.clone(), b, c.clone())));
+        candidates.extend(c.shrink().into_iter().map(|c| (a.clone(), b.clone(), c)));
+        candidates
+    }
+}
+
+/// Checks `property` against `cases` values made by `generate`, with sizes cycling up to 30 so small
+/// cases come early. Panics with the failure shrunk as far as it will go.
+pub fn check<T: Shrink>(cases: u64, generate: impl Fn(&mut Rng, usize) -> T, property: impl Fn(&T) -> Verdict) {
+    for seed in 0..cases {
+        let size = 1 + seed as usize % 30;
+        let value = generate(&mut Rng::new(seed), size);
+        if let Verdict::Fail(message) = property(&value) {
+            let (value, message, shrinks) = shrink(value, message, &property);
+            panic!("property failed for seed {} (size {}), shrunk {} times to: {:?}\n{}", seed, size, shrinks, value, message);
+        }
+    }
+}
+
+/// Repeatedly replaces the failing value with the first smaller candidate that still fails,
+/// returning the smallest along with its failure and how many times it shrank.
+pub fn shrink<T: Shrink>(mut value: T, mut message: String, property: &impl Fn(&T) -> Verdict) -> (T, String, usize) {
+    let mut shrinks = 0;
+    'shrinking: while shrinks < MAX_SHRINKS {
+        for candidate in value.shrink() {
+            if let Verdict::Fail(m) = property(&candidate) {
+                (value, message) = (candidate, m);
+                shrinks += 1;
+                continue 'shrinking;
+            }
+        }
+        break;
+    }
+    (value, message, shrinks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shrinks_to_minimal() {
+        // "No number is 50 or more" is false. Lines only shrink down to one offending line, but
+        // numbers also shrink, down to exactly 50.
+        let property = |lines: &Lines| {
+            let numbers: Vec<u64> = lines.0.lines().map(|l| l.parse().unwrap()).collect();
+            Verdict::check(numbers.iter().all(|&n| n < 50), || format!("{:?}", numbers))
+        };
+        let value = Lines("3\n70\n12\n99\n".to_string());
+        let (value, _, _) = shrink(value, String::new(), &property);
+        assert_eq!(1, value.0.lines().count());
+        assert!(value.0.trim().parse::<u64>().unwrap() >= 50);
+
+        let (value, message, _) = shrink(vec![3u64, 70, 12, 99], String::new(), &|v: &Vec<u64>| Verdict::check(v.iter().all(|&n| n < 50), || format!("{:?}", v)));
+        assert_eq!(vec![50], value);
+        assert_eq!("[50]", message);
+
+        let (value, _, _) = shrink(Text("abc\nxyzzy\n".to_string()), String::new(), &|text: &Text| Verdict::check(!text.0.contains("zz"), String::new));
+        assert_eq!("zz\n", value.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "shrunk")]
+    fn reports_failures() {
+        check(10, |rng, _| rng.range(0..=1000), |&n| Verdict::check(n < 10, || n.to_string()));
+    }
+}
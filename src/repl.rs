@@ -0,0 +1,134 @@
+//! An interactive prompt for poking at a day's parsed input: listing directories on day 7, moving
+//! crates a step at a time on day 5, playing monkey rounds on day 11 and so on.
+//!
+//! Each day with commands offers a session ([`Solution::explorer`]) holding whatever state its
+//! commands change, so stepping a simulation doesn't touch the solution itself. Every day also
+//! gets the built in `help`, `part <1|2>` and `quit` commands.
+
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+
+use crate::Solution;
+
+/// A command a day understands, listed by `help`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Command {
+    pub name: &'static str,
+    /// The arguments it takes, such as `<x> <y>`, with optional ones in brackets.
+    pub args: &'static str,
+    pub help: &'static str,
+}
+
+impl Command {
+    pub const fn new(name: &'static str, args: &'static str, help: &'static str) -> Self {
+        Command { name, args, help }
+    }
+}
+
+/// A session over a day's parsed input that commands can look at and change.
+pub trait Explore {
+    /// The commands the session understands.
+    fn commands(&self) -> &'static [Command];
+
+    /// Runs one of the commands with its arguments, returning the text to show.
+    fn run(&mut self, command: &str, args: &[&str]) -> Result<String>;
+}
+
+/// The commands every day has.
+const BUILT_IN: [Command; 3] = [
+    Command::new("help", "", "lists the commands"),
+    Command::new("part", "<1|2>", "solves a part of the input"),
+    Command::new("quit", "", "leaves (so does end of input)"),
+];
+
+/// Parses the argument at `index`, naming it `what` if it's missing or malformed.
+pub fn arg<T: FromStr>(args: &[&str], index: usize, what: &str) -> Result<T> {
+    let arg = args.get(index).ok_or_else(|| anyhow!("Missing {}", what))?;
+    arg.parse().map_err(|_| anyhow!("Invalid {} '{}'", what, arg))
+}
+
+/// Parses the argument at `index` if it was given, or else gives `default`.
+pub fn optional_arg<T: FromStr>(args: &[&str], index: usize, what: &str, default: T) -> Result<T> {
+    match args.get(index) {
+        Some(_) => arg(args, index, what),
+        None => Ok(default),
+    }
+}
+
+/// The `help` text: every command with its arguments, lined up.
+fn help(commands: &[Command]) -> String {
+    let usage = |command: &Command| format!("{} {}", command.name, command.args).trim_end().to_string();
+    let width = commands.iter().chain(&BUILT_IN).map(|command| usage(command).len()).max().unwrap_or(0);
+    commands.iter().chain(&BUILT_IN).map(|command| format!("{:width$}  {}\n", usage(command), command.help)).collect()
+}
+
+/// Reads commands from `input` until `quit` or the end of it, writing what each gives to `out`.
+/// A command's error is shown rather than ending the session. `prompt`, if not empty, is written
+/// before each command is read.
+pub fn repl(solution: &dyn Solution, input: impl BufRead, out: &mut impl Write, prompt: &str) -> Result<()> {
+    let mut explorer = solution.explorer();
+    let commands = explorer.as_ref().map_or(&[][..], |explorer| explorer.commands());
+    let mut lines = input.lines();
+    loop {
+        write!(out, "{}", prompt)?;
+        out.flush()?;
+        let Some(line) = lines.next().transpose()? else {
+            if !prompt.is_empty() {
+                writeln!(out)?;
+            }
+            return Ok(());
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((&command, args)) = words.split_first() else {
+            continue;
+        };
+        let result = match command {
+            "quit" | "exit" => return Ok(()),
+            "help" => Ok(help(commands)),
+            "part" => arg(args, 0, "part").and_then(|part| solution.part(part)).map(|answer| format!("{}\n", answer)),
+            _ => match explorer.as_mut() {
+                Some(explorer) if commands.iter().any(|c| c.name == command) => explorer.run(command, args),
+                _ => Err(anyhow!("Unknown command '{}', try 'help'", command)),
+            },
+        };
+        match result {
+            Ok(text) if text.ends_with('\n') || text.is_empty() => write!(out, "{}", text)?,
+            Ok(text) => writeln!(out, "{}", text)?,
+            Err(e) => writeln!(out, "Error: {:#}", e)?,
+        }
+    }
+}
+
+/// Runs commands on a day's example input, returning what they wrote, for tests.
+#[cfg(test)]
+pub fn session(day: u8, commands: &str) -> Result<String> {
+    let solution = crate::day(day).ok_or_else(|| anyhow!("No day {}", day))?.load(&format!("day{:02}ex.txt", day))?;
+    let mut out = vec![];
+    repl(&*solution, commands.as_bytes(), &mut out, "")?;
+    Ok(String::from_utf8(out)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_commands() -> Result<()> {
+        assert_eq!("21\n8\n", session(8, "part 1\n\npart 2\nquit\npart 1\n")?);
+        let out = session(8, "help\nfly\npart 3\npart\n")?;
+        assert!(out.starts_with("help        lists the commands\npart <1|2>  solves a part of the input\n"), "{}", out);
+        assert!(out.ends_with("Error: Unknown command 'fly', try 'help'\nError: No such part: 3\nError: Missing part\n"), "{}", out);
+        Ok(())
+    }
+
+    #[test]
+    fn prompts() -> Result<()> {
+        let solution = crate::day(13).unwrap().load("day13ex.txt")?;
+        let mut out = vec![];
+        repl(&*solution, "part 1\n".as_bytes(), &mut out, "day13> ")?;
+        assert_eq!("day13> 13\nday13> \n", String::from_utf8(out)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+//! Starting a new day: a module skeleton implementing [`Solution`](crate::Solution), its entries in
+//! the registry, and placeholder input files, so that the first thing left to write is the puzzle.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+
+/// The skeleton of a day's module, with `NN` standing for the zero-padded day number. Its tests are
+/// ignored until the parts are written, so that the rest of the suite keeps passing.
+const MODULE: &str = r#"use std::io::BufRead;
+
+use anyhow::{Context, Result};
+
+use crate::{Answer, NotSolved, Solution};
+
+pub fn load_lines(input: impl BufRead) -> Result<Vec<String>> {
+    Ok(input.lines().collect::<Result<_, _>>()?)
+}
+
+pub struct DayNN {
+    lines: Vec<String>,
+}
+
+impl Solution for DayNN {
+    fn parse(input: &mut dyn BufRead) -> Result<Self> {
+        Ok(DayNN { lines: load_lines(input)? })
+    }
+
+    fn part_1(&self) -> Result<Answer> {
+        Err(NotSolved).with_context(|| format!("Part 1 ({} lines of input)", self.lines.len()))
+    }
+
+    fn part_2(&self) -> Result<Answer> {
+        Err(NotSolved).with_context(|| format!("Part 2 ({} lines of input)", self.lines.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::open;
+
+    use super::*;
+
+    #[test]
+    #[ignore = "not solved yet"]
+    fn part_1() -> Result<()> {
+        println!("Day NN part 1: {}", DayNN::parse(&mut open("dayNN.txt")?)?.part_1()?);
+        Ok(())
+    }
+
+    #[test]
+    #[ignore = "not solved yet"]
+    fn part_2() -> Result<()> {
+        println!("Day NN part 2: {}", DayNN::parse(&mut open("dayNN.txt")?)?.part_2()?);
+        Ok(())
+    }
+
+    #[test]
+    #[ignore = "not solved yet"]
+    fn part_1_test() -> Result<()> {
+        // TODO: the example's answer, from the puzzle text.
+        assert_eq!(Answer::Number(0), DayNN::parse(&mut open("dayNNex.txt")?)?.part_1()?);
+        Ok(())
+    }
+}
+"#;
+
+/// A new day's module source.
+pub fn module(number: u8) -> String {
+    MODULE.replace("NN", &format!("{:02}", number))
+}
+
+/// Adds the next day to lib.rs's source: its `pub mod` alongside the others, in order, and its entry
+/// at the end of `DAYS`. Days are numbered from 1 with none missed, as the registry expects, so the
+/// day must be the one after the last.
+pub fn register(lib: &str, number: u8) -> Result<String> {
+    let name = format!("day{:02}", number);
+    let declaration = format!("pub mod {};", name);
+    let entry = format!("    Day {{ number: {}, parse: boxed::<{}::Day{:02}> }},", number, name, number);
+    if lib.lines().any(|line| line == declaration) {
+        Err(anyhow!("Day {} is already registered", number))?;
+    }
+
+    let mut lines: Vec<String> = lib.lines().map(str::to_string).collect();
+    let start = lines.iter().position(|line| line.starts_with("const DAYS: [Day; ")).ok_or_else(|| anyhow!("No DAYS array found"))?;
+    let count: usize = lines[start]
+        .trim_start_matches("const DAYS: [Day; ")
+        .split(']')
+        .next()
+        .and_then(|n| n.parse().ok())
+        .ok_or_else(|| anyhow!("Can't read the size of DAYS from '{}'", lines[start]))?;
+    if number as usize != count + 1 {
+        Err(anyhow!("Can't add day {}: there are {} days so far, so the next one is day {}", number, count, count + 1))?;
+    }
+    lines[start] = format!("const DAYS: [Day; {}] = [", count + 1);
+    let end = (start..lines.len()).find(|&i| lines[i] == "];").ok_or_else(|| anyhow!("No end to the DAYS array"))?;
+    lines.insert(end, entry);
+
+    let modules = lines.iter().position(|line| line.starts_with("pub mod ")).ok_or_else(|| anyhow!("No module declarations found"))?;
+    let at = (modules..lines.len()).find(|&i| !lines[i].starts_with("pub mod ") || lines[i] > declaration).unwrap_or(lines.len());
+    lines.insert(at, declaration);
+
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Creates the next day in the crate at `root`: its module, registered in lib.rs, and empty input and
+/// example files where there aren't any yet. Returns the files written.
+pub fn new_day(root: &Path, number: u8) -> Result<Vec<PathBuf>> {
+    if !(1..=25).contains(&number) {
+        Err(anyhow!("There is no day {}, expected 1 to 25", number))?;
+    }
+    let lib_file = root.join("src/lib.rs");
+    let lib = fs::read_to_string(&lib_file).with_context(|| format!("reading {} (run this from the crate root)", lib_file.display()))?;
+    let module_file = root.join(format!("src/day{:02}.rs", number));
+    if module_file.exists() {
+        Err(anyhow!("{} already exists", module_file.display()))?;
+    }
+    let lib = register(&lib, number)?;
+
+    fs::write(&module_file, module(number)).with_context(|| format!("writing {}", module_file.display()))?;
+    fs::write(&lib_file, lib).with_context(|| format!("writing {}", lib_file.display()))?;
+    let mut written = vec![module_file, lib_file];
+    for input in [format!("day{:02}.txt", number), format!("day{:02}ex.txt", number)] {
+        let file = root.join(input);
+        if !file.exists() {
+            fs::write(&file, "").with_context(|| format!("writing {}", file.display()))?;
+            written.push(file);
+        }
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use super::*;
+
+    #[test]
+    fn registers() -> Result<()> {
+        let lib = include_str!("lib.rs");
+        let count = crate::days().len();
+        let next = count as u8 + 1;
+        let registered = register(lib, next)?;
+        assert!(registered.contains(&format!("pub mod day{:02};\npub mod day{:02};", count, next)));
+        assert!(registered.contains(&format!("const DAYS: [Day; {}] = [", count + 1)));
+        assert!(registered.contains(&format!("parse: boxed::<day{:02}::Day{:02}> }},\n];", next, next)));
+        assert_eq!("Day 1 is already registered", register(lib, 1).unwrap_err().to_string());
+
+        // Skipping a day would leave a gap in the registry.
+        let e = register(lib, next + 1).unwrap_err();
+        assert_eq!(format!("Can't add day {}: there are {} days so far, so the next one is day {}", next + 1, count, next), e.to_string());
+
+        let lib = "pub mod day01;\npub mod grid;\n\nconst DAYS: [Day; 1] = [\n    Day { number: 1, parse: boxed::<day01::Day01> },\n];\n";
+        assert_eq!(
+            "pub mod day01;\npub mod day02;\npub mod grid;\n\nconst DAYS: [Day; 2] = [\n    Day { number: 1, parse: boxed::<day01::Day01> },\n    Day { number: 2, parse: boxed::<day02::Day02> },\n];\n",
+            register(lib, 2)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn scaffolds() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("advent2022-scaffold-{}", std::process::id()));
+        fs::create_dir_all(root.join("src"))?;
+        fs::write(root.join("src/lib.rs"), include_str!("lib.rs"))?;
+        let next = crate::days().len() as u8 + 1;
+        let name = format!("day{:02}", next);
+        fs::write(root.join(format!("{}ex.txt", name)), "1\n2\n")?;
+
+        let written = new_day(&root, next)?;
+        assert_eq!(vec![root.join(format!("src/{}.rs", name)), root.join("src/lib.rs"), root.join(format!("{}.txt", name))], written);
+        let module = fs::read_to_string(root.join(format!("src/{}.rs", name)))?;
+        assert_eq!(super::module(next), module);
+        assert!(module.contains(&format!("impl Solution for Day{:02}", next)));
+        assert!(module.contains(&format!("open(\"{}ex.txt\")", name)));
+        assert_eq!(3, module.matches("#[ignore = \"not solved yet\"]").count());
+        assert_eq!(register(include_str!("lib.rs"), next)?, fs::read_to_string(root.join("src/lib.rs"))?);
+        assert_eq!("1\n2\n", fs::read_to_string(root.join(format!("{}ex.txt", name)))?);
+        assert!(new_day(&root, next).is_err());
+        assert!(new_day(&root, next + 2).is_err());
+        assert!(new_day(&root, 26).is_err());
+        fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    /// Copies a directory's files and subdirectories, other than `target` and `.git`.
+    fn copy_crate(from: &Path, to: &Path) -> Result<()> {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            if entry.file_type()?.is_dir() {
+                if name != "target" && name != ".git" {
+                    copy_crate(&entry.path(), &to.join(&name))?;
+                }
+            } else {
+                fs::copy(entry.path(), to.join(&name))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Scaffolds the next day in a copy of this crate and runs the copy's tests. Its target
+    /// directory is kept between runs, so only the crate itself is rebuilt. This builds the whole
+    /// crate again, so it only runs when asked for with `cargo test -- --ignored`.
+    #[test]
+    #[ignore = "builds a copy of the crate"]
+    fn scaffolded_crate_passes() -> Result<()> {
+        let here = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let scratch = here.join("target/scaffold");
+        let root = scratch.join("crate");
+        if root.exists() {
+            fs::remove_dir_all(&root)?;
+        }
+        copy_crate(here, &root)?;
+        new_day(&root, crate::days().len() as u8 + 1)?;
+
+        let output = Command::new(env!("CARGO"))
+            .args(["test", "--offline", "--", "--skip", "scaffold::tests::scaffolded_crate_passes"])
+            .current_dir(&root)
+            .env("CARGO_TARGET_DIR", scratch.join("target"))
+            .output()?;
+        assert!(output.status.success(), "{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+        fs::remove_dir_all(root)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,443 @@
+//! A small HTTP server answering puzzles as JSON, so that other tools can submit inputs without
+//! linking against the crate:
+//!
+//! - `GET /days` lists the registered days.
+//! - `POST /days/{n}/parts/{p}` runs a part on the request body, with parameter changes taken from
+//!   the query string (`?row=10`), and responds with the answer or error and how long it took.
+//!
+//! Each connection gets a thread and a single request, with a limited number answered at once and
+//! each part given a time budget. The server is meant for localhost: it reads whole inputs into
+//! memory and has no authentication.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::executor::{self, panic_message};
+use crate::metrics::{self, Counters};
+use crate::params::{parse_change, Changes};
+use crate::{day, days, Answer, Day};
+
+/// The largest request body accepted, well above any real puzzle input.
+const MAX_BODY: usize = 16 * 1024 * 1024;
+
+/// The longest request line or header accepted.
+const MAX_LINE: usize = 8 * 1024;
+
+/// The most headers a request can have.
+const MAX_HEADERS: usize = 100;
+
+/// How long a client can take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How many connections are answered at once by default.
+pub const MAX_CONNECTIONS: usize = 16;
+
+/// How long a part can run for by default.
+pub const SOLVE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An HTTP request, as far as the server cares.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    /// The path without its query string.
+    pub path: String,
+    /// The query string's `name=value` pairs, percent-decoded.
+    pub query: Vec<String>,
+    pub body: Vec<u8>,
+}
+
+/// A JSON response with its status.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Response { status: 200, body }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Response { status, body: json!({ "error": message.into() }) }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            411 => "Length Required",
+            413 => "Payload Too Large",
+            414 => "URI Too Long",
+            422 => "Unprocessable Entity",
+            431 => "Request Header Fields Too Large",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+
+    fn write_to(&self, out: &mut impl Write) -> Result<()> {
+        let body = serde_json::to_string(&self.body)?;
+        write!(out, "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", self.status, self.reason(), body.len(), body)?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// What running a part on a submitted input gave. `error` is set instead of `answer` if the input
+/// didn't parse or the part failed.
+#[derive(Debug, Clone, Serialize)]
+pub struct Solved {
+    pub day: u8,
+    pub part: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer: Option<Answer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub parse_ns: u64,
+    pub part_ns: u64,
+    /// The work the part counted (see [`metrics`]).
+    #[serde(skip_serializing_if = "Counters::is_empty")]
+    pub counters: Counters,
+}
+
+/// Reads one line of a request's head into `line`, failing with `too_long` if it is longer than
+/// [`MAX_LINE`] rather than reading on.
+fn read_line(input: &mut impl BufRead, line: &mut String, what: &str, too_long: u16) -> Result<(), Response> {
+    line.clear();
+    input.take(MAX_LINE as u64 + 1).read_line(line).map_err(|_| Response::error(400, format!("Couldn't read the {}", what)))?;
+    if line.len() > MAX_LINE {
+        return Err(Response::error(too_long, format!("The {} is longer than {} bytes", what, MAX_LINE)));
+    }
+    Ok(())
+}
+
+/// Decodes a query string's `%XX` escapes and `+` for space, or `None` if an escape is malformed or
+/// the result isn't UTF-8.
+fn percent_decode(s: &str) -> Option<String> {
+    let mut bytes = vec![];
+    let mut rest = s.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = rest.get(..2).filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+                bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+                rest = &rest[2..];
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Reads a request, failing with the response to send if it is malformed or too big.
+pub fn read_request(input: &mut impl BufRead) -> Result<Request, Response> {
+    let bad = |message: &str| Response::error(400, message);
+    let mut line = String::new();
+    read_line(input, &mut line, "request line", 414)?;
+    let mut words = line.split_whitespace();
+    let (Some(method), Some(target), Some(_version)) = (words.next(), words.next(), words.next()) else {
+        return Err(bad("Expected a request line like 'POST /days/1/parts/1 HTTP/1.1'"));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| percent_decode(pair).ok_or_else(|| Response::error(400, format!("Malformed query parameter '{}'", pair))))
+        .collect::<Result<_, _>>()?;
+    let mut request = Request { method: method.to_string(), path: path.to_string(), query, body: vec![] };
+
+    let mut length = None;
+    for headers in 0.. {
+        read_line(input, &mut line, "header", 431)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if headers == MAX_HEADERS {
+            return Err(Response::error(431, format!("Requests are limited to {} headers", MAX_HEADERS)));
+        }
+        let (name, value) = header.split_once(':').ok_or_else(|| bad("Malformed header"))?;
+        if name.trim().eq_ignore_ascii_case("content-length") {
+            length = Some(value.trim().parse::<usize>().map_err(|_| bad("Invalid Content-Length"))?);
+        }
+    }
+
+    if request.method == "POST" {
+        let length = length.ok_or_else(|| Response::error(411, "POST needs a Content-Length"))?;
+        if length > MAX_BODY {
+            return Err(Response::error(413, format!("Inputs are limited to {} bytes", MAX_BODY)));
+        }
+        request.body = vec![0; length];
+        input.read_exact(&mut request.body).map_err(|_| bad("The body is shorter than its Content-Length"))?;
+    }
+    Ok(request)
+}
+
+/// One of the connections the server answers at once, given back when dropped. A part still running
+/// past its budget shares its connection's slot, so the slot is only free once the part finishes.
+struct Slot(Arc<AtomicUsize>);
+
+impl Slot {
+    /// Takes a slot if fewer than `limit` are taken.
+    fn take(taken: &Arc<AtomicUsize>, limit: usize) -> Option<Self> {
+        taken.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| (n < limit).then_some(n + 1)).ok()?;
+        Some(Slot(Arc::clone(taken)))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Answers a request, with parameters changed from their defaults by `changes` and then by the
+/// request's query string, giving a part `timeout` to run.
+fn handle(request: &Request, changes: &Changes, timeout: Duration, slot: Arc<Slot>) -> Response {
+    let segments: Vec<&str> = request.path.split('/').filter(|s| !s.is_empty()).collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["days"]) => Response::ok(json!({ "days": days().iter().map(|day| json!({ "day": day.number, "parts": [1, 2] })).collect::<Vec<_>>() })),
+        ("POST", ["days", number, "parts", part]) => {
+            let Some(day) = number.parse().ok().and_then(day) else {
+                return Response::error(404, format!("Day {} is not registered", number));
+            };
+            let Ok(part @ 1..=2) = part.parse::<u8>() else {
+                return Response::error(404, format!("Invalid part '{}', expected 1 or 2", part));
+            };
+            let mut changes = changes.clone();
+            for change in &request.query {
+                match parse_change(change) {
+                    Ok((name, value)) => changes.insert(name, value),
+                    Err(e) => return Response::error(400, format!("{:#}", e)),
+                };
+            }
+            let Some(solved) = solve(day, part, request.body.clone(), changes, timeout, slot) else {
+                return Response::error(503, format!("Day {} part {} didn't finish within {:?}", day.number, part, timeout));
+            };
+            let status = if solved.error.is_some() { 422 } else { 200 };
+            Response { status, body: serde_json::to_value(&solved).unwrap_or(Value::Null) }
+        }
+        (_, ["days"]) | (_, ["days", _, "parts", _]) => Response::error(405, format!("{} isn't supported for {}", request.method, request.path)),
+        _ => Response::error(404, format!("No such resource: {}", request.path)),
+    }
+}
+
+/// Parses an input, changes its parameters and runs a part, timing each and catching panics, or
+/// gives `None` if that takes longer than `timeout`. The part holds `slot` until it finishes.
+fn solve(day: &'static Day, part: u8, input: Vec<u8>, changes: Changes, timeout: Duration, slot: Arc<Slot>) -> Option<Solved> {
+    executor::with_timeout(timeout, move || {
+        let _slot = slot;
+        run_part(day, part, &input, &changes)
+    })
+}
+
+fn run_part(day: &Day, part: u8, input: &[u8], changes: &Changes) -> Solved {
+    let mut solved = Solved { day: day.number, part, answer: None, error: None, parse_ns: 0, part_ns: 0, counters: Counters::new() };
+    let run = || -> Result<()> {
+        let start = Instant::now();
+        let mut solution = (day.parse)(&mut &input[..]).context("parsing the input")?;
+        solved.parse_ns = start.elapsed().as_nanos() as u64;
+        solution.configure(changes)?;
+        let start = Instant::now();
+        let (answer, counters) = metrics::record(|| solution.part(part));
+        solved.part_ns = start.elapsed().as_nanos() as u64;
+        solved.counters = counters;
+        solved.answer = Some(answer?);
+        Ok(())
+    };
+    match panic::catch_unwind(AssertUnwindSafe(run)) {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => solved.error = Some(format!("{:#}", e)),
+        Err(panic) => solved.error = Some(format!("panicked: {}", panic_message(&*panic))),
+    }
+    solved
+}
+
+/// Reads one request from a connection and writes the response.
+fn respond(mut stream: TcpStream, changes: &Changes, timeout: Duration, slot: Slot) -> Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    match read_request(&mut BufReader::new(&stream)) {
+        Ok(request) => handle(&request, changes, timeout, Arc::new(slot)).write_to(&mut stream),
+        Err(response) => {
+            response.write_to(&mut stream)?;
+            // Closing with some of the request unread resets the connection, which can lose the
+            // response, so what's left is read and dropped first.
+            stream.shutdown(Shutdown::Write)?;
+            let _ = io::copy(&mut (&stream).take(MAX_BODY as u64), &mut io::sink());
+            Ok(())
+        }
+    }
+}
+
+/// Listens for requests.
+pub struct Server {
+    listener: TcpListener,
+    changes: Changes,
+    timeout: Duration,
+    connections: usize,
+    taken: Arc<AtomicUsize>,
+}
+
+impl Server {
+    /// Binds to an address, such as `127.0.0.1:8022` (port 0 picks a free one), answering with
+    /// `changes` made to each day's parameters.
+    pub fn bind(addr: impl ToSocketAddrs, changes: Changes) -> Result<Self> {
+        let listener = TcpListener::bind(addr).context("binding the server's address")?;
+        Ok(Server { listener, changes, timeout: SOLVE_TIMEOUT, connections: MAX_CONNECTIONS, taken: Arc::new(AtomicUsize::new(0)) })
+    }
+
+    /// Gives each part `timeout` to run (by default [`SOLVE_TIMEOUT`]) before answering that it
+    /// timed out.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Answers at most `connections` at once (by default [`MAX_CONNECTIONS`]), counting parts still
+    /// running past their budget, and turns away any more.
+    pub fn with_connections(mut self, connections: usize) -> Self {
+        self.connections = connections;
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Answers requests until the process exits, each connection on its own thread.
+    pub fn serve(self) -> Result<()> {
+        for stream in self.listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("Error: accepting a connection: {}", e);
+                    continue;
+                }
+            };
+            let Some(slot) = Slot::take(&self.taken, self.connections) else {
+                // Turned away here rather than on a thread of its own, without waiting to read it.
+                let _ = stream.set_write_timeout(Some(READ_TIMEOUT));
+                let _ = Response::error(503, "Too many requests at once, try again later").write_to(&mut stream);
+                continue;
+            };
+            let (changes, timeout) = (self.changes.clone(), self.timeout);
+            thread::spawn(move || {
+                if let Err(e) = respond(stream, &changes, timeout, slot) {
+                    eprintln!("Error: answering a request: {:#}", e);
+                }
+            });
+        }
+        Err(anyhow!("Stopped listening"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    /// Sends a raw request and returns the response's status and JSON body.
+    fn send(addr: SocketAddr, request: &str) -> Result<(u16, Value)> {
+        let mut stream = TcpStream::connect(addr)?;
+        stream.write_all(request.as_bytes())?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        let (head, body) = response.split_once("\r\n\r\n").ok_or_else(|| anyhow!("No body in {}", response))?;
+        let status = head.split_whitespace().nth(1).ok_or_else(|| anyhow!("No status in {}", head))?.parse()?;
+        Ok((status, serde_json::from_str(body)?))
+    }
+
+    fn post(addr: SocketAddr, path: &str, body: &str) -> Result<(u16, Value)> {
+        send(addr, &format!("POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}", path, body.len(), body))
+    }
+
+    #[test]
+    fn serves() -> Result<()> {
+        let server = Server::bind("127.0.0.1:0", Changes::new())?;
+        let addr = server.local_addr()?;
+        thread::spawn(move || server.serve());
+
+        let (status, body) = send(addr, "GET /days HTTP/1.1\r\nHost: localhost\r\n\r\n")?;
+        assert_eq!(200, status);
+        assert_eq!(json!({ "day": 1, "parts": [1, 2] }), body["days"][0]);
+        assert_eq!(crate::days().len(), body["days"].as_array().unwrap().len());
+
+        let example = std::fs::read_to_string("day07ex.txt")?;
+        let (status, body) = post(addr, "/days/7/parts/1", &example)?;
+        assert_eq!((200, &json!(95437)), (status, &body["answer"]));
+        assert!(body["part_ns"].is_u64() && body.get("error").is_none());
+
+        let example = std::fs::read_to_string("day15ex.txt")?;
+        let (status, body) = post(addr, "/days/15/parts/1?row=10&bound=20", &example)?;
+        assert_eq!((200, &json!(26)), (status, &body["answer"]));
+        assert!(body["counters"]["sensor_checks"].is_u64());
+
+        let (status, body) = post(addr, "/days/9/parts/1", "X 1\n")?;
+        assert_eq!(422, status);
+        assert!(body["error"].as_str().unwrap().starts_with("parsing the input: "), "{}", body);
+        assert_eq!(404, post(addr, "/days/99/parts/1", "")?.0);
+        assert_eq!(404, post(addr, "/days/1/parts/3", "")?.0);
+        assert_eq!(404, post(addr, "/nothing", "")?.0);
+        assert_eq!(405, send(addr, "GET /days/1/parts/1 HTTP/1.1\r\n\r\n")?.0);
+        assert_eq!(411, send(addr, "POST /days/1/parts/1 HTTP/1.1\r\n\r\n")?.0);
+        assert_eq!(400, send(addr, "nonsense\r\n\r\n")?.0);
+        Ok(())
+    }
+
+    #[test]
+    fn limits() -> Result<()> {
+        let server = Server::bind("127.0.0.1:0", Changes::new())?;
+        let addr = server.local_addr()?;
+        thread::spawn(move || server.serve());
+
+        let example = std::fs::read_to_string("day15ex.txt")?;
+        let (status, body) = post(addr, "/days/15/parts/1?r%6Fw=%31%30&bound=20", &example)?;
+        assert_eq!((200, &json!(26)), (status, &body["answer"]));
+        let (status, body) = post(addr, "/days/15/parts/1?row=%1", &example)?;
+        assert_eq!((400, &json!("Malformed query parameter 'row=%1'")), (status, &body["error"]));
+
+        let long = "x".repeat(MAX_LINE);
+        assert_eq!(414, send(addr, &format!("GET /{} HTTP/1.1\r\n\r\n", long))?.0);
+        assert_eq!(431, send(addr, &format!("GET /days HTTP/1.1\r\nX: {}\r\n\r\n", long))?.0);
+        let headers = "X: 1\r\n".repeat(MAX_HEADERS);
+        assert_eq!(200, send(addr, &format!("GET /days HTTP/1.1\r\n{}\r\n", headers))?.0);
+        assert_eq!(431, send(addr, &format!("GET /days HTTP/1.1\r\n{}X: 1\r\n\r\n", headers))?.0);
+
+        // A cave this big would take tens of gigabytes, which would abort the whole server.
+        let (status, body) = post(addr, "/days/14/parts/1", "100000,100000 -> 100000,100001\n")?;
+        assert_eq!(422, status);
+        assert!(body["error"].as_str().unwrap().contains("expected x coordinate of at most 2000, found 100000"), "{}", body);
+        let (status, body) = post(addr, "/days/14/parts/1?sand_source=%7B%22x%22%3A100000%2C%22y%22%3A0%7D", "498,4 -> 498,6\n")?;
+        assert_eq!(422, status);
+        assert!(body["error"].as_str().unwrap().contains("too far out"), "{}", body);
+        assert_eq!(200, send(addr, "GET /days HTTP/1.1\r\nHost: localhost\r\n\r\n")?.0);
+        Ok(())
+    }
+
+    #[test]
+    fn slots() {
+        assert_eq!(Some("a b/c".to_string()), percent_decode("a+b%2Fc"));
+        assert_eq!(None, percent_decode("%+1"));
+        let taken = Arc::new(AtomicUsize::new(0));
+        let slot = Slot::take(&taken, 1);
+        assert!(slot.is_some() && Slot::take(&taken, 1).is_none());
+        drop(slot);
+        assert!(Slot::take(&taken, 1).is_some());
+        assert_eq!(0, taken.load(Ordering::SeqCst));
+    }
+}
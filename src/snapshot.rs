@@ -0,0 +1,135 @@
+//! Golden snapshot tests: large rendered outputs, such as screens and cave states, are compared
+//! with the expected text kept in `snapshots/<name>.txt` rather than a long literal in the test.
+//!
+//! A mismatch fails with a line diff. To accept new output, rerun the tests with
+//! `UPDATE_SNAPSHOTS=1`, which writes every snapshot checked, and review the changed files.
+
+use std::fs;
+use std::path::Path;
+
+/// Where snapshots are kept, relative to the crate root.
+pub const SNAPSHOT_DIR: &str = "snapshots";
+
+/// The environment variable that, set to anything but 0, records snapshots instead of checking them.
+pub const UPDATE_ENV: &str = "UPDATE_SNAPSHOTS";
+
+/// How many unchanged lines to show around each change in a diff.
+const CONTEXT: usize = 2;
+
+/// Asserts that `actual` matches the snapshot called `name`, or records it in update mode.
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let update = std::env::var(UPDATE_ENV).is_ok_and(|v| !v.is_empty() && v != "0");
+    if let Err(message) = check(Path::new(SNAPSHOT_DIR), name, actual, update) {
+        panic!("{}", message);
+    }
+}
+
+/// Checks `actual` against the snapshot `name` in `dir`, or with `update` writes it there.
+fn check(dir: &Path, name: &str, actual: &str, update: bool) -> Result<(), String> {
+    let file = dir.join(format!("{}.txt", name));
+    if update {
+        fs::create_dir_all(dir).and_then(|_| fs::write(&file, actual)).map_err(|e| format!("Couldn't write {}: {}", file.display(), e))?;
+        return Ok(());
+    }
+    let Ok(expected) = fs::read_to_string(&file) else {
+        return Err(format!("No snapshot {} at {}; run the tests with {}=1 to record it", name, file.display(), UPDATE_ENV));
+    };
+    if expected == actual {
+        return Ok(());
+    }
+    Err(format!("Snapshot {} ({}) doesn't match, - expected + actual:\n{}Run the tests with {}=1 to accept the new output", name, file.display(), diff(&expected, actual), UPDATE_ENV))
+}
+
+/// One line of a diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// A line diff of two texts, showing each change with a little unchanged context around it.
+pub fn diff(expected: &str, actual: &str) -> String {
+    let lines = edits(&expected.lines().collect::<Vec<_>>(), &actual.lines().collect::<Vec<_>>());
+    let changed: Vec<usize> = lines.iter().enumerate().filter(|(_, line)| !matches!(line, Line::Same(_))).map(|(i, _)| i).collect();
+    let near_change = |i: usize| changed.iter().any(|&c| c.abs_diff(i) <= CONTEXT);
+
+    let mut out = String::new();
+    let mut skipped = false;
+    let mut number = 0;
+    for (i, line) in lines.iter().enumerate() {
+        if !matches!(line, Line::Added(_)) {
+            number += 1;
+        }
+        if !near_change(i) {
+            skipped = true;
+            continue;
+        }
+        if skipped || out.is_empty() {
+            out += &format!("@@ line {}\n", number);
+            skipped = false;
+        }
+        out += &match line {
+            Line::Same(text) => format!(" {}\n", text),
+            Line::Removed(text) => format!("-{}\n", text),
+            Line::Added(text) => format!("+{}\n", text),
+        };
+    }
+    if expected.ends_with('\n') != actual.ends_with('\n') {
+        out += &format!("(the {} ends with a newline)\n", if expected.ends_with('\n') { "expected text" } else { "actual text" });
+    }
+    out
+}
+
+/// The shortest edit turning `from` into `to`, from their longest common subsequence.
+fn edits<'a>(from: &[&'a str], to: &[&'a str]) -> Vec<Line<'a>> {
+    // common[i][j] is the longest common subsequence of from[i..] and to[j..].
+    let mut common = vec![vec![0; to.len() + 1]; from.len() + 1];
+    for i in (0..from.len()).rev() {
+        for j in (0..to.len()).rev() {
+            common[i][j] = if from[i] == to[j] { common[i + 1][j + 1] + 1 } else { common[i + 1][j].max(common[i][j + 1]) };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut lines = vec![];
+    while i < from.len() || j < to.len() {
+        if i < from.len() && j < to.len() && from[i] == to[j] {
+            lines.push(Line::Same(from[i]));
+            (i, j) = (i + 1, j + 1);
+        } else if i < from.len() && (j == to.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(Line::Removed(from[i]));
+            i += 1;
+        } else {
+            lines.push(Line::Added(to[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diffs() {
+        assert_eq!("", diff("a\nb\n", "a\nb\n"));
+        let expected: String = (1..=12).map(|n| format!("{}\n", n)).collect();
+        let actual = expected.replace("5\n", "five\n") + "13\n";
+        assert_eq!("@@ line 3\n 3\n 4\n-5\n+five\n 6\n 7\n@@ line 11\n 11\n 12\n+13\n", diff(&expected, &actual));
+        assert_eq!("@@ line 1\n-a\n+b\n(the expected text ends with a newline)\n", diff("a\n", "b"));
+    }
+
+    #[test]
+    fn snapshots() {
+        let dir = std::env::temp_dir().join(format!("advent2022-snapshots-{}", std::process::id()));
+        let error = check(&dir, "screen", "##\n..\n", false).unwrap_err();
+        assert!(error.starts_with("No snapshot screen"), "{}", error);
+        check(&dir, "screen", "##\n..\n", true).unwrap();
+        check(&dir, "screen", "##\n..\n", false).unwrap();
+        let error = check(&dir, "screen", "##\n.#\n", false).unwrap_err();
+        assert!(error.contains("-..\n+.#\n"), "{}", error);
+        assert!(error.ends_with(&format!("{}=1 to accept the new output", UPDATE_ENV)), "{}", error);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
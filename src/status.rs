@@ -0,0 +1,262 @@
+//! Where every day stands: which parts are written, whether their answers on the active input are
+//! verified against answers.json (each a star), whether they get the examples right and how long
+//! they took when last benchmarked. It is worked out from the registry and the answers store each
+//! time, so it can't fall out of step with lib.rs. The parts are run in parallel, each with a time
+//! budget (see [`executor`](crate::executor)), so a slow or stuck part can't hold up the report.
+
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::answers::{Answers, Outcome};
+use crate::bench::{format_ns, Runtimes};
+use crate::executor::{self, run_parallel, Task};
+use crate::params::Config;
+use crate::Day;
+
+/// How a part's answer on the active input compares with answers.json.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Answered {
+    /// It matches the recorded answer.
+    Verified,
+    /// It differs from the recorded answer.
+    Wrong,
+    /// There is no recorded answer to compare it with.
+    Unverified,
+    /// The part, or loading the input, failed.
+    Error,
+}
+
+impl fmt::Display for Answered {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Answered::Verified => "verified",
+            Answered::Wrong => "WRONG",
+            Answered::Unverified => "unverified",
+            Answered::Error => "ERROR",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PartStatus {
+    pub part: u8,
+    /// Whether the part is written, that is it doesn't fail with [`NotSolved`](crate::NotSolved)
+    /// like a new day's parts do. Unknown when there was no input to run it on.
+    pub implemented: Option<bool>,
+    pub answer: Answered,
+    /// Why the part failed or didn't finish on the active input.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// How many example inputs (`dayNNex*.txt`) have a recorded answer for the part.
+    pub examples: usize,
+    pub examples_passed: usize,
+    /// The part's median time on the active input in the stored benchmark report, if it has one.
+    pub runtime_ns: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DayStatus {
+    pub day: u8,
+    /// The active input, if one was found.
+    pub input: Option<String>,
+    /// Why the active input couldn't be found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub parts: Vec<PartStatus>,
+}
+
+/// Every day's status, as shown by the `status` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Status {
+    pub days: Vec<DayStatus>,
+    /// How many parts have a verified answer.
+    pub stars: usize,
+}
+
+impl Status {
+    pub fn new(days: Vec<DayStatus>) -> Self {
+        let stars = days.iter().flat_map(|day| &day.parts).filter(|part| part.answer == Answered::Verified).count();
+        Status { days, stars }
+    }
+}
+
+/// A table with a row for each part, then any errors and the number of stars. Parts that haven't
+/// been benchmarked on the active input have no runtime.
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Day  Part  Written  Answer      Examples     Runtime")?;
+        for day in &self.days {
+            for part in &day.parts {
+                let written = match part.implemented {
+                    Some(true) => "yes",
+                    Some(false) => "no",
+                    None => "?",
+                };
+                let answer = if part.implemented == Some(false) { "-".to_string() } else { part.answer.to_string() };
+                let examples = if part.examples == 0 { "-".to_string() } else { format!("{}/{}", part.examples_passed, part.examples) };
+                let runtime = part.runtime_ns.map_or("-".to_string(), format_ns);
+                writeln!(f, "{:<5}{:<6}{:<9}{:<12}{:<8}{:>12}", format!("{:02}", day.day), part.part, written, answer, examples, runtime)?;
+            }
+        }
+        for day in &self.days {
+            if let Some(error) = &day.error {
+                writeln!(f, "Day {:02}: {}", day.day, error)?;
+            }
+            for part in &day.parts {
+                if let Some(error) = &part.error {
+                    writeln!(f, "Day {:02} part {}: {}", day.day, part.part, error)?;
+                }
+            }
+        }
+        let parts = self.days.iter().map(|day| day.parts.len()).sum::<usize>();
+        let implemented = self.days.iter().flat_map(|day| &day.parts).filter(|part| part.implemented == Some(true)).count();
+        writeln!(f, "{} stars, {} of {} parts written", self.stars, implemented, parts)
+    }
+}
+
+/// Whether an input is one of a day's examples, `dayNNex.txt` or `dayNNex<n>.txt`.
+fn is_example(day: u8, input: &str) -> bool {
+    Path::new(input).file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with(&format!("day{:02}ex", day)))
+}
+
+/// Works out where each day stands, given its active input (or why there is none): both parts are
+/// run on it and on each example with a recorded answer, on up to `jobs` threads with `timeout` for
+/// each, and runtimes are looked up in `runtimes`.
+pub fn status(days: Vec<(&'static Day, Result<String>)>, answers: &Answers, config: &Config, runtimes: &Runtimes, jobs: usize, timeout: Duration) -> Status {
+    let mut statuses = vec![];
+    let mut tasks = vec![];
+    for (day, input) in days {
+        let parts = [1, 2].map(|part| PartStatus { part, implemented: None, answer: Answered::Error, error: None, examples: 0, examples_passed: 0, runtime_ns: None });
+        let mut status = DayStatus { day: day.number, input: None, error: None, parts: parts.to_vec() };
+        let task = |input: &str, part| Task { day, input: input.to_string(), params: config.for_input(day.number, input), part };
+        match input {
+            Ok(input) => {
+                tasks.extend([1, 2].map(|part| task(&input, part)));
+                status.input = Some(input);
+            }
+            Err(e) => status.error = Some(format!("{:#}", e)),
+        }
+        for example in answers.inputs(day.number).into_iter().filter(|&input| is_example(day.number, input) && status.input.as_deref() != Some(input)) {
+            tasks.extend([1, 2].into_iter().filter(|&part| answers.get(day.number, example, part).is_some()).map(|part| task(example, part)));
+        }
+        statuses.push(status);
+    }
+
+    for finished in run_parallel(tasks, jobs, timeout) {
+        let Task { day, input, part, .. } = finished.task;
+        let status = statuses.iter_mut().find(|status| status.day == day.number).expect("every task is for a day");
+        let active = status.input.as_deref() == Some(input.as_str());
+        let part = &mut status.parts[part as usize - 1];
+        if active || part.implemented.is_none() {
+            part.implemented = Some(finished.outcome != executor::Outcome::NotSolved);
+        }
+        if active {
+            part.runtime_ns = runtimes.get(day.number, &input, part.part);
+            match finished.outcome {
+                executor::Outcome::Solved(answer) => {
+                    part.answer = match answers.check(day.number, &input, part.part, answer.to_string()) {
+                        Outcome::Pass => Answered::Verified,
+                        Outcome::Fail { .. } => Answered::Wrong,
+                        _ => Answered::Unverified,
+                    }
+                }
+                executor::Outcome::Failed(e) => part.error = Some(e),
+                executor::Outcome::TimedOut => part.error = Some(format!("timed out after {:?}", finished.elapsed)),
+                executor::Outcome::NotSolved => {}
+            }
+        } else {
+            part.examples += 1;
+            if matches!(finished.outcome, executor::Outcome::Solved(answer) if answers.get(day.number, &input, part.part) == Some(&answer.to_string())) {
+                part.examples_passed += 1;
+            }
+        }
+    }
+    Status::new(statuses)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufRead;
+
+    use anyhow::Context;
+
+    use super::*;
+    use crate::answers::ANSWERS_FILE;
+    use crate::params::PARAMS_FILE;
+    use crate::{Answer, NotSolved, Solution};
+
+    /// Counts lines for part 1, with part 2 still to be written.
+    struct Half {
+        lines: usize,
+    }
+
+    impl Solution for Half {
+        fn parse(input: &mut dyn BufRead) -> Result<Self> {
+            Ok(Half { lines: input.lines().count() })
+        }
+
+        fn part_1(&self) -> Result<Answer> {
+            Ok(self.lines.into())
+        }
+
+        fn part_2(&self) -> Result<Answer> {
+            Err(NotSolved).context("Part 2")
+        }
+    }
+
+    fn boxed(input: &mut dyn BufRead) -> Result<Box<dyn Solution>> {
+        Ok(Box::new(Half::parse(input)?))
+    }
+
+    static HALF: Day = Day { number: 99, parse: boxed };
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn registered_day() -> Result<()> {
+        let mut runtimes = Runtimes::default();
+        runtimes.set(7, "day07.txt", 1, 1500);
+        let days = vec![(crate::day(7).unwrap(), Ok("day07.txt".to_string()))];
+        let found = status(days, &Answers::load(ANSWERS_FILE)?, &Config::load(PARAMS_FILE)?, &runtimes, 2, TIMEOUT);
+        for part in &found.days[0].parts {
+            assert_eq!((Some(true), Answered::Verified, 1, 1), (part.implemented, part.answer, part.examples, part.examples_passed));
+        }
+        assert_eq!([Some(1500), None], [found.days[0].parts[0].runtime_ns, found.days[0].parts[1].runtime_ns]);
+        assert!(found.to_string().contains("\n07   1     yes      verified    1/1            1.5µs\n07   2     yes      verified    1/1                -\n"), "{}", found);
+        Ok(())
+    }
+
+    #[test]
+    fn unwritten_parts() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("advent2022-status-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let example = dir.join("day99ex.txt").display().to_string();
+        std::fs::write(&example, "a\nb\n")?;
+        let mut answers = Answers::default();
+        answers.set(99, &example, 1, "3");
+        answers.set(99, &example, 2, "2");
+        answers.set(99, "day99.txt", 1, "4");
+
+        let no_input = vec![(&HALF, Err(anyhow::anyhow!("No input")))];
+        let found = status(no_input, &answers, &Config::default(), &Runtimes::default(), 2, TIMEOUT);
+        let [part_1, part_2] = &found.days[0].parts[..] else { panic!("{:?}", found) };
+        let unrun = PartStatus { part: 1, implemented: Some(true), answer: Answered::Error, error: None, examples: 1, examples_passed: 0, runtime_ns: None };
+        assert_eq!(unrun, *part_1);
+        assert_eq!(PartStatus { part: 2, implemented: Some(false), ..unrun }, *part_2);
+        let table = found.to_string();
+        assert!(table.contains("\n99   2     no       -           0/1                -\nDay 99: No input\n0 stars, 1 of 2 parts written\n"), "{}", table);
+
+        answers.set(99, &example, 1, "2");
+        let found = status(vec![(&HALF, Ok(example.clone()))], &answers, &Config::default(), &Runtimes::default(), 2, TIMEOUT);
+        assert_eq!(Some(example), found.days[0].input);
+        assert_eq!((Answered::Verified, 0, 1), (found.days[0].parts[0].answer, found.days[0].parts[0].examples, found.stars));
+        assert_eq!((Some(false), None), (found.days[0].parts[1].implemented, found.days[0].parts[1].error.as_deref()));
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
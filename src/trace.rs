@@ -0,0 +1,218 @@
+//! Tracing what a solver does, such as each monkey's throw or each unit of sand coming to rest, as a
+//! log of structured events, to debug simulations without uncommenting `println!`s.
+//!
+//! Solvers call [`event`] with how detailed the event is and a closure giving its fields. Nothing
+//! is built unless the caller is inside [`trace`] (or [`record`]) at that verbosity or higher, so
+//! tracing costs a thread-local lookup otherwise, and hot loops should check [`enabled`] once
+//! instead. Logs are written as JSON lines, one event per line, and [`replay`] reads them back, with
+//! each event's `Display` rendering it as text.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::io::BufRead;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// How detailed an event is. Tracing at a level keeps the events at it and every level below.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    /// Once per round or phase, like a monkey round's inspection counts.
+    Summary = 1,
+    /// Each step of a simulation: a throw, a move, a cycle or a unit of sand coming to rest.
+    Step = 2,
+    /// The workings inside a step, like each place sand falls through.
+    Detail = 3,
+}
+
+impl FromStr for Level {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "summary" | "1" => Ok(Level::Summary),
+            "step" | "2" => Ok(Level::Step),
+            "detail" | "3" => Ok(Level::Detail),
+            _ => Err(anyhow!("Invalid verbosity '{}', expected summary, step or detail (or 1 to 3)", s)),
+        }
+    }
+}
+
+/// Something a solver did: what kind of thing, how detailed it is and the fields describing it,
+/// which are written alongside `kind` and `level` in the event's JSON object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    pub kind: String,
+    pub level: Level,
+    #[serde(flatten)]
+    pub fields: Map<String, Value>,
+}
+
+impl Event {
+    /// An event from a JSON object's fields; anything else gives an event with no fields.
+    pub fn new(kind: &str, level: Level, fields: Value) -> Self {
+        let fields = match fields {
+            Value::Object(fields) => fields,
+            _ => Map::new(),
+        };
+        Event { kind: kind.to_string(), level, fields }
+    }
+
+    /// A field as text, with points (`{"x": 1, "y": 2}`) shown as `1,2` and strings unquoted.
+    pub fn field(&self, name: &str) -> String {
+        fn text(value: &Value) -> String {
+            match value {
+                Value::String(s) => s.clone(),
+                Value::Object(o) if o.len() == 2 && o.contains_key("x") && o.contains_key("y") => format!("{},{}", o["x"], o["y"]),
+                Value::Array(values) => values.iter().map(text).collect::<Vec<_>>().join(", "),
+                other => other.to_string(),
+            }
+        }
+        self.fields.get(name).map_or("?".to_string(), text)
+    }
+}
+
+/// Renders an event as text, in the puzzles' own words where they have them.
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field = |name: &str| self.field(name);
+        match self.kind.as_str() {
+            "round" => {
+                write!(f, "== After round {} ==", field("round"))?;
+                for (monkey, inspected) in self.fields.get("inspected").and_then(Value::as_array).into_iter().flatten().enumerate() {
+                    write!(f, "\nMonkey {} inspected items {} times.", monkey, inspected)?;
+                }
+                Ok(())
+            }
+            "throw" => write!(f, "Monkey {} throws an item with worry level {} to monkey {}.", field("monkey"), field("worry"), field("to")),
+            "motion" => write!(f, "== {} {} == tail has visited {} positions", field("direction"), field("steps"), field("visited")),
+            "move" => write!(f, "{}: head at {}, tail at {}", field("direction"), field("head"), field("tail")),
+            "signal" => write!(f, "During cycle {}, X is {}, so the signal strength is {}.", field("cycle"), field("x"), field("strength")),
+            "cycle" => {
+                let lit = if self.fields.get("lit") == Some(&Value::Bool(true)) { "lit" } else { "dark" };
+                write!(f, "Cycle {}: sprite at {}, pixel {} is {}", field("cycle"), field("sprite"), field("pixel"), lit)
+            }
+            "rest" => write!(f, "Sand unit {} comes to rest at {}", field("unit"), field("at")),
+            "fall" => write!(f, "  falls to {}", field("to")),
+            "answer" => write!(f, "Day {} part {}: {}", field("day"), field("part"), field("answer")),
+            _ => {
+                write!(f, "{}", self.kind)?;
+                for name in self.fields.keys() {
+                    write!(f, " {}={}", name, field(name))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Where the events traced on this thread go, and the most detailed level wanted.
+struct Sink {
+    verbosity: Level,
+    write: Box<dyn FnMut(&Event)>,
+}
+
+thread_local! {
+    static TRACING: RefCell<Option<Sink>> = const { RefCell::new(None) };
+}
+
+/// Whether events at `level` are being kept on this thread.
+pub fn enabled(level: Level) -> bool {
+    TRACING.with(|tracing| tracing.borrow().as_ref().is_some_and(|sink| level <= sink.verbosity))
+}
+
+/// Traces an event, with its fields (a JSON object) only made if it's kept.
+pub fn event(level: Level, kind: &str, fields: impl FnOnce() -> Value) {
+    TRACING.with(|tracing| {
+        if let Some(sink) = tracing.borrow_mut().as_mut() {
+            if level <= sink.verbosity {
+                (sink.write)(&Event::new(kind, level, fields()));
+            }
+        }
+    });
+}
+
+/// Runs `f`, handing every event it traces at `verbosity` or below to `write`. Tracing inside `f`
+/// replaces this one until it finishes.
+pub fn trace<T>(verbosity: Level, write: impl FnMut(&Event) + 'static, f: impl FnOnce() -> T) -> T {
+    let outer = TRACING.with(|tracing| tracing.replace(Some(Sink { verbosity, write: Box::new(write) })));
+    let result = f();
+    TRACING.with(|tracing| tracing.replace(outer));
+    result
+}
+
+/// Runs `f`, returning the events it traced at `verbosity` or below along with its result.
+pub fn record<T>(verbosity: Level, f: impl FnOnce() -> T) -> (T, Vec<Event>) {
+    let events = Rc::new(RefCell::new(vec![]));
+    let sink = Rc::clone(&events);
+    let result = trace(verbosity, move |event| sink.borrow_mut().push(event.clone()), f);
+    let events = events.take();
+    (result, events)
+}
+
+/// Reads a JSON lines log, skipping blank lines, calling `each` with every event at `verbosity` or
+/// below.
+pub fn replay(input: impl BufRead, verbosity: Level, mut each: impl FnMut(&Event) -> Result<()>) -> Result<()> {
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event: Event = serde_json::from_str(&line).with_context(|| format!("line {} of the log", i + 1))?;
+        if event.level <= verbosity {
+            each(&event)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn records() {
+        event(Level::Summary, "ignored", || json!({}));
+        let ((), events) = record(Level::Step, || {
+            assert!(enabled(Level::Step) && !enabled(Level::Detail));
+            event(Level::Step, "throw", || json!({ "monkey": 0, "worry": 500, "to": 3 }));
+            event(Level::Detail, "fall", || panic!("Detail events aren't made when tracing steps"));
+            let ((), inner) = record(Level::Summary, || event(Level::Summary, "inner", || json!({ "n": 1 })));
+            assert_eq!(1, inner.len());
+        });
+        assert!(!enabled(Level::Summary));
+        assert_eq!(vec![Event::new("throw", Level::Step, json!({ "monkey": 0, "worry": 500, "to": 3 }))], events);
+        assert_eq!(r#"{"kind":"throw","level":"step","monkey":0,"to":3,"worry":500}"#, serde_json::to_string(&events[0]).unwrap());
+    }
+
+    #[test]
+    fn replays() -> Result<()> {
+        let log = r#"{"kind":"round","level":"summary","round":1,"inspected":[2,4]}
+
+{"kind":"rest","level":"step","unit":1,"at":{"x":500,"y":8}}
+{"kind":"fall","level":"detail","to":{"x":500,"y":1}}
+{"kind":"new","level":"step","b":"two","a":[1,2]}
+"#;
+        let mut out = vec![];
+        replay(log.as_bytes(), Level::Step, |event| {
+            out.push(event.to_string());
+            Ok(())
+        })?;
+        let expected = [
+            "== After round 1 ==\nMonkey 0 inspected items 2 times.\nMonkey 1 inspected items 4 times.",
+            "Sand unit 1 comes to rest at 500,8",
+            "new a=1, 2 b=two",
+        ];
+        assert_eq!(expected.to_vec(), out);
+        let e = replay("{\"kind\":\"x\"}\n".as_bytes(), Level::Step, |_| Ok(())).unwrap_err();
+        assert!(format!("{:#}", e).starts_with("line 1 of the log: missing field `level`"), "{:#}", e);
+        assert!("loud".parse::<Level>().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+//! Drawing the intermediate states of a simulation as a series of frames, either as text to the
+//! terminal or as numbered PPM images.
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::geometry::Bounds;
+use crate::grid::Grid;
+
+/// A colour as red, green and blue.
+pub type Rgb = [u8; 3];
+
+/// The colour of each kind of cell in image output.
+pub type Palette = fn(char) -> Rgb;
+
+/// Black for empty cells ('.' or ' ') and white for anything else.
+pub fn default_palette(cell: char) -> Rgb {
+    match cell {
+        '.' | ' ' => [0, 0, 0],
+        _ => [255, 255, 255],
+    }
+}
+
+/// One state of a simulation, drawn with a character per cell.
+pub struct Frame {
+    /// What is happening, shown above text frames.
+    pub caption: String,
+    pub cells: Grid<char>,
+    pub palette: Palette,
+}
+
+impl Frame {
+    pub fn new(caption: impl Into<String>, cells: Grid<char>) -> Self {
+        Frame { caption: caption.into(), cells, palette: default_palette }
+    }
+
+    pub fn with_palette(self, palette: Palette) -> Self {
+        Frame { palette, ..self }
+    }
+}
+
+/// Where frames are drawn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// As text, each frame after a caption line. With a delay the screen is cleared before each
+    /// frame, so that they play as an animation.
+    Text { delay: Option<Duration> },
+    /// As `frame000000.ppm` onwards in a directory, with each cell `scale` pixels square.
+    Ppm { dir: PathBuf, scale: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Draws every `every`th frame, starting with the first, and skips the rest.
+    pub every: usize,
+    /// Draws only the cells within this region of each frame.
+    pub region: Option<Bounds<usize>>,
+    pub target: Target,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options { every: 1, region: None, target: Target::Text { delay: None } }
+    }
+}
+
+/// Receives a simulation's frames and draws the ones the options ask for.
+pub struct Frames<'a> {
+    options: Options,
+    /// Where text frames are written.
+    out: &'a mut dyn Write,
+    seen: usize,
+    drawn: usize,
+}
+
+impl<'a> Frames<'a> {
+    pub fn new(options: Options, out: &'a mut dyn Write) -> Result<Self> {
+        if options.every == 0 {
+            Err(anyhow!("Can't draw every 0th frame"))?;
+        }
+        if let Target::Ppm { dir, .. } = &options.target {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        Ok(Frames { options, out, seen: 0, drawn: 0 })
+    }
+
+    /// Adds the next frame. `draw` is only called if the frame will be drawn, as simulations can
+    /// have far more frames than are worth drawing.
+    pub fn frame(&mut self, draw: impl FnOnce() -> Frame) -> Result<()> {
+        let index = self.seen;
+        self.seen += 1;
+        if !index.is_multiple_of(self.options.every) {
+            return Ok(());
+        }
+
+        let mut frame = draw();
+        if let Some(region) = self.options.region {
+            frame.cells = frame.cells.crop(region).ok_or_else(|| {
+                anyhow!("The region {}-{} is outside the {}x{} frame", region.min, region.max, frame.cells.width(), frame.cells.height())
+            })?;
+        }
+        match &self.options.target {
+            Target::Text { delay } => {
+                if delay.is_some() {
+                    write!(self.out, "\x1b[2J\x1b[H")?;
+                }
+                write!(self.out, "{}\n{}\n", frame.caption, frame.cells)?;
+                self.out.flush()?;
+                if let Some(delay) = delay {
+                    thread::sleep(*delay);
+                }
+            }
+            Target::Ppm { dir, scale } => {
+                let file = dir.join(format!("frame{:06}.ppm", self.drawn));
+                write_ppm(&frame, *scale, &mut BufWriter::new(File::create(&file)?)).with_context(|| format!("writing {}", file.display()))?;
+            }
+        }
+        self.drawn += 1;
+        Ok(())
+    }
+
+    /// How many frames the simulation has produced.
+    pub fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// How many of those frames were drawn.
+    pub fn drawn(&self) -> usize {
+        self.drawn
+    }
+}
+
+/// Writes a frame as a binary PPM image.
+fn write_ppm(frame: &Frame, scale: usize, out: &mut impl Write) -> Result<()> {
+    let scale = scale.max(1);
+    let cells = &frame.cells;
+    write!(out, "P6\n{} {}\n255\n", cells.width() * scale, cells.height() * scale)?;
+    for y in 0..cells.height() {
+        let row: Vec<u8> = cells.row(y).iter().flat_map(|&c| (frame.palette)(c).repeat(scale)).collect();
+        for _ in 0..scale {
+            out.write_all(&row)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// A simulation that can show its intermediate states.
+pub trait Visualize {
+    /// Runs a part's simulation, adding a frame to `frames` for each step.
+    fn visualize(&self, part: u8, frames: &mut Frames) -> Result<()>;
+}
+
+/// Every frame of a part as text, for tests.
+#[cfg(test)]
+pub fn text_frames(simulation: &dyn Visualize, part: u8) -> Result<Vec<String>> {
+    let mut out = vec![];
+    simulation.visualize(part, &mut Frames::new(Options::default(), &mut out)?)?;
+    Ok(String::from_utf8(out)?.split_terminator("\n\n").map(|frame| frame.to_string() + "\n").collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::Point;
+
+    use super::*;
+
+    /// Counts to `size`, drawing each number as a bar of '#'.
+    struct Counter {
+        size: usize,
+    }
+
+    impl Visualize for Counter {
+        fn visualize(&self, _part: u8, frames: &mut Frames) -> Result<()> {
+            for n in 0..=self.size {
+                frames.frame(|| {
+                    let mut cells = Grid::new(self.size, 1, '.');
+                    (0..n).for_each(|x| cells[Point::new(x, 0)] = '#');
+                    Frame::new(n.to_string(), cells)
+                })?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn text() -> Result<()> {
+        assert_eq!(vec!["0\n...\n", "1\n#..\n", "2\n##.\n", "3\n###\n"], text_frames(&Counter { size: 3 }, 1)?);
+
+        let mut out = vec![];
+        let options = Options { every: 2, region: Some(Bounds { min: Point::new(1, 0), max: Point::new(9, 0) }), ..Options::default() };
+        let mut frames = Frames::new(options, &mut out)?;
+        Counter { size: 4 }.visualize(1, &mut frames)?;
+        assert_eq!((5, 3), (frames.seen(), frames.drawn()));
+        assert_eq!("0\n...\n\n2\n#..\n\n4\n###\n\n", String::from_utf8(out)?);
+
+        let options = Options { region: Some(Bounds::new(Point::new(5, 0))), ..Options::default() };
+        assert!(Counter { size: 4 }.visualize(1, &mut Frames::new(options, &mut vec![])?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn ppm() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("advent2022-ppm-{}", std::process::id()));
+        let options = Options { target: Target::Ppm { dir: dir.clone(), scale: 2 }, ..Options::default() };
+        Counter { size: 3 }.visualize(1, &mut Frames::new(options, &mut vec![])?)?;
+        let image = fs::read(dir.join("frame000001.ppm"))?;
+        let header = b"P6\n6 2\n255\n";
+        assert_eq!(header, &image[..header.len()]);
+        assert_eq!(header.len() + 6 * 2 * 3, image.len());
+        // The first cell is drawn, the rest are empty, in both rows of pixels.
+        assert_eq!(&[255; 6], &image[header.len()..header.len() + 6]);
+        assert_eq!(&[0; 12], &image[header.len() + 6..header.len() + 18]);
+        assert!(!dir.join("frame000004.ppm").exists());
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
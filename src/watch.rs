@@ -0,0 +1,218 @@
+//! Re-running days when their input files change, reporting how the answers moved.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use notify::{EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::answers::single_line;
+use crate::open;
+use crate::output::{self, DayOutput, PartOutput};
+use crate::params::Changes;
+use crate::Day;
+
+/// How long to wait after a change for more, as editors often save a file in several steps.
+const SETTLE: Duration = Duration::from_millis(100);
+
+/// An input file being watched, with what running its day on it last gave.
+struct Watched {
+    day: &'static Day,
+    input: String,
+    path: PathBuf,
+    changes: Changes,
+    last: Option<DayOutput>,
+}
+
+impl Watched {
+    fn run(&self) -> DayOutput {
+        match open(&self.input) {
+            Ok(mut reader) => output::run_day(self.day, &mut reader, &self.input, &self.changes, &[1, 2], false),
+            Err(e) => DayOutput { day: self.day.number, input: self.input.clone(), error: Some(format!("{:#}", e)), parts: vec![], model: None },
+        }
+    }
+}
+
+/// How one part's result compares with the previous run's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartChange {
+    pub part: u8,
+    /// The answer or error from the previous run, if the part ran then.
+    pub before: Option<String>,
+    pub after: String,
+}
+
+impl PartChange {
+    pub fn is_changed(&self) -> bool {
+        self.before.as_ref() != Some(&self.after)
+    }
+}
+
+impl fmt::Display for PartChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.before {
+            Some(before) if *before == self.after => write!(f, "part {}: {} (unchanged)", self.part, single_line(&self.after)),
+            Some(before) => write!(f, "part {}: {} -> {}", self.part, single_line(before), single_line(&self.after)),
+            None => write!(f, "part {}: {}", self.part, single_line(&self.after)),
+        }
+    }
+}
+
+/// The answer a part gave, or its error, or the parse error that stopped it running.
+fn result(output: &DayOutput, part: u8) -> Option<String> {
+    if let Some(e) = &output.error {
+        return Some(format!("ERROR, {}", e));
+    }
+    output.parts.iter().find(|p| p.part == part).map(|PartOutput { answer, error, .. }| match (answer, error) {
+        (Some(answer), _) => answer.to_string().trim_end().to_string(),
+        (None, e) => format!("ERROR, {}", e.as_deref().unwrap_or("no answer")),
+    })
+}
+
+/// Compares both parts of a run with the previous run on the same input.
+pub fn compare(before: Option<&DayOutput>, after: &DayOutput) -> Vec<PartChange> {
+    [1, 2]
+        .into_iter()
+        .filter_map(|part| Some(PartChange { part, before: before.and_then(|b| result(b, part)), after: result(after, part)? }))
+        .collect()
+}
+
+/// A rerun of one day on one input after its file changed.
+pub struct Rerun {
+    pub day: u8,
+    pub input: String,
+    pub changes: Vec<PartChange>,
+}
+
+/// The days and inputs being watched.
+#[derive(Default)]
+pub struct Watch {
+    watched: Vec<Watched>,
+}
+
+impl Watch {
+    pub fn new() -> Self {
+        Watch::default()
+    }
+
+    /// Watches a day's input file, which must exist, running with some parameters changed.
+    pub fn add(&mut self, day: &'static Day, input: &str, changes: Changes) -> Result<()> {
+        let path = Path::new(input).canonicalize().with_context(|| format!("watching {}", input))?;
+        self.watched.push(Watched { day, input: input.to_string(), path, changes, last: None });
+        Ok(())
+    }
+
+    /// The files being watched, without repeats.
+    pub fn paths(&self) -> BTreeSet<PathBuf> {
+        self.watched.iter().map(|w| w.path.clone()).collect()
+    }
+
+    /// Runs the days watching any of `changed` files (or every one, given `None`) and compares
+    /// them with their previous runs.
+    pub fn run(&mut self, changed: Option<&BTreeSet<PathBuf>>) -> Vec<Rerun> {
+        let mut reruns = vec![];
+        for watched in &mut self.watched {
+            if changed.is_some_and(|changed| !changed.contains(&watched.path)) {
+                continue;
+            }
+            let output = watched.run();
+            reruns.push(Rerun { day: watched.day.number, input: watched.input.clone(), changes: compare(watched.last.as_ref(), &output) });
+            watched.last = Some(output);
+        }
+        reruns
+    }
+}
+
+/// Waits for any of `paths` to change, calling `changed` with the ones that did until it returns
+/// false. Filesystem notifications are used where the platform has them, otherwise (or given a
+/// `poll` interval) the files are checked on a timer.
+///
+/// The directories holding the files are watched rather than the files themselves, as editors
+/// often save by replacing a file, which would end a watch on the old one.
+pub fn watch_files(paths: &BTreeSet<PathBuf>, poll: Option<Duration>, mut changed: impl FnMut(&BTreeSet<PathBuf>) -> Result<bool>) -> Result<()> {
+    let (sender, events) = mpsc::channel();
+    let mut watcher: Box<dyn Watcher> = match poll {
+        None => match RecommendedWatcher::new(sender.clone(), notify::Config::default()) {
+            Ok(watcher) => Box::new(watcher),
+            Err(_) => Box::new(PollWatcher::new(sender, poll_config(Duration::from_secs(1)))?),
+        },
+        Some(interval) => Box::new(PollWatcher::new(sender, poll_config(interval))?),
+    };
+    let dirs: BTreeSet<&Path> = paths.iter().filter_map(|p| p.parent()).collect();
+    for dir in dirs {
+        watcher.watch(dir, RecursiveMode::NonRecursive).with_context(|| format!("watching {}", dir.display()))?;
+    }
+
+    loop {
+        let mut batch = BTreeSet::new();
+        let mut wait = None;
+        loop {
+            let event = match wait {
+                None => events.recv().map_err(|_| anyhow!("Stopped receiving file changes"))?,
+                Some(wait) => match events.recv_timeout(wait) {
+                    Ok(event) => event,
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => Err(anyhow!("Stopped receiving file changes"))?,
+                },
+            };
+            let event = event?;
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                batch.extend(event.paths.into_iter().filter(|p| paths.contains(p)));
+            }
+            if !batch.is_empty() {
+                wait = Some(SETTLE);
+            }
+        }
+        if !changed(&batch)? {
+            return Ok(());
+        }
+    }
+}
+
+fn poll_config(interval: Duration) -> notify::Config {
+    // Comparing contents catches changes made within the file system's timestamp resolution.
+    notify::Config::default().with_poll_interval(interval).with_compare_contents(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn reruns() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("advent2022-watch-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let input = dir.join("day01.txt");
+        fs::write(&input, "1\n2\n\n3\n\n1\n")?;
+
+        let mut watch = Watch::new();
+        watch.add(crate::day(1).unwrap(), &input.display().to_string(), Changes::new())?;
+        let reruns = watch.run(None);
+        assert_eq!("part 1: 3", reruns[0].changes[0].to_string());
+        assert!(watch.run(Some(&BTreeSet::new())).is_empty());
+
+        // Poll quickly, change the file once the watch has started, and stop after the first change.
+        let paths = watch.paths();
+        let writer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(300));
+            fs::write(&input, "1\n2\n\n4\n\n1\n").unwrap();
+        });
+        let mut reruns = vec![];
+        watch_files(&paths, Some(Duration::from_millis(20)), |changed| {
+            reruns = watch.run(Some(changed));
+            Ok(false)
+        })?;
+        writer.join().unwrap();
+        assert_eq!(vec!["part 1: 3 -> 4", "part 2: 7 -> 8"], reruns[0].changes.iter().map(|c| c.to_string()).collect::<Vec<_>>());
+        assert!(reruns[0].changes.iter().all(PartChange::is_changed));
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}